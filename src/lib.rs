@@ -13,34 +13,40 @@ pub mod simcore;
 
 pub mod prelude {
     pub use crate::MakeSigList;
-    pub use crate::simcore::{sim_model, sim_system, sim_signal, sim_common};
+    pub use crate::simcore::{sim_model, sim_system, sim_signal, sim_common, sim_analysis};
 
     // models
     pub use sim_model::{
         // コントローラモデル
-        controller_models::PIDController,
+        controller_models::{PIDController, PIDControllerMIMO, DiscretePID, SchmittTrigger},
         // 常微分方程式モデル
-        de_models::{SolverType, DEModel, SpaceStateModel, TransFuncModel, Integrator},
+        de_models::{SolverType, SolverConfig, DiscretizationMethod, DEModel, SpaceStateModel, TransFuncModel, Integrator, StabilityMargins},
         // モデルの共通トレイト
-        model_core::{connect_models, ModelCore},
+        model_core::{connect_models, connect_models_auto, connect_models_unchecked, ModelCore},
         // サンプルモデル
         sample_models::{BallAndBeam, make_rlc_circuit_model},
         // シンクモデル
-        sink_models::SimRecorder,
+        sink_models::{SimRecorder, SignalStats, EventRecorder, TriggerEdge},
         // ソースモデル
-        source_models::{ConstantFunc, StepFunc, RampFunc, WaveFunc, WaveFuncType},
+        source_models::{ConstantFunc, StepFunc, RampFunc, RampToTarget, RampToTargetSetting, WaveFunc, WaveFuncType, ClockFunc, PolynomialFunc, LookupFunc, OutOfRangeMode, NoiseFunc, NoiseType, NoiseSetting},
         // サブシステム
         subsystem::SubSystem,
+        // 非理想要素モデル
+        nonideal_models::{SensorModel, SensorSetting, ActuatorModel, ActuatorSetting},
+        closure_models::ClosureModel,
+        // 非線形要素モデル
+        nonlinear_models::{Saturation, SoftSaturation, DeadZone},
+        basic_models::{TimeDelay, UnitDelay, RateLimiter, DifferenceEquation, DiscreteTransFunc, MovingAverage, EnergyMeter, Gain, FrequencyEstimator},
         // 機械モデル
-        mechanical_model::{mass_models::MassModel, spring_models::SimpleSpring}
+        mechanical_model::{ForceCheckable, mass_models::MassModel, spring_models::SimpleSpring}
     };
 
     // 信号定義
-    pub use sim_signal::{signal::SigDef};
+    pub use sim_signal::{signal::{SigDef, SigDefList}};
     // バス定義
     pub use sim_signal::bus::{Bus, RefBus};
     // システム
-    pub use sim_system::SimSystem;
+    pub use sim_system::{SimSystem, RunState, DivergenceMonitor, FailurePolicy, ModelFailure, SignalChange, Probe, SimManifest, ConvergenceStudy, convergence_study};
 }
 
 #[cfg(test)]
@@ -1,6 +1,7 @@
-
 /// mintsim共通で使う信号とそれをまとめるsignalbusの定義
 pub mod sim_signal;
 pub mod sim_model;
 pub mod sim_system;
 pub mod sim_common;
+pub mod sim_optimize;
+pub mod sim_analysis;
@@ -0,0 +1,246 @@
+/// # 制御系解析
+/// 伝達関数モデルを用いた制御系の解析・可視化を行う
+///
+/// - ナイキスト線図の出力
+/// - 根軌跡の計算と出力
+/// - 極・零点（根）からの多項式の作成（複素根の共役対の検証）
+use anyhow::{anyhow};
+
+extern crate nalgebra as na;
+use na::{DMatrix, Complex};
+
+use plotters::prelude::*;
+
+use super::sim_model::de_models::TransFuncModel;
+
+/// ナイキスト線図をPNGファイルに出力する
+/// freqs[rad/s]の各角周波数で周波数応答を計算し、ω > 0の軌跡（赤）とその共役（ω < 0、青）を描画する。
+/// 安定判別の基準となる-1の点を黒で示す。ゲインが発散する周波数（原点の極など）は描画しない
+pub fn nyquist_plot(tf: &TransFuncModel, freqs: &[f64], filename: &str) -> anyhow::Result<()> {
+    let points = freqs.iter()
+                      .map(|w| tf.freq_response(*w))
+                      .filter(|g| g.re.is_finite() && g.im.is_finite())
+                      .map(|g| (g.re, g.im))
+                      .collect::<Vec<(f64, f64)>>();
+    if points.is_empty() {
+        return Err(anyhow!("描画できる周波数応答がありません。"));
+    }
+
+    // 軌跡と-1の点が収まる範囲（虚軸は共役を含めて対称にする）
+    let (x_min, x_max, y_abs) = points.iter().fold((-1.0f64, 0.0f64, 0.0f64), |(x0, x1, y), (re, im)| (x0.min(*re), x1.max(*re), y.max(im.abs())));
+    let margin_x = (x_max - x_min) * 0.05 + 1e-3;
+    let margin_y = y_abs * 0.05 + 1e-3;
+
+    let root_area = BitMapBackend::new(filename, (600, 600)).into_drawing_area();
+    root_area.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Nyquist", ("sans-serif", 20).into_font())
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d((x_min - margin_x)..(x_max + margin_x), (-y_abs - margin_y)..(y_abs + margin_y))?;
+
+    chart.configure_mesh().x_desc("Re").y_desc("Im").draw()?;
+
+    chart.draw_series(LineSeries::new(points.iter().copied(), &RED))?;
+    chart.draw_series(LineSeries::new(points.iter().map(|(re, im)| (*re, -*im)), &BLUE))?;
+    chart.draw_series(std::iter::once(Cross::new((-1.0, 0.0), 6, BLACK.stroke_width(2))))?;
+
+    root_area.present()?;
+
+    Ok(())
+}
+
+/// 多項式（係数は高次から）の根を求める
+/// コンパニオン行列の固有値として計算する。最高次の係数が0の項は取り除く
+pub fn polynomial_roots(coef: &[f64]) -> Vec<Complex<f64>> {
+    let coef = match coef.iter().position(|c| *c != 0.0) {
+        Some(idx) => &coef[idx..],
+        None => return Vec::new(),
+    };
+    let n = coef.len() - 1;
+    if n == 0 {
+        return Vec::new();
+    }
+
+    // コンパニオン行列（1行目に -a_{n-1}/a_n ... -a_0/a_n、その下に単位行列）
+    let mut companion = DMatrix::from_element(n, n, 0.0);
+    for c in 0..n {
+        companion[(0, c)] = -coef[c + 1] / coef[0];
+    }
+    for r in 1..n {
+        companion[(r, r - 1)] = 1.0;
+    }
+
+    companion.complex_eigenvalues().iter().copied().collect()
+}
+
+/// 複素根が共役対になっているかを検証する
+/// 虚部の絶対値が tol * max(1, |r|) 以下の根は実根とみなし、それ以外の根は共役との差が同じ許容値以内の根と1対1で対応付ける
+/// 全ての複素根が共役対を持つ場合のみ、根から作る多項式の係数は実数になる（対にならない根がある場合はその根を示してエラーとする）
+pub fn check_conjugate_pairs(roots: &[Complex<f64>], tol: f64) -> anyhow::Result<()> {
+    let valid = tol >= 0.0 && tol.is_finite();
+    if !valid {
+        return Err(anyhow!("許容誤差は0以上の有限値で設定してください。tol = {}", tol));
+    }
+    if let Some(r) = roots.iter().find(|r| !(r.re.is_finite() && r.im.is_finite())) {
+        return Err(anyhow!("有限でない根があります。: {}", r));
+    }
+
+    let limit = |r: &Complex<f64>| tol * r.norm().max(1.0);
+    let mut lower = roots.iter().filter(|r| r.im < -limit(r)).collect::<Vec<&Complex<f64>>>(); // 対になっていない虚部が負の根
+    for r in roots.iter().filter(|r| r.im > limit(r)) {
+        // 共役に最も近い虚部が負の根と対にする
+        let nearest = lower.iter().enumerate()
+                           .map(|(i, q)| (i, (r.conj() - *q).norm()))
+                           .min_by(|a, b| a.1.total_cmp(&b.1));
+        match nearest {
+            Some((i, dist)) if dist <= limit(r) => { lower.swap_remove(i); },
+            _ => return Err(anyhow!("複素根{}と共役対になる根がありません（許容誤差{}）。複素根は共役対で指定してください。", r, tol)),
+        }
+    }
+    if let Some(q) = lower.first() {
+        return Err(anyhow!("複素根{}と共役対になる根がありません（許容誤差{}）。複素根は共役対で指定してください。", q, tol));
+    }
+
+    Ok(())
+}
+
+/// 根（極・零点）から最高次の係数が1の多項式の係数（高次から）を求める（polynomial_rootsの逆）
+/// 複素根はcheck_conjugate_pairsで共役対になっていることを検証し、対になっていない場合はエラーとする
+/// 許容誤差内の共役のずれにより生じる係数の虚部は切り捨てる
+pub fn polynomial_from_roots(roots: &[Complex<f64>], tol: f64) -> anyhow::Result<Vec<f64>> {
+    check_conjugate_pairs(roots, tol)?;
+
+    let coef = roots.iter().fold(vec![Complex::new(1.0, 0.0)], |acc, r| {
+        // (acc) * (s - r)
+        let mut next = acc.clone();
+        next.push(Complex::new(0.0, 0.0));
+        acc.iter().enumerate().for_each(|(i, c)| next[i + 1] -= c * r);
+        next
+    });
+
+    Ok(coef.iter().map(|c| c.re).collect())
+}
+
+/// 根軌跡を計算する
+/// 開ループ伝達関数G(s) = num / den にゲインkのフィードバックを掛けた閉ループ系の極（den + k * num = 0 の根）を
+/// gainsの各ゲインについて求め、(ゲイン, 極の一覧)を返す
+pub fn root_locus(plant_tf: &TransFuncModel, gains: &[f64]) -> Vec<(f64, Vec<Complex<f64>>)> {
+    let den = plant_tf.den();
+    let num = plant_tf.num();
+    let offset = den.len() - num.len(); // 分子の係数を分母の次数に揃える
+
+    gains.iter().map(|k| {
+        let mut coef = den.to_vec();
+        num.iter().enumerate().for_each(|(i, b)| coef[i + offset] += k * b);
+        (*k, polynomial_roots(&coef))
+    }).collect()
+}
+
+/// 根軌跡をPNGファイルに出力する
+/// 閉ループ極を赤の点で、開ループ極（先頭のゲインでの極）を黒の×で示す
+pub fn root_locus_plot(locus: &[(f64, Vec<Complex<f64>>)], filename: &str) -> anyhow::Result<()> {
+    let poles = locus.iter().flat_map(|(_k, p)| p.iter()).filter(|p| p.re.is_finite() && p.im.is_finite()).collect::<Vec<_>>();
+    if poles.is_empty() {
+        return Err(anyhow!("描画できる極がありません。"));
+    }
+
+    let (x_min, x_max, y_abs) = poles.iter().fold((0.0f64, 0.0f64, 0.0f64), |(x0, x1, y), p| (x0.min(p.re), x1.max(p.re), y.max(p.im.abs())));
+    let margin_x = (x_max - x_min) * 0.05 + 1e-3;
+    let margin_y = y_abs * 0.05 + 1e-3;
+
+    let root_area = BitMapBackend::new(filename, (600, 600)).into_drawing_area();
+    root_area.fill(&WHITE)?;
+
+    let mut chart = ChartBuilder::on(&root_area)
+        .caption("Root Locus", ("sans-serif", 20).into_font())
+        .margin(10)
+        .x_label_area_size(30)
+        .y_label_area_size(50)
+        .build_cartesian_2d((x_min - margin_x)..(x_max + margin_x), (-y_abs - margin_y)..(y_abs + margin_y))?;
+
+    chart.configure_mesh().x_desc("Re").y_desc("Im").draw()?;
+
+    chart.draw_series(poles.iter().map(|p| Circle::new((p.re, p.im), 2, RED.filled())))?;
+    chart.draw_series(locus[0].1.iter().map(|p| Cross::new((p.re, p.im), 6, BLACK.stroke_width(2))))?;
+
+    root_area.present()?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod analysis_test {
+    use super::*;
+    use crate::simcore::sim_model::de_models::SolverType;
+    use crate::simcore::sim_signal::signal::SigDef;
+
+    #[test]
+    fn nyquist_plot_test() {
+        let tf = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[1.0, 3.0, 2.0, 0.0], SolverType::Euler).unwrap();
+        let freqs = (0..400).map(|k| 10f64.powf(-2.0 + k as f64 / 100.0)).collect::<Vec<f64>>();
+
+        nyquist_plot(&tf, &freqs, "test_output\\nyquist.png").unwrap();
+        assert!(nyquist_plot(&tf, &[0.0], "test_output\\nyquist_err.png").is_err()); // 原点の極でゲインが発散する
+    }
+
+    #[test]
+    fn polynomial_roots_test() {
+        // (s + 1)(s + 2)(s^2 + 4) = s^4 + 3s^3 + 6s^2 + 12s + 8
+        let mut roots = polynomial_roots(&[0.0, 1.0, 3.0, 6.0, 12.0, 8.0]);
+        roots.sort_by(|a, b| a.re.partial_cmp(&b.re).unwrap().then(a.im.partial_cmp(&b.im).unwrap()));
+        let expected = [Complex::new(-2.0, 0.0), Complex::new(-1.0, 0.0), Complex::new(0.0, -2.0), Complex::new(0.0, 2.0)];
+        assert_eq!(roots.len(), 4);
+        roots.iter().zip(expected.iter()).for_each(|(r, e)| assert!((r - e).norm() < 1e-9));
+
+        assert!(polynomial_roots(&[3.0]).is_empty());
+    }
+
+    #[test]
+    fn polynomial_from_roots_test() {
+        // (s + 1)(s + 2)(s^2 + 4) = s^4 + 3s^3 + 6s^2 + 12s + 8
+        let roots = [Complex::new(-1.0, 0.0), Complex::new(0.0, 2.0), Complex::new(-2.0, 0.0), Complex::new(0.0, -2.0)];
+        let coef = polynomial_from_roots(&roots, 1e-9).unwrap();
+        coef.iter().zip([1.0, 3.0, 6.0, 12.0, 8.0].iter()).for_each(|(c, e)| assert!((c - e).abs() < 1e-12));
+        assert_eq!(coef.len(), 5);
+
+        // polynomial_rootsで求めた根（丸め誤差を含む共役対）から元の多項式に戻る
+        let coef = polynomial_from_roots(&polynomial_roots(&[2.0, 2.0, 5.0, 4.0]), 1e-9).unwrap();
+        coef.iter().zip([1.0, 1.0, 2.5, 2.0].iter()).for_each(|(c, e)| assert!((c - e).abs() < 1e-9));
+
+        assert_eq!(polynomial_from_roots(&[], 1e-9).unwrap(), vec![1.0]);
+
+        // 共役対にならない複素根はエラー
+        let err = polynomial_from_roots(&[Complex::new(-1.0, 1.0), Complex::new(-1.0, -2.0)], 1e-9).unwrap_err().to_string();
+        assert!(err.contains("共役対になる根がありません"), "{}", err);
+        assert!(check_conjugate_pairs(&[Complex::new(-1.0, 1.0)], 1e-9).is_err());
+        assert!(check_conjugate_pairs(&[Complex::new(-1.0, -1.0)], 1e-9).is_err());
+        assert!(check_conjugate_pairs(&[Complex::new(-1.0, 1.0), Complex::new(-1.0, 1.0)], 1e-9).is_err()); // 同じ根の重複は共役対ではない
+
+        // 許容誤差以内のずれは共役対・実根とみなす
+        assert!(check_conjugate_pairs(&[Complex::new(-1.0, 1.0), Complex::new(-1.0 + 1e-12, -1.0)], 1e-9).is_ok());
+        assert!(check_conjugate_pairs(&[Complex::new(-3.0, 1e-12)], 1e-9).is_ok());
+        assert!(check_conjugate_pairs(&[Complex::new(-3.0, 0.0)], -1.0).is_err());
+    }
+
+    #[test]
+    fn root_locus_test() {
+        // G(s) = 1 / (s(s + 2))：閉ループ極は s^2 + 2s + k = 0 の根。k > 1で虚部 ±√(k - 1) を持つ
+        let tf = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[1.0, 2.0, 0.0], SolverType::Euler).unwrap();
+        let gains = (0..=50).map(|k| k as f64 * 0.2).collect::<Vec<f64>>();
+        let locus = root_locus(&tf, &gains);
+
+        assert_eq!(locus.len(), gains.len());
+        for (k, poles) in locus.iter() {
+            assert_eq!(poles.len(), 2);
+            poles.iter().for_each(|p| assert!((p * p + 2.0 * p + k).norm() < 1e-9));
+            if *k > 1.0 {
+                poles.iter().for_each(|p| assert!((p.re + 1.0).abs() < 1e-9 && (p.im.abs() - (k - 1.0).sqrt()).abs() < 1e-9));
+            }
+        }
+
+        root_locus_plot(&locus, "test_output\\root_locus.png").unwrap();
+    }
+}
@@ -1,5 +1,5 @@
 pub mod model_core;
-//pub mod basic_models;
+pub mod basic_models;
 pub mod de_models;
 pub mod sink_models;
 pub mod source_models;
@@ -8,4 +8,7 @@ pub mod sample_models;
 pub mod subsystem;
 pub mod test_models;
 pub mod mechanical_model;
+pub mod nonideal_models;
+pub mod closure_models;
+pub mod nonlinear_models;
 
@@ -1,11 +1,18 @@
-// # basicモデル
-// basicモデルには、下記のモデルを実装する
-// 
-// - step delayモデル
-// - time delayモデル
-// - unit delayモデル
-
-/* 
+/// # basicモデル
+/// basicモデルには、下記のモデルを実装する
+///
+/// - step delayモデル
+/// - time delayモデル
+/// - unit delayモデル
+/// - レートリミッタ（変化率制限）モデル
+/// - 差分方程式モデル
+/// - 離散伝達関数モデル
+/// - 移動平均モデル
+/// - エネルギーメータ（電力・仕事率の時間積分）
+/// - ゲイン（行列）モデル
+/// - 周波数応答推定モデル
+use anyhow::{anyhow, Context};
+
 use super::model_core::{ModelCore};
 
 use super::super::sim_signal;
@@ -15,4 +22,1203 @@ use sim_signal::bus::{Bus, RefBus};
 
 use super::super::sim_system;
 use sim_system::SimTime;
-*/
\ No newline at end of file
+
+extern crate nalgebra as na;
+use na::{DMatrix, Complex};
+
+use std::f64::consts::PI;
+use std::collections::VecDeque;
+
+/// 遅延ステップ数が整数とみなせる誤差（相対値）
+const DELAY_STEP_EPS: f64 = 1e-9;
+
+/// むだ時間（time delay）モデル
+/// 入力をdelay[s]遅らせて出力する。遅延時間が刻み幅の整数倍でない場合は前後のステップの値を線形補間する
+/// 遅延時間より前（シミュレーション開始直後）は初期値を出力する。入力と出力は要素ごとに対応する
+#[derive(Debug)]
+pub struct TimeDelay {
+    input_bus: RefBus,
+    output_bus: Bus,
+    delay: f64,          // 遅延時間[s]
+    init_value: f64,     // 遅延時間が経過するまでの出力
+    buffer: Vec<Vec<f64>>, // 入力の履歴（リングバッファ）
+    head: usize,         // 最新の入力を格納した位置
+    delay_steps: usize,  // 遅延ステップ数の整数部
+    frac: f64,           // 遅延ステップ数の小数部（補間の重み）
+}
+
+impl TimeDelay {
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, delay: f64, init_value: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("TimeDelayの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("TimeDelayの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("TimeDelay: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if !delay.is_finite() || delay < 0.0 {
+            return Err(anyhow!("TimeDelay: 遅延時間は0以上の有限値で設定してください。delay = {}", delay));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            delay,
+            init_value,
+            buffer: Vec::new(),
+            head: 0,
+            delay_steps: 0,
+            frac: 0.0,
+        })
+    }
+
+    /// mステップ前の入力（m = 0が最新）
+    fn past(&self, m: usize) -> &[f64] {
+        let len = self.buffer.len();
+        &self.buffer[(self.head + len - m) % len]
+    }
+}
+
+impl ModelCore for TimeDelay {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        // 遅延ステップ数（整数に十分近い値は丸めて補間しない）
+        let steps = self.delay / sim_time.delta_t().abs();
+        let rounded = steps.round();
+        let steps = if (steps - rounded).abs() <= DELAY_STEP_EPS * rounded.max(1.0) { rounded } else { steps };
+        self.delay_steps = steps.floor() as usize;
+        self.frac = steps - steps.floor();
+
+        // 補間用に1つ多く保持する
+        let elemnum = self.input_bus.len();
+        self.buffer = vec![vec![self.init_value; elemnum]; self.delay_steps + 2];
+        self.head = 0;
+        self.output_bus.set_all(self.init_value);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        self.head = (self.head + 1) % self.buffer.len();
+        let input = self.input_bus.to_vec_f64();
+        self.buffer[self.head] = input;
+
+        let (k, frac) = (self.delay_steps, self.frac);
+        let out = self.past(k).iter().zip(self.past(k + 1).iter())
+                              .map(|(a, b)| (1.0 - frac) * a + frac * b)
+                              .collect::<Vec<f64>>();
+        self.output_bus.iter_mut().zip(out).for_each(|(sig, v)| sig.set_val(v));
+    }
+}
+
+/// 単位遅延（z^-1）モデル
+/// 1ステップ前に取り込んだ入力を出力し、現在の入力を次のステップ用に保持する。入力と出力は要素ごとに対応する
+/// 入力の取り込みは全モデルの計算後（post_step）に行うため、接続元との実行順に依らず1ステップの遅延になる
+/// 出力は内部状態のみで決まるため直達が無く、フィードバックループを切るのに使用できる
+#[derive(Debug)]
+pub struct UnitDelay {
+    input_bus: RefBus,
+    output_bus: Bus,
+    init_values: Vec<f64>, // 初期値（最初のステップの出力）
+    latched: Vec<f64>,     // 前ステップで取り込んだ入力
+}
+
+impl UnitDelay {
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, init_values: &[f64]) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("UnitDelayの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("UnitDelayの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("UnitDelay: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if init_values.len() != outbus.len() {
+            return Err(anyhow!("UnitDelay: 初期値の数は出力バスの長さと同じである必要があります。\ninit_values.len = {}, output.len = {}", init_values.len(), outbus.len()));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            init_values: init_values.to_vec(),
+            latched: init_values.to_vec(),
+        })
+    }
+}
+
+impl ModelCore for UnitDelay {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.latched = self.init_values.clone();
+        self.output_bus.iter_mut().zip(self.init_values.iter()).for_each(|(sig, v)| sig.set_val(*v));
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        self.output_bus.iter_mut().zip(self.latched.iter()).for_each(|(sig, v)| sig.set_val(*v));
+    }
+
+    fn post_step(&mut self, _sim_time: &SimTime) {
+        self.latched = self.input_bus.to_vec_f64();
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        self.output_bus.iter().zip(self.latched.iter()).map(|(sig, v)| (format!("{}_latched", sig.name()), *v)).collect()
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+}
+
+/// レートリミッタ（変化率制限）モデル
+/// 出力を入力に近づけるが、1ステップの変化量を上昇時はrising_rate * Δt、下降時はfalling_rate * Δtまでに制限する（入力と出力は要素ごとに対応する）
+/// 有限のスルーレートを持つアクチュエータ等の模擬に使用する。出力の初期値は最初の入力値とする
+#[derive(Debug)]
+pub struct RateLimiter {
+    input_bus: RefBus,
+    output_bus: Bus,
+    rising_rate: f64,  // 上昇側の変化率の上限[単位/s]
+    falling_rate: f64, // 下降側の変化率の上限[単位/s]
+    primed: bool,      // 出力を最初の入力値で初期化したかどうか
+}
+
+impl RateLimiter {
+    /// ## RateLimiterの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス（入力バスと同じ要素数）
+    /// 1. 第3引数：rising_rate: 上昇側の変化率の上限[単位/s]（正の値）
+    /// 1. 第4引数：falling_rate: 下降側の変化率の上限[単位/s]（正の値、下降の大きさで指定する）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, rising_rate: f64, falling_rate: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("RateLimiterの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("RateLimiterの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("RateLimiter: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        let valid = rising_rate > 0.0 && falling_rate > 0.0 && rising_rate.is_finite() && falling_rate.is_finite();
+        if !valid {
+            return Err(anyhow!("RateLimiter: 変化率の上限は正の値で設定してください。rising_rate = {}, falling_rate = {}", rising_rate, falling_rate));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            rising_rate,
+            falling_rate,
+            primed: false,
+        })
+    }
+
+    /// 出力を入力値で初期化する
+    fn prime(&mut self) {
+        self.output_bus.copy_val_by_index(&self.input_bus).expect("入出力バスの長さは同じ");
+        self.primed = true;
+    }
+}
+
+impl ModelCore for RateLimiter {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        // 入力の接続元の初期化が終わっていない可能性があるため、出力の初期化はpost_step（または最初のnextstate）で行う
+        self.primed = false;
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        if !self.primed {
+            self.prime();
+            return;
+        }
+
+        let dt = sim_time.delta_t().abs();
+        let (rise, fall) = (self.rising_rate * dt, self.falling_rate * dt);
+        self.output_bus.iter_mut().zip(self.input_bus.iter()).for_each(|(y, u)| {
+            let diff = (u.val() - y.val()).clamp(-fall, rise);
+            y.set_val(y.val() + diff);
+        });
+    }
+
+    fn post_step(&mut self, _sim_time: &SimTime) {
+        if !self.primed {
+            self.prime();
+        }
+    }
+}
+
+/// 差分方程式の漸化式（差分方程式モデル・離散伝達関数モデルで共通）
+/// y[k] = b0 * u[k] + ... + bM * u[k-M] - a1 * y[k-1] - ... - aN * y[k-N]
+#[derive(Debug, Clone)]
+struct Recurrence {
+    b: Vec<f64>,             // 入力側の係数 [b0, b1, ..., bM]
+    a: Vec<f64>,             // 出力側の係数 [a1, a2, ..., aN]（y[k]の係数a0 = 1は含めない）
+    u_hist: VecDeque<f64>,   // 過去の入力 [u[k-1], u[k-2], ..., u[k-M]]
+    y_hist: VecDeque<f64>,   // 過去の出力 [y[k-1], y[k-2], ..., y[k-N]]
+}
+
+impl Recurrence {
+    fn new(b: &[f64], a: &[f64]) -> Self {
+        Self {
+            b: b.to_vec(),
+            a: a.to_vec(),
+            u_hist: VecDeque::from(vec![0.0; b.len() - 1]),
+            y_hist: VecDeque::from(vec![0.0; a.len()]),
+        }
+    }
+
+    /// 過去の入出力を0にする
+    fn reset(&mut self) {
+        self.u_hist.iter_mut().for_each(|v| *v = 0.0);
+        self.y_hist.iter_mut().for_each(|v| *v = 0.0);
+    }
+
+    /// 入力uに対する出力を計算し、過去の入出力を更新する
+    fn step(&mut self, u: f64) -> f64 {
+        let y = self.b[0] * u
+              + self.b[1..].iter().zip(self.u_hist.iter()).map(|(b, u)| b * u).sum::<f64>()
+              - self.a.iter().zip(self.y_hist.iter()).map(|(a, y)| a * y).sum::<f64>();
+
+        Self::shift(&mut self.u_hist, u);
+        Self::shift(&mut self.y_hist, y);
+        y
+    }
+
+    /// 過去の入出力を一つずらして最新の値を先頭に入れる
+    fn shift(hist: &mut VecDeque<f64>, val: f64) {
+        if hist.pop_back().is_some() {
+            hist.push_front(val);
+        }
+    }
+
+    /// 過去の入出力（"u[k-1]", ..., "y[k-1]", ...）
+    fn states(&self) -> Vec<(String, f64)> {
+        let u = self.u_hist.iter().enumerate().map(|(i, v)| (format!("u[k-{}]", i + 1), *v));
+        let y = self.y_hist.iter().enumerate().map(|(i, v)| (format!("y[k-{}]", i + 1), *v));
+        u.chain(y).collect()
+    }
+}
+
+/// 差分方程式モデル
+/// y[k] = b0 * u[k] + b1 * u[k-1] + ... + bM * u[k-M] - a1 * y[k-1] - ... - aN * y[k-N] を計算して出力する（1入力1出力）
+/// kはシミュレーションのステップで、デジタルフィルタや制御器をその漸化式から直接実装する時に使用する
+/// 過去の入出力（初期化前の値）は0とする。b0が0の場合は直達が無い
+#[derive(Debug)]
+pub struct DifferenceEquation {
+    input_bus: RefBus,
+    output_bus: Bus,
+    recurrence: Recurrence, // 漸化式と過去の入出力
+}
+
+impl DifferenceEquation {
+    /// ## DifferenceEquationの引数定義
+    /// 1. 第1引数：入力バス（1要素）
+    /// 1. 第2引数：出力バス（1要素）
+    /// 1. 第3引数：b: 入力側の係数 [b0, b1, ..., bM]（1要素以上）
+    /// 1. 第4引数：a: 出力側の係数 [a1, a2, ..., aN]（FIRフィルタの場合は空）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, b: &[f64], a: &[f64]) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("DifferenceEquationの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("DifferenceEquationの出力バスが不正です。")?;
+
+        if inbus.len() != 1 || outbus.len() != 1 {
+            return Err(anyhow!("DifferenceEquation: 入出力バスは1要素で設定してください。input.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if b.is_empty() {
+            return Err(anyhow!("DifferenceEquation: 入力側の係数bは1要素以上で設定してください。"));
+        }
+        if b.iter().chain(a.iter()).any(|c| !c.is_finite()) {
+            return Err(anyhow!("DifferenceEquation: 係数に有限でない値が含まれています。b = {:?}, a = {:?}", b, a));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            recurrence: Recurrence::new(b, a),
+        })
+    }
+}
+
+impl ModelCore for DifferenceEquation {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.recurrence.reset();
+        self.output_bus[0].set_val(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let y = self.recurrence.step(self.input_bus[0].val());
+        self.output_bus[0].set_val(y);
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        self.recurrence.states()
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.recurrence.b[0] != 0.0
+    }
+}
+
+/// 離散伝達関数モデル
+/// G(z) = (b0 + b1 z^-1 + ... + bM z^-M) / (a0 + a1 z^-1 + ... + aN z^-N) を、
+/// a0 * y[n] = b0 * u[n] + ... + bM * u[n-M] - a1 * y[n-1] - ... - aN * y[n-N] の漸化式で計算する（1入力1出力）
+/// 漸化式はサンプリング周期tsごとに実行し、周期の間は出力を保持する（tsが刻み幅以下の場合は毎ステップ実行する）
+/// 直流ゲインは sum(b) / sum(a)
+#[derive(Debug)]
+pub struct DiscreteTransFunc {
+    input_bus: RefBus,
+    output_bus: Bus,
+    recurrence: Recurrence, // a0で正規化した漸化式と過去の入出力
+    sample_time: f64,       // サンプリング周期[s]
+    next_sample: f64,       // 次回の実行時刻[s]
+}
+
+impl DiscreteTransFunc {
+    /// ## DiscreteTransFuncの引数定義
+    /// 1. 第1引数：入力バス（1要素）
+    /// 1. 第2引数：出力バス（1要素）
+    /// 1. 第3引数：b_coeffs: 分子の係数 [b0, b1, ..., bM]（z^-1の昇順、1要素以上）
+    /// 1. 第4引数：a_coeffs: 分母の係数 [a0, a1, ..., aN]（z^-1の昇順、a0 != 0）
+    /// 1. 第5引数：ts: サンプリング周期[s]（正の値）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, b_coeffs: &[f64], a_coeffs: &[f64], ts: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("DiscreteTransFuncの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("DiscreteTransFuncの出力バスが不正です。")?;
+
+        if inbus.len() != 1 || outbus.len() != 1 {
+            return Err(anyhow!("DiscreteTransFunc: 入出力バスは1要素で設定してください。input.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if b_coeffs.is_empty() || a_coeffs.is_empty() {
+            return Err(anyhow!("DiscreteTransFunc: 分子・分母の係数は1要素以上で設定してください。"));
+        }
+        if b_coeffs.iter().chain(a_coeffs.iter()).any(|c| !c.is_finite()) {
+            return Err(anyhow!("DiscreteTransFunc: 係数に有限でない値が含まれています。b = {:?}, a = {:?}", b_coeffs, a_coeffs));
+        }
+        if a_coeffs[0] == 0.0 {
+            return Err(anyhow!("DiscreteTransFunc: 分母の係数a0は0以外で設定してください。"));
+        }
+        let valid = ts > 0.0 && ts.is_finite();
+        if !valid {
+            return Err(anyhow!("DiscreteTransFunc: サンプリング周期は正の値で設定してください。ts = {}", ts));
+        }
+
+        let a0 = a_coeffs[0];
+        let b = b_coeffs.iter().map(|b| b / a0).collect::<Vec<f64>>();
+        let a = a_coeffs[1..].iter().map(|a| a / a0).collect::<Vec<f64>>();
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            recurrence: Recurrence::new(&b, &a),
+            sample_time: ts,
+            next_sample: 0.0,
+        })
+    }
+
+    /// サンプリング周期[s]
+    pub fn sample_time(&self) -> f64 {
+        self.sample_time
+    }
+
+    /// 直流ゲイン sum(b) / sum(a)（分母の和が0の場合は無限大またはNaN）
+    pub fn dc_gain(&self) -> f64 {
+        let rc = &self.recurrence;
+        rc.b.iter().sum::<f64>() / (1.0 + rc.a.iter().sum::<f64>())
+    }
+}
+
+impl ModelCore for DiscreteTransFunc {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.next_sample = sim_time.time();
+        self.recurrence.reset();
+        self.output_bus[0].set_val(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        // 刻み幅の丸め誤差で実行周期がずれないよう、少し手前から実行時刻に達したとみなす
+        let t = sim_time.time() + sim_time.delta_t() * 1e-6;
+        if t < self.next_sample {
+            return; // サンプリング周期の間は出力を保持する
+        }
+        while self.next_sample <= t {
+            self.next_sample += self.sample_time;
+        }
+
+        let y = self.recurrence.step(self.input_bus[0].val());
+        self.output_bus[0].set_val(y);
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        self.recurrence.states()
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.recurrence.b[0] != 0.0
+    }
+}
+
+/// 移動平均モデル
+/// 要素ごとに直近window_samplesステップの入力を保持し、その平均を出力する（重みの等しいFIRフィルタ）
+/// 開始直後で入力がwindow_samples個に満たない間は、それまでの入力の平均を出力する
+/// 出力は窓の長さの半分程度（(window_samples - 1) / 2ステップ）遅れる
+#[derive(Debug)]
+pub struct MovingAverage {
+    input_bus: RefBus,
+    output_bus: Bus,
+    window: usize,              // 平均する入力の数
+    buffers: Vec<VecDeque<f64>>, // 要素ごとの直近の入力（古い順）
+}
+
+impl MovingAverage {
+    /// ## MovingAverageの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス（入力バスと同じ要素数）
+    /// 1. 第3引数：window_samples: 平均するステップ数（1以上）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, window_samples: usize) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("MovingAverageの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("MovingAverageの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("MovingAverage: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if window_samples == 0 {
+            return Err(anyhow!("MovingAverage: 平均するステップ数は1以上で設定してください。"));
+        }
+
+        let len = inbus.len();
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            window: window_samples,
+            buffers: vec![VecDeque::with_capacity(window_samples); len],
+        })
+    }
+
+    /// 平均するステップ数
+    pub fn window_samples(&self) -> usize {
+        self.window
+    }
+}
+
+impl ModelCore for MovingAverage {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.buffers.iter_mut().for_each(|buf| buf.clear());
+        self.output_bus.zero_reset();
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let window = self.window;
+        for ((buf, u), y) in self.buffers.iter_mut().zip(self.input_bus.iter()).zip(self.output_bus.iter_mut()) {
+            if buf.len() == window {
+                buf.pop_front();
+            }
+            buf.push_back(u.val());
+            // 累積和の丸め誤差が残らないよう、毎ステップ窓内の値から平均を計算する
+            y.set_val(buf.iter().sum::<f64>() / buf.len() as f64);
+        }
+    }
+}
+
+/// エネルギーメータモデル
+/// 入力[力, 速度]（または[電圧, 電流]）の積から瞬時パワーを求め、その時間積分（累積エネルギー）を出力する
+/// 出力は[パワー, エネルギー]の順。単位は出力バスの信号定義で指定する（[N]・[m/s]であれば[W]・[J]）
+/// 積分は台形則で行うため、パワーがステップ間で直線的に変化する場合は厳密な値になる
+#[derive(Debug)]
+pub struct EnergyMeter {
+    input_bus: RefBus,
+    output_bus: Bus,
+    power: f64,  // 瞬時パワー（前ステップの値）
+    energy: f64, // 累積エネルギー
+}
+
+impl EnergyMeter {
+    /// ## EnergyMeterの引数定義
+    /// 1. 第1引数：入力バス（2要素）[力, 速度]または[電圧, 電流]
+    /// 1. 第2引数：出力バス（2要素）[パワー, エネルギー]
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("EnergyMeterの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("EnergyMeterの出力バスが不正です。")?;
+
+        if inbus.len() != 2 {
+            return Err(anyhow!("EnergyMeter: 入力バスは[力, 速度]（または[電圧, 電流]）の2要素で設定してください。input.len = {}", inbus.len()));
+        }
+        if outbus.len() != 2 {
+            return Err(anyhow!("EnergyMeter: 出力バスは[パワー, エネルギー]の2要素で設定してください。output.len = {}", outbus.len()));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            power: 0.0,
+            energy: 0.0,
+        })
+    }
+
+    /// 現在の累積エネルギー
+    pub fn energy(&self) -> f64 {
+        self.energy
+    }
+
+    /// 入力から瞬時パワーを計算する
+    fn input_power(&self) -> f64 {
+        self.input_bus[0].val() * self.input_bus[1].val()
+    }
+}
+
+impl ModelCore for EnergyMeter {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.power = self.input_power();
+        self.energy = 0.0;
+        self.output_bus[0].set_val(self.power);
+        self.output_bus[1].set_val(self.energy);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        let power = self.input_power();
+        self.energy += 0.5 * (self.power + power) * sim_time.delta_t();
+        self.power = power;
+
+        self.output_bus[0].set_val(self.power);
+        self.output_bus[1].set_val(self.energy);
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        vec![("energy".to_string(), self.energy)]
+    }
+}
+
+/// ゲインモデル
+/// 入力ベクトルuにゲイン行列Kを掛けて y = K u を出力する（信号のスケーリング・混合に使用する）
+/// Kは 出力の要素数 x 入力の要素数 の行列で、行優先で指定する
+#[derive(Debug)]
+pub struct Gain {
+    input_bus: RefBus,
+    output_bus: Bus,
+    gain: DMatrix<f64>, // ゲイン行列K
+}
+
+impl Gain {
+    /// ## Gainの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス
+    /// 1. 第3引数：matrix: ゲイン行列K（出力の要素数 x 入力の要素数、行優先）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, matrix: &[f64]) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("Gainの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("Gainの出力バスが不正です。")?;
+
+        if matrix.len() != outbus.len() * inbus.len() {
+            return Err(anyhow!("Gain: ゲイン行列の要素数は 出力の要素数 x 入力の要素数 である必要があります。\nmatrix.len = {}, output.len = {}, input.len = {}", matrix.len(), outbus.len(), inbus.len()));
+        }
+
+        Ok(Self {
+            gain: DMatrix::from_row_slice(outbus.len(), inbus.len(), matrix),
+            input_bus: inbus,
+            output_bus: outbus,
+        })
+    }
+
+    /// ゲイン行列
+    pub fn gain(&self) -> &DMatrix<f64> {
+        &self.gain
+    }
+}
+
+impl ModelCore for Gain {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let y = &self.gain * self.input_bus.export_to_matrix();
+        self.output_bus.import_matrix(&y);
+    }
+}
+
+/// 周波数応答推定モデル
+/// 入力u（加振信号）と出力y（応答）を、周波数スケジュールで与えた周波数の複素正弦波と相関させ（Goertzel法と同等）
+/// 設定した周期数分の積分が終わるたびに、ゲイン|Y/U|[倍]と位相∠(Y/U)[deg]（-180°～180°）を推定して出力する
+/// 推定値は次の積分が終わるまで保持する（最初の推定が終わるまではゲイン0・位相0を出力する）
+/// 出力バスを3要素にすると、推定値に対応する周波数[Hz]（積分区間の平均値）も出力する
+/// 周波数スケジュールは[時刻[s], 周波数[Hz]]の組で、間は線形補間する（ステップ正弦波・チャープ加振の両方に使える）
+#[derive(Debug)]
+pub struct FrequencyEstimator {
+    input_bus: RefBus,
+    output_bus: Bus,
+    schedule: Vec<(f64, f64)>,  // 周波数スケジュール[時刻[s], 周波数[Hz]]
+    cycles: usize,              // 1回の推定に使う周期数
+    angle: f64,                 // 積分中の参照正弦波の位相[rad]
+    corr: (Complex<f64>, Complex<f64>), // 入力・出力の相関の積算値
+    freq_sum: f64,              // 積分中の周波数の積算値（推定に対応する周波数の計算用）
+    samples: usize,             // 積分中のサンプル数
+}
+
+impl FrequencyEstimator {
+    /// ## FrequencyEstimatorの引数定義
+    /// 1. 第1引数：入力バス（2要素）[加振信号u, 応答y]
+    /// 1. 第2引数：出力バス（2要素または3要素）[ゲイン, 位相[deg]]または[ゲイン, 位相[deg], 周波数[Hz]]
+    /// 1. 第3引数：schedule: 周波数スケジュール[時刻[s], 周波数[Hz]]（時刻の昇順、周波数は正）
+    /// 1. 第4引数：cycles: 1回の推定に使う周期数（1以上）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, schedule: Vec<(f64, f64)>, cycles: usize) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("FrequencyEstimatorの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("FrequencyEstimatorの出力バスが不正です。")?;
+
+        if inbus.len() != 2 {
+            return Err(anyhow!("FrequencyEstimator: 入力バスは[加振信号, 応答]の2要素で設定してください。input.len = {}", inbus.len()));
+        }
+        if outbus.len() != 2 && outbus.len() != 3 {
+            return Err(anyhow!("FrequencyEstimator: 出力バスは[ゲイン, 位相]または[ゲイン, 位相, 周波数]で設定してください。output.len = {}", outbus.len()));
+        }
+        if schedule.is_empty() {
+            return Err(anyhow!("FrequencyEstimator: 周波数スケジュールが空です。"));
+        }
+        if let Some(&(time, freq)) = schedule.iter().find(|(_, f)| !(f.is_finite() && *f > 0.0)) {
+            return Err(anyhow!("FrequencyEstimator: 周波数は正の値である必要があります。time = {}, freq = {}", time, freq));
+        }
+        if schedule.windows(2).any(|w| w[1].0 <= w[0].0) {
+            return Err(anyhow!("FrequencyEstimator: 周波数スケジュールの時刻は昇順である必要があります。"));
+        }
+        if cycles == 0 {
+            return Err(anyhow!("FrequencyEstimator: 推定に使う周期数は1以上である必要があります。"));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            schedule,
+            cycles,
+            angle: 0.0,
+            corr: (Complex::new(0.0, 0.0), Complex::new(0.0, 0.0)),
+            freq_sum: 0.0,
+            samples: 0,
+        })
+    }
+
+    /// 時刻timeにおける周波数[Hz]（スケジュールの範囲外は端の値を保持する）
+    fn freq_at(&self, time: f64) -> f64 {
+        let (first, last) = (self.schedule[0], self.schedule[self.schedule.len() - 1]);
+        if time <= first.0 {
+            return first.1;
+        }
+        if time >= last.0 {
+            return last.1;
+        }
+        let idx = self.schedule.partition_point(|(t, _)| *t <= time);
+        let ((t0, f0), (t1, f1)) = (self.schedule[idx - 1], self.schedule[idx]);
+        f0 + (f1 - f0) * (time - t0) / (t1 - t0)
+    }
+
+    /// 積分中の値をリセットする
+    fn reset_window(&mut self) {
+        self.angle = 0.0;
+        self.corr = (Complex::new(0.0, 0.0), Complex::new(0.0, 0.0));
+        self.freq_sum = 0.0;
+        self.samples = 0;
+    }
+}
+
+impl ModelCore for FrequencyEstimator {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.reset_window();
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        let freq = self.freq_at(sim_time.time());
+        let reference = Complex::from_polar(1.0, -self.angle);
+        self.corr.0 += reference * self.input_bus[0].val();
+        self.corr.1 += reference * self.input_bus[1].val();
+        self.freq_sum += freq;
+        self.samples += 1;
+        self.angle += 2.0 * PI * freq * sim_time.delta_t();
+
+        // 設定した周期数分の積分が終わったら推定値を更新する（加振信号が無い区間は推定しない）
+        if self.angle >= 2.0 * PI * self.cycles as f64 - 1e-9 {
+            if self.corr.0.norm() > 0.0 {
+                let ratio = self.corr.1 / self.corr.0;
+                self.output_bus[0].set_val(ratio.norm());
+                self.output_bus[1].set_val(ratio.arg().to_degrees());
+                if self.output_bus.len() == 3 {
+                    self.output_bus[2].set_val(self.freq_sum / self.samples as f64);
+                }
+            }
+            self.reset_window();
+        }
+    }
+
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod basic_model_test {
+    use super::*;
+    use crate::simcore::sim_model::{source_models::{StepFunc, RampFunc, ConstantFunc}, sink_models::SimRecorder, model_core::connect_models, closure_models::ClosureModel};
+    use crate::simcore::sim_model::{source_models::{WaveFunc, WaveFuncSetting, WaveFuncType}, de_models::{TransFuncModel, SolverType}};
+    use crate::simcore::sim_system::SimSystem;
+
+    #[test]
+    fn time_delay_test() {
+        let src = StepFunc::new(vec![SigDef::new("u", "-")], vec![(0.0, 1.0, 0.2)]).unwrap();
+        let mut delay = TimeDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 0.5, 0.0).unwrap();
+        connect_models(&src, &["u"], &mut delay, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-"), SigDef::new("y", "-")]).unwrap();
+        connect_models(&src, &["u"], &mut rcd, &["u"]).unwrap();
+        connect_models(&delay, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 2.0, 0.1);
+        sys.regist_model(src);
+        sys.regist_model(delay);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        // 入力・出力が最初に1になる時刻の差が遅延時間と一致する
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let time = rcd.get_time().to_vec();
+        let first_on = |name: &str| time[rcd.get_signal(name).unwrap().iter().position(|v| *v >= 1.0).unwrap()];
+        assert!((first_on("y") - first_on("u") - 0.5).abs() < 1e-9);
+        rcd.assert_final_approx("y", 1.0, 1e-12).unwrap();
+
+        assert!(TimeDelay::new(vec![SigDef::new("u", "-")], vec![], 0.5, 0.0).is_err());
+        assert!(TimeDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], -0.1, 0.0).is_err());
+        assert!(TimeDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], f64::INFINITY, 0.0).is_err());
+        assert!(TimeDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], f64::NAN, 0.0).is_err());
+    }
+
+    #[test]
+    fn time_delay_interpolation_test() {
+        // 刻み幅の整数倍でない遅延（0.25[s] = 2.5ステップ）はランプ入力を正確に遅らせる
+        let src = RampFunc::new(vec![SigDef::new("u", "-")], vec![(0.0, 0.0, false, 0.0, 2.0)]).unwrap();
+        let mut delay = TimeDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 0.25, 0.0).unwrap();
+        connect_models(&src, &["u"], &mut delay, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        connect_models(&delay, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(src);
+        sys.regist_model(delay);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let time = rcd.get_time().to_vec();
+        time.iter().zip(rcd.get_signal("y").unwrap().iter())
+            .filter(|(t, _)| **t >= 0.35)
+            .for_each(|(t, y)| assert!((y - 2.0 * (t - 0.25)).abs() < 1e-9, "t = {}, y = {}", t, y));
+    }
+
+    #[test]
+    fn unit_delay_feedback_test() {
+        // y = UnitDelay(y + 1) は1ステップごとに1ずつ増える
+        // 加算器の初期出力は遅延の初期値に合わせる（u = 5 + 1）
+        let mut adder = ClosureModel::new(
+            vec![SigDef::new("y", "-")],
+            vec![SigDef::new("u", "-")],
+            |outbus, _| outbus[0].set_val(6.0),
+            |inbus, outbus, _| outbus[0].set_val(inbus[0].val() + 1.0),
+        ).unwrap();
+        let mut delay = UnitDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[5.0]).unwrap();
+        connect_models(&adder, &["u"], &mut delay, &["u"]).unwrap();
+        connect_models(&delay, &["y"], &mut adder, &["y"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        connect_models(&delay, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(adder);
+        sys.regist_model(delay);
+        sys.regist_recorder("rcd", rcd);
+        sys.resolve_order().unwrap();
+        sys.run();
+
+        let y = sys.get_recorder("rcd").unwrap().get_signal("y").unwrap().to_vec();
+        assert_eq!(y[0], 5.0);
+        y.windows(2).for_each(|w| assert_eq!(w[1] - w[0], 1.0));
+
+        assert!(UnitDelay::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[]).is_err());
+        assert!(UnitDelay::new(vec![SigDef::new("u", "-")], vec![], &[0.0]).is_err());
+    }
+
+    #[test]
+    fn difference_equation_test() {
+        // 単位ステップ入力に対する移動平均（FIR）と1次IIRフィルタ y[k] = 0.5 u[k] + 0.5 y[k-1]
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut fir = DifferenceEquation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("fir", "-")], &[0.25; 4], &[]).unwrap();
+        let mut iir = DifferenceEquation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("iir", "-")], &[0.5], &[-0.5]).unwrap();
+        connect_models(&input, &["u"], &mut fir, &["u"]).unwrap();
+        connect_models(&input, &["u"], &mut iir, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("fir", "-"), SigDef::new("iir", "-")]).unwrap();
+        connect_models(&fir, &["fir"], &mut rcd, &["fir"]).unwrap();
+        connect_models(&iir, &["iir"], &mut rcd, &["iir"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 0.75, 0.125);
+        sys.regist_model(input);
+        sys.regist_model(fir);
+        sys.regist_model(iir);
+        sys.regist_recorder("rcd", rcd);
+        for _ in 0..2 { // 再実行しても過去の入出力は初期化される
+            sys.reset();
+            sys.run();
+            let rcd = sys.get_result("rcd").unwrap();
+            assert_eq!(rcd.get_signal("fir").unwrap(), &[0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0]);
+            assert_eq!(rcd.get_signal("iir").unwrap(), &[0.0, 0.5, 0.75, 0.875, 0.9375, 0.96875, 0.984375]);
+        }
+
+        // b0 = 0は直達無し（1ステップ遅れ）
+        let delay = DifferenceEquation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[0.0, 1.0], &[]).unwrap();
+        assert!(!delay.direct_feedthrough());
+        assert_eq!(delay.internal_states().len(), 1);
+
+        assert!(DifferenceEquation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[], &[0.5]).is_err());
+        assert!(DifferenceEquation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[f64::NAN], &[]).is_err());
+        assert!(DifferenceEquation::new(vec![SigDef::new("u", "-"), SigDef::new("v", "-")], vec![SigDef::new("y", "-")], &[1.0], &[]).is_err());
+    }
+
+    #[test]
+    fn discrete_trans_func_test() {
+        // 1次IIRローパス G(z) = (0.5 + 0.25 z^-1) / (2 - z^-1)、直流ゲイン 0.75 / 1.0 = 0.75
+        // サンプリング周期0.1[s]を刻み幅0.025[s]で実行する
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[2.0]).unwrap();
+        let mut lpf = DiscreteTransFunc::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[0.5, 0.25], &[2.0, -1.0], 0.1).unwrap();
+        assert!((lpf.dc_gain() - 0.75).abs() < 1e-12);
+        assert_eq!(lpf.sample_time(), 0.1);
+        connect_models(&input, &["u"], &mut lpf, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        connect_models(&lpf, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 5.0, 0.025);
+        sys.regist_model(input);
+        sys.regist_model(lpf);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_result("rcd").unwrap();
+        let y = rcd.get_signal("y").unwrap();
+        // 時刻0の実行で y[0] = 0.5 * 2 / 2、時刻0.1の実行で y[1] = (0.5 * 2 + 0.25 * 2 + 0.5) / 2 で、周期の間は保持する
+        assert_eq!(&y[0..9], &[0.0, 0.5, 0.5, 0.5, 1.0, 1.0, 1.0, 1.0, 1.25]);
+        // 定常ゲインは解析的な直流ゲイン sum(b) / sum(a)
+        rcd.assert_final_approx("y", 2.0 * 0.75, 1e-9).unwrap();
+
+        let sig = |name: &str| vec![SigDef::new(name, "-")];
+        assert!(DiscreteTransFunc::new(sig("u"), sig("y"), &[1.0], &[0.0, 1.0], 0.1).is_err());
+        assert!(DiscreteTransFunc::new(sig("u"), sig("y"), &[1.0], &[], 0.1).is_err());
+        assert!(DiscreteTransFunc::new(sig("u"), sig("y"), &[1.0], &[1.0], 0.0).is_err());
+    }
+
+    #[test]
+    fn rate_limiter_test() {
+        // 0 → 1（時刻0.25[s]）のステップと、1 → -1（時刻0.25[s]）のステップを上昇2[/s]・下降4[/s]で制限する
+        let src = StepFunc::new(vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")], vec![(0.0, 1.0, 0.25), (1.0, -1.0, 0.25)]).unwrap();
+        let mut limiter = RateLimiter::new(
+            vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")],
+            vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")],
+            2.0, 4.0,
+        ).unwrap();
+        connect_models(&src, &["u1", "u2"], &mut limiter, &["u1", "u2"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")]).unwrap();
+        connect_models(&limiter, &["y1", "y2"], &mut rcd, &["y1", "y2"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.5, 0.125);
+        sys.regist_model(src);
+        sys.regist_model(limiter);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_result("rcd").unwrap();
+        // 出力の初期値は最初の入力値。ステップ後は設定した傾き（1ステップ当たり0.25・0.5）で変化し、入力に達したら止まる
+        assert_eq!(rcd.get_signal("y1").unwrap(), &[0.0, 0.0, 0.25, 0.5, 0.75, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0]);
+        assert_eq!(rcd.get_signal("y2").unwrap(), &[1.0, 1.0, 0.5, 0.0, -0.5, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0, -1.0]);
+
+        assert!(RateLimiter::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 0.0, 1.0).is_err());
+        assert!(RateLimiter::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 1.0, f64::INFINITY).is_err());
+        assert!(RateLimiter::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")], 1.0, 1.0).is_err());
+    }
+
+    #[test]
+    fn moving_average_test() {
+        // 周期1[s]・±1の矩形波（0/1の矩形波を2倍して-1する）を0.75[s]（48ステップ）の窓で平均する
+        let dt = 1.0 / 64.0;
+        let window = 48;
+        let wave = WaveFunc::new(vec![SigDef::new("u", "-")], vec![WaveFuncSetting::new(WaveFuncType::Square, 2.0, 0.0, 1.0, -1.0)]).unwrap();
+        let mut ma = MovingAverage::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], window).unwrap();
+        assert_eq!(ma.window_samples(), window);
+        connect_models(&wave, &["u"], &mut ma, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-"), SigDef::new("y", "-")]).unwrap();
+        connect_models(&wave, &["u"], &mut rcd, &["u"]).unwrap();
+        connect_models(&ma, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 4.0, dt);
+        sys.regist_model(wave);
+        sys.regist_model(ma);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_result("rcd").unwrap();
+        let (t, u, y) = (rcd.get_time(), rcd.get_signal("u").unwrap(), rcd.get_signal("y").unwrap());
+        let steady = t.iter().position(|&t| t >= 1.0).unwrap();
+
+        // 振幅は減衰する（窓に+1が0.5[s]・-1が0.25[s]含まれる時が最大で1/3）
+        let amp = y[steady..].iter().fold(0.0_f64, |a, v| a.max(v.abs()));
+        assert!(amp < 0.4 && amp > 0.3, "amp = {}", amp);
+
+        // 入力の立ち上がりから出力が0を横切るまでの遅れは窓の長さの半分程度
+        let rise = |sig: &[f64], from: usize| (from..sig.len() - 1).find(|&k| sig[k] < 0.0 && sig[k + 1] >= 0.0).unwrap() + 1;
+        let k_in = rise(u, steady);
+        let lag = (rise(y, k_in) - k_in) as f64;
+        assert!((lag - (window - 1) as f64 / 2.0).abs() <= 1.0, "lag = {} steps", lag);
+
+        // 入力が窓の長さに満たない間はそれまでの平均
+        let mut databus = Bus::try_from(vec![SigDef::new("u", "-")]).unwrap();
+        let mut ma = MovingAverage::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 3).unwrap();
+        ma.interface_in().unwrap().connect_to(&databus, &["u"], &["u"]).unwrap();
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.25);
+        ma.initialize(&sim_time);
+        let mut out = Vec::new();
+        for u in [1.0, 2.0, 3.0, 4.0] {
+            databus[0].set_val(u);
+            sim_time.next();
+            ma.nextstate(&sim_time);
+            out.push(ma.interface_out().unwrap()[0].val());
+        }
+        assert_eq!(out, vec![1.0, 1.5, 2.0, 3.0]);
+
+        assert!(MovingAverage::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 0).is_err());
+    }
+
+    #[test]
+    fn energy_meter_test() {
+        // 力2[N]一定、速度 v = t[m/s] の時、パワーは2t[W]、エネルギーはt^2[J]
+        let force = ConstantFunc::new(vec![SigDef::new("f", "N")], &[2.0]).unwrap();
+        let vel = RampFunc::new(vec![SigDef::new("v", "m/s")], vec![(0.0, 0.0, false, 0.0, 1.0)]).unwrap();
+        let mut meter = EnergyMeter::new(
+            vec![SigDef::new("f", "N"), SigDef::new("v", "m/s")],
+            vec![SigDef::new("p", "W"), SigDef::new("e", "J")],
+        ).unwrap();
+        connect_models(&force, &["f"], &mut meter, &["f"]).unwrap();
+        connect_models(&vel, &["v"], &mut meter, &["v"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("p", "W"), SigDef::new("e", "J")]).unwrap();
+        connect_models(&meter, &["p", "e"], &mut rcd, &["p", "e"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 2.0, 0.1);
+        sys.regist_model(force);
+        sys.regist_model(vel);
+        sys.regist_model(meter);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        rcd.assert_final_approx("p", 4.0, 1e-9).unwrap();
+        rcd.assert_final_approx("e", 4.0, 1e-9).unwrap();
+
+        assert!(EnergyMeter::new(vec![SigDef::new("f", "N")], vec![SigDef::new("p", "W"), SigDef::new("e", "J")]).is_err());
+        assert!(EnergyMeter::new(vec![SigDef::new("f", "N"), SigDef::new("v", "m/s")], vec![SigDef::new("p", "W")]).is_err());
+    }
+
+    #[test]
+    fn gain_test() {
+        // K = [1, -1] で2入力の差を出力する
+        let src = ConstantFunc::new(vec![SigDef::new("a", "m"), SigDef::new("b", "m")], &[3.0, 1.25]).unwrap();
+        let mut gain = Gain::new(vec![SigDef::new("a", "m"), SigDef::new("b", "m")], vec![SigDef::new("diff", "m")], &[1.0, -1.0]).unwrap();
+        connect_models(&src, &["a", "b"], &mut gain, &["a", "b"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("diff", "m")]).unwrap();
+        connect_models(&gain, &["diff"], &mut rcd, &["diff"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 0.5, 0.1);
+        sys.regist_model(src);
+        sys.regist_model(gain);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        assert!(rcd.get_signal("diff").unwrap().iter().skip(1).all(|v| *v == 1.75));
+
+        // 2x2の行列（行優先）
+        let gain = Gain::new(vec![SigDef::new("a", "-"), SigDef::new("b", "-")], vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")], &[1.0, 2.0, 3.0, 4.0]).unwrap();
+        assert_eq!(gain.gain()[(0, 1)], 2.0);
+        assert_eq!(gain.gain()[(1, 0)], 3.0);
+
+        assert!(Gain::new(vec![SigDef::new("a", "-"), SigDef::new("b", "-")], vec![SigDef::new("y", "-")], &[1.0]).is_err());
+    }
+
+    #[test]
+    fn frequency_estimator_test() {
+        // 1次遅れ系 G(s) = 1/(0.2s + 1) を1Hz→2Hzのステップ正弦波で加振する
+        let wave = WaveFunc::new(vec![SigDef::new("u", "-")], vec![WaveFuncSetting::new(WaveFuncType::Sin, 1.0, 0.0, 1.0, 0.0)]).unwrap();
+        let mut plant = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[0.2, 1.0], SolverType::RungeKutta).unwrap();
+        connect_models(&wave, &["u"], &mut plant, &["u"]).unwrap();
+        let expected = plant.bode(2.0 * PI);
+
+        let mut est = FrequencyEstimator::new(
+            vec![SigDef::new("u", "-"), SigDef::new("y", "-")],
+            vec![SigDef::new("gain", "-"), SigDef::new("phase", "deg")],
+            vec![(0.0, 1.0)],
+            2,
+        ).unwrap();
+        connect_models(&wave, &["u"], &mut est, &["u"]).unwrap();
+        connect_models(&plant, &["y"], &mut est, &["y"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("gain", "-"), SigDef::new("phase", "deg")]).unwrap();
+        connect_models(&est, &["gain", "phase"], &mut rcd, &["gain", "phase"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 8.0, 0.001);
+        sys.regist_model(wave);
+        sys.regist_model(plant);
+        sys.regist_model(est);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        // 過渡応答が収まった後の推定値は解析的なボード線図の値と一致する
+        let rcd = sys.get_recorder("rcd").unwrap();
+        rcd.assert_final_approx("gain", expected.0, 1e-3).unwrap();
+        rcd.assert_final_approx("phase", expected.1, 0.2).unwrap();
+
+        // スケジュールの検証
+        let sigs = || (vec![SigDef::new("u", "-"), SigDef::new("y", "-")], vec![SigDef::new("gain", "-"), SigDef::new("phase", "deg")]);
+        let (i, o) = sigs();
+        assert!(FrequencyEstimator::new(i, o, vec![], 1).is_err());
+        let (i, o) = sigs();
+        assert!(FrequencyEstimator::new(i, o, vec![(0.0, 0.0)], 1).is_err());
+        let (i, o) = sigs();
+        assert!(FrequencyEstimator::new(i, o, vec![(1.0, 1.0), (0.5, 2.0)], 1).is_err());
+        let (i, o) = sigs();
+        assert!(FrequencyEstimator::new(i, o, vec![(0.0, 1.0)], 0).is_err());
+    }
+
+    #[test]
+    fn frequency_estimator_chirp_test() {
+        // 0.5Hz→2.5Hzのチャープ加振。スケジュールは加振信号の瞬時周波数に合わせる
+        let wave = WaveFunc::new(
+            vec![SigDef::new("u", "-")],
+            vec![WaveFuncSetting::new(WaveFuncType::Chirp { f_start: 0.5, f_end: 2.5, duration: 20.0 }, 1.0, 0.0, 0.0, 0.0)],
+        ).unwrap();
+        let mut plant = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[0.2, 1.0], SolverType::RungeKutta).unwrap();
+        connect_models(&wave, &["u"], &mut plant, &["u"]).unwrap();
+
+        let mut est = FrequencyEstimator::new(
+            vec![SigDef::new("u", "-"), SigDef::new("y", "-")],
+            vec![SigDef::new("gain", "-"), SigDef::new("phase", "deg"), SigDef::new("freq", "Hz")],
+            vec![(0.0, 0.5), (20.0, 2.5)],
+            1,
+        ).unwrap();
+        assert_eq!(est.freq_at(-1.0), 0.5);
+        assert_eq!(est.freq_at(10.0), 1.5);
+        assert_eq!(est.freq_at(30.0), 2.5);
+        connect_models(&wave, &["u"], &mut est, &["u"]).unwrap();
+        connect_models(&plant, &["y"], &mut est, &["y"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("gain", "-"), SigDef::new("phase", "deg"), SigDef::new("freq", "Hz")]).unwrap();
+        connect_models(&est, &["gain", "phase", "freq"], &mut rcd, &["gain", "phase", "freq"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 20.0, 0.001);
+        sys.regist_model(wave);
+        sys.regist_model(plant);
+        sys.regist_model(est);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        // 過渡応答が収まった後（5秒以降）の推定値を、推定周波数における解析値と比較する
+        let tf = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[0.2, 1.0], SolverType::RungeKutta).unwrap();
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let (gain, phase, freq) = (rcd.get_signal("gain").unwrap(), rcd.get_signal("phase").unwrap(), rcd.get_signal("freq").unwrap());
+        let mut checked = 0;
+        for (k, t) in rcd.get_time().iter().enumerate().filter(|(_, t)| **t > 5.0) {
+            let expected = tf.bode(2.0 * PI * freq[k]);
+            assert!((gain[k] - expected.0).abs() < 0.01, "t = {}, gain = {}, expected = {}", t, gain[k], expected.0);
+            assert!((phase[k] - expected.1).abs() < 1.0, "t = {}, phase = {}, expected = {}", t, phase[k], expected.1);
+            checked += 1;
+        }
+        assert!(checked > 0);
+    }
+}
@@ -0,0 +1,152 @@
+/// # クロージャモデル
+/// ModelCoreトレイトを実装した構造体を作らずに、初期化処理と1ステップの処理をクロージャで与えてモデルを定義する
+///
+/// - 状態なしのモデル（ClosureModel::new）
+/// - ユーザ定義の状態を持つモデル（ClosureModel::with_state）
+use anyhow::{Context};
+
+use crate::simcore::{sim_model, sim_signal, sim_system};
+use sim_model::model_core::{ModelCore};
+
+use sim_signal::signal::{SigDef};
+use sim_signal::bus::{Bus, RefBus};
+
+use sim_system::SimTime;
+
+use std::fmt;
+
+type InitFn<S> = Box<dyn Fn(&mut S, &mut Bus, &SimTime)>;
+type StepFn<S> = Box<dyn Fn(&mut S, &RefBus, &mut Bus, &SimTime)>;
+
+/// クロージャで処理を定義するモデル
+/// Sはモデルが保持する状態の型（状態を持たない場合は()）
+pub struct ClosureModel<S> {
+    input_bus: RefBus,
+    output_bus: Bus,
+    state: S,          // ユーザ定義の状態
+    init_fn: InitFn<S>, // 初期化処理
+    step_fn: StepFn<S>, // 1ステップの処理
+}
+
+impl ClosureModel<()> {
+    /// 状態を持たないモデルを作成する
+    /// init_fn: 初期化処理（出力の初期値を設定する）
+    /// step_fn: 1ステップの処理（入力バスから出力バスを計算する）
+    pub fn new<I, F>(input_def: Vec<SigDef>, output_def: Vec<SigDef>, init_fn: I, step_fn: F) -> anyhow::Result<Self>
+    where
+        I: Fn(&mut Bus, &SimTime) + 'static,
+        F: Fn(&RefBus, &mut Bus, &SimTime) + 'static,
+    {
+        Self::with_state(input_def, output_def, (),
+            move |_, outbus, sim_time| init_fn(outbus, sim_time),
+            move |_, inbus, outbus, sim_time| step_fn(inbus, outbus, sim_time))
+    }
+}
+
+impl<S> ClosureModel<S> {
+    /// ユーザ定義の状態stateを持つモデルを作成する
+    /// 状態は各クロージャに&mutで渡される。init_fnで状態を初期値に戻すことで、繰り返し実行しても同じ結果になる
+    pub fn with_state<I, F>(input_def: Vec<SigDef>, output_def: Vec<SigDef>, state: S, init_fn: I, step_fn: F) -> anyhow::Result<Self>
+    where
+        I: Fn(&mut S, &mut Bus, &SimTime) + 'static,
+        F: Fn(&mut S, &RefBus, &mut Bus, &SimTime) + 'static,
+    {
+        let inbus = RefBus::try_from(input_def).context("ClosureModelの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("ClosureModelの出力バスが不正です。")?;
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            state,
+            init_fn: Box::new(init_fn),
+            step_fn: Box::new(step_fn),
+        })
+    }
+
+    /// 現在の状態
+    pub fn state(&self) -> &S {
+        &self.state
+    }
+}
+
+impl<S: fmt::Debug> fmt::Debug for ClosureModel<S> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ClosureModel")
+         .field("input_bus", &self.input_bus)
+         .field("output_bus", &self.output_bus)
+         .field("state", &self.state)
+         .finish()
+    }
+}
+
+impl<S> ModelCore for ClosureModel<S> {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        (self.init_fn)(&mut self.state, &mut self.output_bus, sim_time);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        (self.step_fn)(&mut self.state, &self.input_bus, &mut self.output_bus, sim_time);
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        if self.input_bus.len() == 0 { None } else { Some(&mut self.input_bus) }
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        if self.output_bus.len() == 0 { None } else { Some(&self.output_bus) }
+    }
+}
+
+#[cfg(test)]
+mod closure_model_test {
+    use super::*;
+    use sim_signal::signal::SigTrait;
+    use crate::simcore::sim_model::{source_models::ConstantFunc, sink_models::SimRecorder, model_core::connect_models};
+    use crate::simcore::sim_system::SimSystem;
+
+    #[test]
+    fn closure_model_test() {
+        // 状態なし：入力の2倍を出力する
+        let src = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.5]).unwrap();
+        let mut gain = ClosureModel::new(
+            vec![SigDef::new("u", "-")],
+            vec![SigDef::new("y", "-")],
+            |outbus, _| outbus[0].set_val(0.0),
+            |inbus, outbus, _| outbus[0].set_val(inbus[0].val() * 2.0),
+        ).unwrap();
+        connect_models(&src, &["u"], &mut gain, &["u"]).unwrap();
+
+        // 状態あり：入力の累積和（前進オイラー積分）
+        let mut integ = ClosureModel::with_state(
+            vec![SigDef::new("y", "-")],
+            vec![SigDef::new("sum", "-")],
+            0.0,
+            |sum, outbus, _| { *sum = 0.0; outbus[0].set_val(0.0); },
+            |sum, inbus, outbus, sim_time| {
+                *sum += inbus[0].val() * sim_time.delta_t();
+                outbus[0].set_val(*sum);
+            },
+        ).unwrap();
+        connect_models(&gain, &["y"], &mut integ, &["y"]).unwrap();
+
+        let mut rcd = SimRecorder::new(vec![SigDef::new("sum", "-")]).unwrap();
+        connect_models(&integ, &["sum"], &mut rcd, &["sum"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(src);
+        sys.regist_model(gain);
+        sys.regist_model(integ);
+        sys.regist_recorder("rcd", rcd);
+
+        sys.run();
+        sys.get_recorder("rcd").unwrap().assert_final_approx("sum", 3.0, 1e-12).unwrap();
+
+        sys.reset();
+        sys.run(); // 初期化で状態が戻るため同じ結果になる
+        sys.get_recorder("rcd").unwrap().assert_final_approx("sum", 3.0, 1e-12).unwrap();
+    }
+}
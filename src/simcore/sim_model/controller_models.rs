@@ -2,6 +2,9 @@
 /// Controllerモデルには、下記のモデルを実装する
 /// 
 /// - PID制御モデル
+/// - 多チャンネルPID制御モデル（独立した複数のPIDループを1ブロックで扱う）
+/// - 離散PID制御モデル（固定周期実行）
+/// - シュミットトリガ（ヒステリシス付き比較器）
 
 use anyhow::{anyhow, Context};
 
@@ -17,15 +20,20 @@ use sim_system::SimTime;
 use super::super::sim_common::Saturation;
 
 /// PIDコントローラモデル
+/// 目標値重み付け（set_setpoint_weights）を設定した場合、比例項は b * 目標値 - 現在値、微分項は c * 目標値 - 現在値 に対して計算する
+/// （積分項は常に 目標値 - 現在値）。b, cを小さくすると目標値変更時の比例キック・微分キックを抑えられる
 #[derive(Debug, Clone)]
 pub struct PIDController {
     integrator: Integrator, // 積分器
-    u_old: f64, // 入力前回値（微分用）
+    u_old: f64, // 微分項の入力前回値（c * 目標値 - 現在値）
     input_bus: RefBus, // 必ず2要素で使用する（1要素目：目標値、2要素目：現在値)
     output_bus: Bus, // 必ず1要素で使用する
     error_bus: Bus, // 誤差（目標値 - 現在値)
     gain: (f64, f64, f64), // PIDゲイン配列 (P, I, D)
     minmax: (f64, f64), // 出力の上下限 (min, max)
+    setpoint_weights: (f64, f64), // 目標値重み (b: 比例項, c: 微分項)
+    sat_steps: u64, // 出力が上下限に張り付いたステップ数
+    total_steps: u64, // 実行ステップ数
 }
 
 impl PIDController {
@@ -59,17 +67,46 @@ impl PIDController {
             error_bus: err_bus,
             gain: gain,
             minmax: minmax,
+            setpoint_weights: (1.0, 1.0),
+            sat_steps: 0,
+            total_steps: 0,
         })
     }
 
     pub fn reset(&mut self) {
         self.integrator.reset(0.0);
     }
+
+    /// 目標値重み付けを設定する（b: 比例項の目標値の重み、c: 微分項の目標値の重み、既定値はどちらも1.0）
+    pub fn set_setpoint_weights(&mut self, b: f64, c: f64) -> anyhow::Result<()> {
+        if !b.is_finite() || !c.is_finite() {
+            return Err(anyhow!("PIDController: 目標値の重みは有限の値で設定してください。b = {}, c = {}", b, c));
+        }
+        self.setpoint_weights = (b, c);
+        Ok(())
+    }
+
+    /// 目標値重み (b, c)
+    pub fn setpoint_weights(&self) -> (f64, f64) {
+        self.setpoint_weights
+    }
+
+    /// 出力が上下限で制限されたステップ数
+    pub fn saturated_steps(&self) -> u64 {
+        self.sat_steps
+    }
+
+    /// 出力が上下限で制限されたステップの割合（0～1、未実行の時は0）
+    pub fn saturation_fraction(&self) -> f64 {
+        if self.total_steps == 0 { 0.0 } else { self.sat_steps as f64 / self.total_steps as f64 }
+    }
 }
 
 impl ModelCore for PIDController {
     fn initialize(&mut self, _sim_time: &SimTime) {
         self.integrator.reset(0.0);
+        self.sat_steps = 0;
+        self.total_steps = 0;
     }
 
     fn finalize(&mut self) {
@@ -86,19 +123,574 @@ impl ModelCore for PIDController {
 
     fn nextstate(&mut self, sim_time: &SimTime) {
         
-        let u = self.input_bus[0].val() - self.input_bus[1].val();
+        let (target, current) = (self.input_bus[0].val(), self.input_bus[1].val());
+        let (b, c) = self.setpoint_weights;
+        let u = target - current;
         self.error_bus[0].set_val(u); // 目標値 - 現在値      
 
         self.integrator.nextstate(sim_time); // 積分する
         
         let gain = self.gain;
         let integ = self.integrator.interface_out().unwrap()[0].val(); // 積分器の結果を取得
-        let diff = (u - self.u_old) / sim_time.delta_t(); // 単純微分
-        let o = gain.0 * u + gain.1 * integ + gain.2 * diff; // 出力計算
+        let u_p = b * target - current; // 比例項の入力
+        let u_d = c * target - current; // 微分項の入力
+        let diff = (u_d - self.u_old) / sim_time.delta_t(); // 単純微分
+        let o = gain.0 * u_p + gain.1 * integ + gain.2 * diff; // 出力計算
         
-        self.output_bus[0].set_val(o.guard_minmax(self.minmax));
+        let out = o.guard_minmax(self.minmax);
+        self.output_bus[0].set_val(out);
 
-        self.u_old = u; // 前回値更新
+        self.total_steps += 1;
+        if out != o { // 上下限で制限された
+            self.sat_steps += 1;
+        }
+
+        self.u_old = u_d; // 前回値更新
         
     }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        vec![
+            ("integral".to_string(), self.integrator.interface_out().unwrap()[0].val()),
+            ("error_old".to_string(), self.u_old),
+        ]
+    }
+
+    fn take_step_error(&mut self) -> Option<anyhow::Error> {
+        self.integrator.take_step_error()
+    }
+}
+
+/// 多チャンネルPIDコントローラモデル
+/// 互いに独立したn個のPIDループ（チャンネル）を1つのブロックで計算する。各チャンネルの動作はPIDControllerと同じ
+/// 入力バスは2n要素で、目標値と現在値を交互に並べる（[目標値0, 現在値0, 目標値1, 現在値1, ...]）。出力バスはn要素
+#[derive(Debug, Clone)]
+pub struct PIDControllerMIMO {
+    integrator: Integrator, // 積分器（n要素）
+    u_old: Vec<f64>, // 入力前回値（微分用）
+    input_bus: RefBus, // 必ず2n要素で使用する（目標値・現在値の交互）
+    output_bus: Bus, // 必ずn要素で使用する
+    error_bus: Bus, // 誤差（目標値 - 現在値) n要素
+    gains: Vec<(f64, f64, f64)>, // チャンネルごとのPIDゲイン (P, I, D)
+    minmax: Vec<(f64, f64)>, // チャンネルごとの出力の上下限 (min, max)
+    sat_steps: Vec<u64>, // チャンネルごとの出力が上下限に張り付いたステップ数
+    total_steps: u64, // 実行ステップ数
+}
+
+impl PIDControllerMIMO {
+    /// ## PIDControllerMIMOの引数定義
+    /// 1. 第1引数：入力バス（2n要素）[目標値0, 現在値0, 目標値1, 現在値1, ...]
+    /// 1. 第2引数：出力バス（n要素）
+    /// 1. 第3引数：gains: チャンネルごとのPIDゲイン (P, I, D)（n要素）
+    /// 1. 第4引数：minmax: チャンネルごとの出力の上下限 (min, max)（n要素）
+    /// 1. 第5引数：solvertype: 積分器のソルバ
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, gains: Vec<(f64, f64, f64)>, minmax: Vec<(f64, f64)>, solvertype: SolverType) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("PIDControllerMIMOの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("PIDControllerMIMOの出力バスが不正です。")?;
+        let n = outbus.len();
+
+        if n == 0 {
+            return Err(anyhow!("PIDControllerMIMO: 出力信号の要素数は1個以上で設定してください"));
+        }
+        if inbus.len() != 2 * n {
+            return Err(anyhow!("PIDControllerMIMO: 入力信号の要素数は出力信号の2倍（目標値・現在値の交互）で設定してください。input.len = {}, output.len = {}", inbus.len(), n));
+        }
+        if gains.len() != n || minmax.len() != n {
+            return Err(anyhow!("PIDControllerMIMO: ゲイン・上下限の要素数は出力信号の要素数と一致している必要があります。gains.len = {}, minmax.len = {}, output.len = {}", gains.len(), minmax.len(), n));
+        }
+
+        // Integrator用のBusを作る
+        let errnames = (0..n).map(|ch| format!("error{}", ch)).collect::<Vec<String>>();
+        let innames = (0..n).map(|ch| format!("integ_in{}", ch)).collect::<Vec<String>>();
+        let mut integ_in = RefBus::try_from(innames.iter().map(|name| SigDef::new(name, "-")).collect::<Vec<SigDef>>()).unwrap();
+        let integ_out = Bus::try_from((0..n).map(|ch| SigDef::new(format!("integ_out{}", ch), "-")).collect::<Vec<SigDef>>()).unwrap();
+
+        let err_bus = Bus::try_from(errnames.iter().map(|name| SigDef::new(name, "-")).collect::<Vec<SigDef>>()).unwrap();
+        let errnames = errnames.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+        let innames = innames.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+        integ_in.connect_to(&err_bus, &errnames, &innames).unwrap();
+
+        let integrator = Integrator::new(integ_in, integ_out, solvertype)?;
+
+        Ok(Self {
+            integrator,
+            u_old: vec![0.0; n],
+            input_bus: inbus,
+            output_bus: outbus,
+            error_bus: err_bus,
+            gains,
+            minmax,
+            sat_steps: vec![0; n],
+            total_steps: 0,
+        })
+    }
+
+    /// チャンネル数
+    pub fn channels(&self) -> usize {
+        self.output_bus.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.integrator.reset(0.0);
+    }
+
+    /// チャンネルchの出力が上下限で制限されたステップ数
+    pub fn saturated_steps(&self, ch: usize) -> u64 {
+        self.sat_steps[ch]
+    }
+
+    /// チャンネルchの出力が上下限で制限されたステップの割合（0～1、未実行の時は0）
+    pub fn saturation_fraction(&self, ch: usize) -> f64 {
+        if self.total_steps == 0 { 0.0 } else { self.sat_steps[ch] as f64 / self.total_steps as f64 }
+    }
+}
+
+impl ModelCore for PIDControllerMIMO {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.integrator.reset(0.0);
+        self.u_old.iter_mut().for_each(|u| *u = 0.0);
+        self.sat_steps.iter_mut().for_each(|cnt| *cnt = 0);
+        self.total_steps = 0;
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        for ch in 0..self.channels() {
+            let u = self.input_bus[2 * ch].val() - self.input_bus[2 * ch + 1].val();
+            self.error_bus[ch].set_val(u); // 目標値 - 現在値
+        }
+
+        self.integrator.nextstate(sim_time); // 全チャンネルまとめて積分する
+
+        let integ = self.integrator.interface_out().unwrap();
+        for ch in 0..self.channels() {
+            let u = self.error_bus[ch].val();
+            let gain = self.gains[ch];
+            let diff = (u - self.u_old[ch]) / sim_time.delta_t(); // 単純微分
+            let o = gain.0 * u + gain.1 * integ[ch].val() + gain.2 * diff; // 出力計算
+
+            let out = o.guard_minmax(self.minmax[ch]);
+            self.output_bus[ch].set_val(out);
+            if out != o { // 上下限で制限された
+                self.sat_steps[ch] += 1;
+            }
+
+            self.u_old[ch] = u; // 前回値更新
+        }
+        self.total_steps += 1;
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        let integ = self.integrator.interface_out().unwrap();
+        (0..self.channels()).flat_map(|ch| vec![
+            (format!("integral{}", ch), integ[ch].val()),
+            (format!("error_old{}", ch), self.u_old[ch]),
+        ]).collect()
+    }
+
+    fn take_step_error(&mut self) -> Option<anyhow::Error> {
+        self.integrator.take_step_error()
+    }
+}
+
+/// 離散PIDコントローラモデル
+/// シミュレーションの刻み幅とは独立した固定の制御周期（sample_time）で差分方程式を実行し、
+/// 周期と周期の間は出力を保持する（組込みソフトの固定周期タスク相当）
+///
+/// - e[k] = 目標値 - 現在値
+/// - I[k] = I[k-1] + Ts * e[k]（前進オイラー）
+/// - D[k] = (e[k] - e[k-1]) / Ts
+/// - u[k] = Kp * e[k] + Ki * I[k] + Kd * D[k]（上下限で制限）
+///
+/// 制御周期がシミュレーションの刻み幅より短い場合は、1ステップにつき1回だけ実行される
+#[derive(Debug, Clone)]
+pub struct DiscretePID {
+    input_bus: RefBus, // 必ず2要素で使用する（1要素目：目標値、2要素目：現在値)
+    output_bus: Bus, // 必ず1要素で使用する
+    gain: (f64, f64, f64), // PIDゲイン配列 (P, I, D)
+    minmax: (f64, f64), // 出力の上下限 (min, max)
+    sample_time: f64, // 制御周期[s]
+    next_sample: f64, // 次回の実行時刻[s]
+    integral: f64, // 誤差の積分値
+    e_old: f64, // 誤差前回値（微分用）
+}
+
+impl DiscretePID {
+    /// 入力バス定義：第1要素目⇒目標値、第2要素⇒現在値
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, gain: (f64, f64, f64), minmax: (f64, f64), sample_time: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("DiscretePIDの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("DiscretePIDの出力バスが不正です。")?;
+
+        if inbus.len() != 2 {
+            return Err(anyhow!("DiscretePID: 入力信号の要素数は2個（1要素目：目標値、2要素目：現在値)で設定してください"))
+        }
+
+        if outbus.len() != 1 {
+            return Err(anyhow!("DiscretePID: 出力信号の要素数は1個で設定してください"))
+        }
+
+        if !sample_time.is_finite() || sample_time <= 0.0 {
+            return Err(anyhow!("DiscretePID: 制御周期は正の有限値で設定してください。sample_time = {}", sample_time))
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            gain,
+            minmax,
+            sample_time,
+            next_sample: 0.0,
+            integral: 0.0,
+            e_old: 0.0,
+        })
+    }
+
+    /// 制御周期[s]
+    pub fn sample_time(&self) -> f64 {
+        self.sample_time
+    }
+}
+
+impl ModelCore for DiscretePID {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.next_sample = sim_time.time();
+        self.integral = 0.0;
+        self.e_old = 0.0;
+        self.output_bus[0].set_val(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        // 刻み幅の丸め誤差で実行周期がずれないよう、少し手前から実行時刻に達したとみなす
+        let t = sim_time.time() + sim_time.delta_t() * 1e-6;
+        if t < self.next_sample {
+            return; // 制御周期の間は出力を保持する
+        }
+        while self.next_sample <= t {
+            self.next_sample += self.sample_time;
+        }
+
+        let ts = self.sample_time;
+        let e = self.input_bus[0].val() - self.input_bus[1].val();
+        self.integral += ts * e;
+        let diff = (e - self.e_old) / ts;
+        let o = self.gain.0 * e + self.gain.1 * self.integral + self.gain.2 * diff;
+
+        self.output_bus[0].set_val(o.guard_minmax(self.minmax));
+        self.e_old = e;
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        vec![
+            ("integral".to_string(), self.integral),
+            ("error_old".to_string(), self.e_old),
+        ]
+    }
+}
+
+/// シュミットトリガ（ヒステリシス付き比較器）モデル
+/// 入力が上側閾値以上になるとON（1.0）、下側閾値以下になるとOFF（0.0）を出力し、その間は前回の状態を保持する。
+/// ノイズを含む信号からチャタリングのない切替信号を作る時に使用する。入力信号ごとに独立して判定する
+#[derive(Debug, Clone)]
+pub struct SchmittTrigger {
+    input_bus: RefBus,
+    output_bus: Bus,
+    thresholds: (f64, f64), // (下側閾値, 上側閾値)
+    state: Vec<bool>, // 信号ごとのON/OFF状態
+}
+
+impl SchmittTrigger {
+    /// 入力と出力の要素数は等しい必要がある。thresholdsは(下側閾値, 上側閾値)で、下側閾値 <= 上側閾値とする
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, thresholds: (f64, f64)) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("SchmittTriggerの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("SchmittTriggerの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("SchmittTrigger: 入力信号と出力信号の要素数は一致している必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()))
+        }
+
+        if thresholds.0 > thresholds.1 {
+            return Err(anyhow!("SchmittTrigger: 閾値は(下側閾値, 上側閾値)の順に、下側閾値 <= 上側閾値で設定してください。thresholds = {:?}", thresholds))
+        }
+
+        let len = inbus.len();
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            thresholds,
+            state: vec![false; len],
+        })
+    }
+
+    /// 信号ごとの現在のON/OFF状態
+    pub fn state(&self) -> &[bool] {
+        &self.state
+    }
+}
+
+impl ModelCore for SchmittTrigger {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.state.iter_mut().for_each(|s| *s = false);
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let (low, high) = self.thresholds;
+        for (idx, state) in self.state.iter_mut().enumerate() {
+            let u = self.input_bus[idx].val();
+            if u >= high {
+                *state = true;
+            } else if u <= low {
+                *state = false;
+            }
+            self.output_bus[idx].set_val(if *state { 1.0 } else { 0.0 });
+        }
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        self.output_bus.iter().zip(self.state.iter())
+                       .map(|(sig, s)| (format!("{}_state", sig.name()), if *s { 1.0 } else { 0.0 }))
+                       .collect()
+    }
+}
+
+#[cfg(test)]
+mod controller_model_test {
+    use super::*;
+
+    #[test]
+    fn pid_saturation_count_test() {
+        let mut databus = Bus::try_from(vec![SigDef::new("target", "-"), SigDef::new("current", "-")]).unwrap();
+        let mut pid = PIDController::new(
+            vec![SigDef::new("target", "-"), SigDef::new("current", "-")],
+            vec![SigDef::new("out", "-")],
+            (2.0, 0.0, 0.0),
+            (-1.0, 1.0),
+            SolverType::Euler,
+        ).unwrap();
+        pid.interface_in().unwrap().connect_to(&databus, &["target", "current"], &["target", "current"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        pid.initialize(&sim_time);
+        assert_eq!(pid.saturation_fraction(), 0.0);
+
+        // 誤差1.0（出力2.0で上限に張り付く）を4ステップ、誤差0.25（出力0.5）を6ステップ
+        for k in 0..10 {
+            sim_time.next();
+            databus[0].set_val(if k < 4 { 1.0 } else { 0.25 });
+            pid.nextstate(&sim_time);
+        }
+
+        assert_eq!(pid.saturated_steps(), 4);
+        assert!((pid.saturation_fraction() - 0.4).abs() < 1e-12);
+
+        pid.initialize(&sim_time);
+        assert_eq!(pid.saturated_steps(), 0);
+    }
+
+    #[test]
+    fn pid_setpoint_weight_test() {
+        // 目標値を0→1にステップ変化させた直後の出力を、b = 1（従来）とb = 0で比較する
+        let run = |b: f64, c: f64| {
+            let mut databus = Bus::try_from(vec![SigDef::new("target", "-"), SigDef::new("current", "-")]).unwrap();
+            let mut pid = PIDController::new(
+                vec![SigDef::new("target", "-"), SigDef::new("current", "-")],
+                vec![SigDef::new("out", "-")],
+                (2.0, 1.0, 0.01),
+                (-100.0, 100.0),
+                SolverType::Euler,
+            ).unwrap();
+            pid.set_setpoint_weights(b, c).unwrap();
+            pid.interface_in().unwrap().connect_to(&databus, &["target", "current"], &["target", "current"]).unwrap();
+
+            let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+            pid.initialize(&sim_time);
+            let mut out = Vec::new();
+            for k in 0..5 {
+                sim_time.next();
+                databus[0].set_val(1.0);
+                databus[1].set_val(0.1 * k as f64);
+                pid.nextstate(&sim_time);
+                out.push(pid.interface_out().unwrap()[0].val());
+            }
+            out
+        };
+
+        let full = run(1.0, 1.0);
+        let weighted = run(0.0, 0.0);
+        assert!(weighted[0] < full[0] - 1.0, "full = {:?}, weighted = {:?}", full, weighted); // 比例・微分キックが無い
+        assert!(weighted[0].abs() < 0.2);
+        // 積分項は重みに依らず誤差全体で計算する（比例・微分項の差はステップ後一定）
+        let diffs = full.iter().zip(weighted.iter()).map(|(f, w)| f - w).collect::<Vec<f64>>();
+        diffs[1..].iter().for_each(|d| assert!((d - 2.0).abs() < 1e-9, "{:?}", diffs));
+
+        // 既定値(1, 1)は重み付けなしと同じ
+        let mut pid = PIDController::new(vec![SigDef::new("t", "-"), SigDef::new("c", "-")], vec![SigDef::new("o", "-")], (1.0, 0.0, 0.0), (-1.0, 1.0), SolverType::Euler).unwrap();
+        assert_eq!(pid.setpoint_weights(), (1.0, 1.0));
+        assert!(pid.set_setpoint_weights(f64::NAN, 1.0).is_err());
+    }
+
+    #[test]
+    fn pid_mimo_test() {
+        // 2つの独立したループを1ブロックで計算し、チャンネルごとに単独のPIDControllerと比較する
+        let mut databus = Bus::try_from(vec![
+            SigDef::new("target0", "-"), SigDef::new("current0", "-"), SigDef::new("target1", "-"), SigDef::new("current1", "-"),
+        ]).unwrap();
+        let gains = vec![(2.0, 1.0, 0.1), (0.5, 3.0, 0.0)];
+        let minmax = vec![(-1.0, 1.0), (-10.0, 10.0)];
+
+        let mut mimo = PIDControllerMIMO::new(
+            vec![SigDef::new("target0", "-"), SigDef::new("current0", "-"), SigDef::new("target1", "-"), SigDef::new("current1", "-")],
+            vec![SigDef::new("out0", "-"), SigDef::new("out1", "-")],
+            gains.clone(),
+            minmax.clone(),
+            SolverType::RungeKutta,
+        ).unwrap();
+        mimo.interface_in().unwrap().connect_to(&databus, &["target0", "current0", "target1", "current1"], &["target0", "current0", "target1", "current1"]).unwrap();
+
+        let mut singles = (0..2).map(|ch| {
+            let (target, current) = (format!("target{}", ch), format!("current{}", ch));
+            let mut pid = PIDController::new(
+                vec![SigDef::new(&target, "-"), SigDef::new(&current, "-")],
+                vec![SigDef::new("out", "-")],
+                gains[ch],
+                minmax[ch],
+                SolverType::RungeKutta,
+            ).unwrap();
+            pid.interface_in().unwrap().connect_to(&databus, &[&target, &current], &[&target, &current]).unwrap();
+            pid
+        }).collect::<Vec<PIDController>>();
+
+        let mut sim_time = SimTime::new(0.0, 2.0, 0.01);
+        mimo.initialize(&sim_time);
+        singles.iter_mut().for_each(|pid| pid.initialize(&sim_time));
+        assert_eq!(mimo.channels(), 2);
+
+        while let Some((_, t)) = sim_time.next() {
+            databus[0].set_val(1.0);
+            databus[1].set_val((3.0 * t).sin());
+            databus[2].set_val(if t < 1.0 { 2.0 } else { -1.0 });
+            databus[3].set_val(0.5 * t);
+            mimo.nextstate(&sim_time);
+            singles.iter_mut().for_each(|pid| pid.nextstate(&sim_time));
+
+            for (ch, pid) in singles.iter().enumerate() {
+                assert!((mimo.interface_out().unwrap()[ch].val() - pid.interface_out().unwrap()[0].val()).abs() < 1e-12, "t = {}, ch = {}", t, ch);
+            }
+        }
+        for (ch, pid) in singles.iter().enumerate() {
+            assert_eq!(mimo.saturated_steps(ch), pid.saturated_steps());
+        }
+        assert!(mimo.saturated_steps(0) > 0);
+        assert_eq!(mimo.internal_states().len(), 4);
+
+        let sigs = |names: &[&str]| names.iter().map(|n| SigDef::new(*n, "-")).collect::<Vec<SigDef>>();
+        assert!(PIDControllerMIMO::new(sigs(&["t0", "m0", "t1"]), sigs(&["o0", "o1"]), gains.clone(), minmax.clone(), SolverType::Euler).is_err());
+        assert!(PIDControllerMIMO::new(sigs(&["t0", "m0", "t1", "m1"]), sigs(&["o0", "o1"]), gains[..1].to_vec(), minmax.clone(), SolverType::Euler).is_err());
+        assert!(PIDControllerMIMO::new(sigs(&[]), sigs(&[]), vec![], vec![], SolverType::Euler).is_err());
+    }
+
+    #[test]
+    fn discrete_pid_test() {
+        let mut databus = Bus::try_from(vec![SigDef::new("target", "-"), SigDef::new("current", "-")]).unwrap();
+        databus[0].set_val(1.0);
+        let mut pid = DiscretePID::new(
+            vec![SigDef::new("target", "-"), SigDef::new("current", "-")],
+            vec![SigDef::new("out", "-")],
+            (0.0, 1.0, 0.0),
+            (-10.0, 10.0),
+            0.1,
+        ).unwrap();
+        pid.interface_in().unwrap().connect_to(&databus, &["target", "current"], &["target", "current"]).unwrap();
+
+        // 刻み幅0.01[s]で0.35[s]まで実行。0.1[s]周期で積分値が0.1ずつ増え、周期の間は保持される
+        let mut sim_time = SimTime::new(0.0, 0.35, 0.01);
+        pid.initialize(&sim_time);
+        let mut out = Vec::new();
+        while sim_time.next().is_some() {
+            pid.nextstate(&sim_time);
+            out.push((sim_time.time(), pid.interface_out().unwrap()[0].val()));
+        }
+        for (t, o) in out.iter() {
+            let expected = (((t + 1e-9) / 0.1).floor() + 1.0) * 0.1; // 時刻0での実行分を含む
+            assert!((o - expected).abs() < 1e-9, "t = {}, out = {}", t, o);
+        }
+
+        // 刻み幅を変えても同じ時刻の出力は変わらない
+        let mut pid2 = pid.clone();
+        let mut sim_time = SimTime::new(0.0, 0.35, 0.05);
+        pid2.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            pid2.nextstate(&sim_time);
+        }
+        assert!((pid2.interface_out().unwrap()[0].val() - out.last().unwrap().1).abs() < 1e-9);
+
+        assert!(DiscretePID::new(vec![SigDef::new("a", "-"), SigDef::new("b", "-")], vec![SigDef::new("c", "-")], (1.0, 0.0, 0.0), (-1.0, 1.0), 0.0).is_err());
+        assert!(DiscretePID::new(vec![SigDef::new("a", "-"), SigDef::new("b", "-")], vec![SigDef::new("c", "-")], (1.0, 0.0, 0.0), (-1.0, 1.0), f64::NAN).is_err());
+        assert!(DiscretePID::new(vec![SigDef::new("a", "-"), SigDef::new("b", "-")], vec![SigDef::new("c", "-")], (1.0, 0.0, 0.0), (-1.0, 1.0), f64::INFINITY).is_err());
+    }
+
+    #[test]
+    fn schmitt_trigger_test() {
+        let mut databus = Bus::try_from(vec![SigDef::new("u", "-")]).unwrap();
+        let mut st = SchmittTrigger::new(vec![SigDef::new("u", "-")], vec![SigDef::new("on", "-")], (0.4, 0.6)).unwrap();
+        st.interface_in().unwrap().connect_to(&databus, &["u"], &["u"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        st.initialize(&sim_time);
+
+        // 閾値付近で揺らぐ入力でもチャタリングしない
+        let inputs = [0.5, 0.61, 0.55, 0.45, 0.59, 0.39, 0.41, 0.5, 0.6, 0.4];
+        let expected = [0.0, 1.0, 1.0, 1.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.0];
+        for (u, e) in inputs.iter().zip(expected.iter()) {
+            sim_time.next();
+            databus[0].set_val(*u);
+            st.nextstate(&sim_time);
+            assert_eq!(st.interface_out().unwrap()[0].val(), *e, "u = {}", u);
+        }
+
+        databus[0].set_val(0.7);
+        st.nextstate(&sim_time);
+        assert_eq!(st.state(), &[true]);
+        st.initialize(&sim_time); // 初期化でOFFに戻る
+        assert_eq!(st.state(), &[false]);
+        assert_eq!(st.interface_out().unwrap()[0].val(), 0.0);
+
+        assert!(SchmittTrigger::new(vec![SigDef::new("u", "-")], vec![SigDef::new("on", "-")], (0.6, 0.4)).is_err());
+        assert!(SchmittTrigger::new(vec![SigDef::new("u", "-")], vec![], (0.4, 0.6)).is_err());
+    }
 }
\ No newline at end of file
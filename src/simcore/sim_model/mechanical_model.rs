@@ -2,4 +2,126 @@ pub mod mass_models;
 pub mod rigid_body_models;
 pub mod spring_models;
 pub mod damper_models;
-pub mod spring_damper_models;
\ No newline at end of file
+pub mod spring_damper_models;
+
+use crate::simcore::sim_signal;
+use sim_signal::signal::SigTrait;
+use sim_signal::bus::Bus;
+
+use anyhow::anyhow;
+
+/// 作用反作用の確認（力の保存則チェック）
+/// 出力バスは(Fx1, Fy1, Fz1, Fx2, Fy2, Fz2)の6要素であることを前提とし、
+/// 端1側の力と端2側の力の和がtolerance以内で0になっているかを確認する
+pub fn check_force_balance(outbus: &Bus, tolerance: f64) -> anyhow::Result<()> {
+    if outbus.len() != 6 {
+        return Err(anyhow!("check_force_balance: 出力バスの要素数は6である必要があります。要素数:{}", outbus.len()));
+    }
+
+    for axis in 0..3 {
+        let sum = outbus[axis].val() + outbus[axis + 3].val();
+        if sum.is_nan() || sum.abs() > tolerance {
+            return Err(anyhow!("力の釣り合いが崩れています。{} + {} = {} (許容値:{})",
+                outbus[axis], outbus[axis + 3], sum, tolerance));
+        }
+    }
+
+    Ok(())
+}
+
+/// 力の釣り合いチェックの設定（ばね・ダンパ系のモデルが持ち、ForceCheckableで有効・無効を切り替える）
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ForceCheck {
+    tolerance: Option<f64>, // 許容値（Noneの時はチェックしない）
+}
+
+impl ForceCheck {
+    /// チェックが有効な時、出力バスの力の釣り合いを確認し、崩れていればモデル名を付けてpanicする（check_force_balanceを参照）
+    pub fn check(&self, model_name: &str, outbus: &Bus) {
+        if let Some(tol) = self.tolerance {
+            if let Err(e) = check_force_balance(outbus, tol) {
+                panic!("{}: {}", model_name, e);
+            }
+        }
+    }
+}
+
+/// 力の釣り合いチェックを設定できるモデル（ばね・ダンパ系のモデル）
+/// force_check_mutのみ実装し、有効化・無効化は既定の実装を使用する
+pub trait ForceCheckable {
+    /// 力の釣り合いチェックの設定
+    fn force_check_mut(&mut self) -> &mut ForceCheck;
+
+    /// 力の釣り合いチェックを有効にする
+    /// 各ステップで端1側と端2側の力の和がtolerance以内で0になっているかを確認し、崩れていればpanicする
+    fn enable_force_check(&mut self, tolerance: f64) {
+        self.force_check_mut().tolerance = Some(tolerance);
+    }
+
+    /// 力の釣り合いチェックを無効にする
+    fn disable_force_check(&mut self) {
+        self.force_check_mut().tolerance = None;
+    }
+}
+
+#[cfg(test)]
+mod mechanical_model_test {
+    use super::*;
+    use sim_signal::signal::SigDef;
+
+    #[test]
+    fn force_balance_test() {
+        let mut bus = Bus::try_from(vec![
+            SigDef::new("fx1", "N"), SigDef::new("fy1", "N"), SigDef::new("fz1", "N"),
+            SigDef::new("fx2", "N"), SigDef::new("fy2", "N"), SigDef::new("fz2", "N"),
+        ]).unwrap();
+
+        bus.import_matrix(&nalgebra::DMatrix::from_vec(6, 1, vec![1.0, -2.0, 3.0, -1.0, 2.0, -3.0]));
+        assert!(check_force_balance(&bus, 1e-12).is_ok());
+
+        bus[4].set_val(2.1);
+        assert!(check_force_balance(&bus, 1e-12).is_err());
+        assert!(check_force_balance(&bus, 0.2).is_ok());
+    }
+
+    #[test]
+    fn force_balance_nan_test() {
+        let mut bus = Bus::try_from(vec![
+            SigDef::new("fx1", "N"), SigDef::new("fy1", "N"), SigDef::new("fz1", "N"),
+            SigDef::new("fx2", "N"), SigDef::new("fy2", "N"), SigDef::new("fz2", "N"),
+        ]).unwrap();
+
+        bus[0].set_val(f64::NAN);
+        assert!(check_force_balance(&bus, 1e-12).is_err());
+    }
+
+    struct CheckedModel {
+        force_check: ForceCheck,
+    }
+
+    impl ForceCheckable for CheckedModel {
+        fn force_check_mut(&mut self) -> &mut ForceCheck {
+            &mut self.force_check
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "CheckedModel: 力の釣り合いが崩れています。")]
+    fn force_check_test() {
+        let mut bus = Bus::try_from(vec![
+            SigDef::new("fx1", "N"), SigDef::new("fy1", "N"), SigDef::new("fz1", "N"),
+            SigDef::new("fx2", "N"), SigDef::new("fy2", "N"), SigDef::new("fz2", "N"),
+        ]).unwrap();
+        bus[0].set_val(1.0);
+
+        // 無効の時は釣り合いが崩れていてもチェックしない
+        let mut mdl = CheckedModel { force_check: ForceCheck::default() };
+        mdl.force_check.check("CheckedModel", &bus);
+        mdl.enable_force_check(1e-9);
+        mdl.disable_force_check();
+        mdl.force_check.check("CheckedModel", &bus);
+
+        mdl.enable_force_check(1e-9);
+        mdl.force_check.check("CheckedModel", &bus);
+    }
+}
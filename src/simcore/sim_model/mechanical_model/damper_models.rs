@@ -6,6 +6,8 @@ use crate::prelude::{*, sim_signal::signal::SigTrait};
 
 use anyhow::{anyhow, Context};
 
+use super::{ForceCheck, ForceCheckable};
+
 /// 単純なダンパモデル（自然長からの変位（ダンパ端1, 2の距離）に応じて力を出力する単純なもの）
 /// ダンパの変位は伸縮する方向のみであると仮定したモデル
 #[derive(Debug, Clone)]
@@ -14,6 +16,7 @@ pub struct SimpleDamper {
     damper_length: f64, // ダンパ長[m]
     input_bus: RefBus, // 必ず6要素で使用する( ダンパ端1の座標(x1, y1, z1)、ダンパ端2の座標（x2, y2, z2) )
     output_bus: Bus, // 必ず6要素で使用する( ダンパ端1側の力(Fx1, Fy1, Fz1), ダンパ端2側の力(Fx2, Fy2, Fz2) この力は反対方向を向いている＝合力は0)
+    force_check: ForceCheck, // 力の釣り合いチェックの設定
 }
 
 impl SimpleDamper {
@@ -37,10 +40,17 @@ impl SimpleDamper {
             damper_length: 0.0, 
             input_bus: inbus,
             output_bus: outbus,
+            force_check: ForceCheck::default(),
         })
     }
 }
 
+impl ForceCheckable for SimpleDamper {
+    fn force_check_mut(&mut self) -> &mut ForceCheck {
+        &mut self.force_check
+    }
+}
+
 impl ModelCore for SimpleDamper {
     fn initialize(&mut self, _sim_time: &sim_system::SimTime) { 
         let dx = self.input_bus[0].val() - self.input_bus[3].val();
@@ -81,6 +91,7 @@ impl ModelCore for SimpleDamper {
         self.output_bus[4].set_val(-fy);
         self.output_bus[5].set_val(-fz);
 
+        self.force_check.check("SimpleDamper", &self.output_bus);
     }
 }
 
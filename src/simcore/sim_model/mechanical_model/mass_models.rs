@@ -74,6 +74,14 @@ impl ModelCore for MassModel {
     fn nextstate(&mut self, sim_time: &sim_system::SimTime) {
         self.model.nextstate(sim_time);
     }
+
+    fn direct_feedthrough(&self) -> bool {
+        self.model.direct_feedthrough()
+    }
+
+    fn take_step_error(&mut self) -> Option<anyhow::Error> {
+        self.model.take_step_error()
+    }
 }
 
 #[cfg(test)]
@@ -1,11 +1,14 @@
 /// # ばねダンパモデル
 /// - 単純なばねダンパモデル（質量の無い　理想ばね）
+/// - 並列ばねダンパモデル（複数のばね・ダンパを並列に接続したもの）
 /// 
 
 use crate::prelude::{*, sim_signal::signal::SigTrait};
 
 use anyhow::{anyhow, Context};
 
+use super::{ForceCheck, ForceCheckable};
+
 use super::{spring_models, damper_models};
 
 /// 単純なばねモデル（自然長からの変位（ばね端1, 2の距離）に応じて力を出力する単純なもの）
@@ -18,6 +21,7 @@ pub struct SimpleSpringDamper {
     damper_length: f64, // ダンパ長[m]
     input_bus: RefBus, // 必ず6要素で使用する( ばね端1の座標(x1, y1, z1)、ばね端2の座標（x2, y2, z2) )
     output_bus: Bus, // 必ず6要素で使用する( ばね端1側の力(Fx1, Fy1, Fz1), ばね端2側の力(Fx2, Fy2, Fz2) この力は反対方向を向いている＝合力は0)
+    force_check: ForceCheck, // 力の釣り合いチェックの設定
 }
 
 impl SimpleSpringDamper {
@@ -47,10 +51,17 @@ impl SimpleSpringDamper {
             damper_length: 0.0,
             input_bus: inbus,
             output_bus: outbus,
+            force_check: ForceCheck::default(),
         })
     }
 }
 
+impl ForceCheckable for SimpleSpringDamper {
+    fn force_check_mut(&mut self) -> &mut ForceCheck {
+        &mut self.force_check
+    }
+}
+
 impl ModelCore for SimpleSpringDamper {
     fn initialize(&mut self, _sim_time: &sim_system::SimTime) { 
         let dx = self.input_bus[0].val() - self.input_bus[3].val();
@@ -97,6 +108,155 @@ impl ModelCore for SimpleSpringDamper {
         self.output_bus[4].set_val(-fy);
         self.output_bus[5].set_val(-fz);
 
+        self.force_check.check("SimpleSpringDamper", &self.output_bus);
+    }
+}
+
+/// 並列ばねダンパモデルの1要素（ばねとダンパの組）の設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpringDamperElement {
+    natural_length: f64,  // ばねの自然長[m]（length >= 0 値のみ許可）
+    spring_constant: f64, // ばね定数[N/m]（0でダンパのみの要素）
+    damping_coeff: f64,   // 減衰係数[N/(m/s)]（0でばねのみの要素）
+}
+
+impl SpringDamperElement {
+    pub fn new(natural_length: f64, spring_constant: f64, damping_coeff: f64) -> Self {
+        Self {
+            natural_length,
+            spring_constant,
+            damping_coeff,
+        }
+    }
+}
+
+/// 並列ばねダンパモデル
+/// 同じ2端点の間に並列に接続した複数のばね・ダンパ（要素ごとに自然長・ばね定数・減衰係数が異なってよい）の合力を出力する
+/// 入出力はSimpleSpringDamperと同じで、出力をそのままMassModelの力入力に接続できる（要素ごとの力を足し合わせる必要が無い）
+#[derive(Debug, Clone)]
+pub struct ParallelSpringDamper {
+    elements: Vec<SpringDamperElement>, // 並列に接続した要素
+    damper_length: f64, // ダンパ長[m]（前ステップの端点間距離）
+    input_bus: RefBus, // 必ず6要素で使用する( 端1の座標(x1, y1, z1)、端2の座標（x2, y2, z2) )
+    output_bus: Bus, // 必ず6要素で使用する( 端1側の力(Fx1, Fy1, Fz1), 端2側の力(Fx2, Fy2, Fz2) この力は反対方向を向いている＝合力は0)
+    force_check: ForceCheck, // 力の釣り合いチェックの設定
+}
+
+impl ParallelSpringDamper {
+    /// ## ParallelSpringDamperの引数定義
+    /// 1. 第1引数：入力バス（6要素）[x1, y1, z1, x2, y2, z2]
+    /// 1. 第2引数：出力バス（6要素）[Fx1, Fy1, Fz1, Fx2, Fy2, Fz2]
+    /// 1. 第3引数：elements: 並列に接続する要素（1つ以上）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, elements: Vec<SpringDamperElement>) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("ParallelSpringDamperの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("ParallelSpringDamperの出力バスが不正です。")?;
+
+        if inbus.len() != 6 {
+            return Err(anyhow!("ParallelSpringDamper:inputbusの要素数は6である必要があります。\n"));
+        }
+        if outbus.len() != 6 {
+            return Err(anyhow!("ParallelSpringDamper:outbusの要素数は6である必要があります。\n"));
+        }
+        if elements.is_empty() {
+            return Err(anyhow!("ParallelSpringDamper:要素を1つ以上設定してください。\n"));
+        }
+
+        for (idx, elem) in elements.iter().enumerate() {
+            if elem.natural_length < 0.0 {
+                return Err(anyhow!("ParallelSpringDamper:要素{}のばねの自然長>=0である必要があります。\n", idx));
+            }
+            if elem.spring_constant < 0.0 {
+                return Err(anyhow!("ParallelSpringDamper:要素{}のばね定数>=0である必要があります。\n", idx));
+            }
+        }
+
+        Ok(Self {
+            elements,
+            damper_length: 0.0,
+            input_bus: inbus,
+            output_bus: outbus,
+            force_check: ForceCheck::default(),
+        })
+    }
+
+    /// 並列に接続した要素
+    pub fn elements(&self) -> &[SpringDamperElement] {
+        &self.elements
+    }
+
+    /// 合成ばね定数[N/m]（各要素のばね定数の和）
+    pub fn total_spring_constant(&self) -> f64 {
+        self.elements.iter().map(|e| e.spring_constant).sum()
+    }
+
+    /// 合成減衰係数[N/(m/s)]（各要素の減衰係数の和）
+    pub fn total_damping_coeff(&self) -> f64 {
+        self.elements.iter().map(|e| e.damping_coeff).sum()
+    }
+
+    /// 合力が0になる端点間距離[m]（各要素の自然長をばね定数で重み付けした平均。ばね要素が無い場合はNone）
+    pub fn equilibrium_length(&self) -> Option<f64> {
+        let k = self.total_spring_constant();
+        if k > 0.0 {
+            Some(self.elements.iter().map(|e| e.spring_constant * e.natural_length).sum::<f64>() / k)
+        } else {
+            None
+        }
+    }
+}
+
+impl ForceCheckable for ParallelSpringDamper {
+    fn force_check_mut(&mut self) -> &mut ForceCheck {
+        &mut self.force_check
+    }
+}
+
+impl ModelCore for ParallelSpringDamper {
+    fn initialize(&mut self, _sim_time: &sim_system::SimTime) {
+        let dx = self.input_bus[0].val() - self.input_bus[3].val();
+        let dy = self.input_bus[1].val() - self.input_bus[4].val();
+        let dz = self.input_bus[2].val() - self.input_bus[5].val();
+
+        self.damper_length = ((dx * dx) + (dy * dy) + (dz * dz)).sqrt();
+    }
+
+    fn finalize(&mut self) { }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, sim_time: &sim_system::SimTime) {
+        let dx = self.input_bus[0].val() - self.input_bus[3].val();
+        let dy = self.input_bus[1].val() - self.input_bus[4].val();
+        let dz = self.input_bus[2].val() - self.input_bus[5].val();
+
+        let distance = ((dx * dx) + (dy * dy) + (dz * dz)).sqrt();
+        let velocity = (self.damper_length - distance) / sim_time.delta_t(); // 端点間距離の縮む速度
+
+        // 各要素のばね力とダンパ力の合計（全要素で同じ端点を共有するため力の向きは共通）
+        let force = self.elements.iter()
+                        .map(|e| e.spring_constant * (e.natural_length - distance) + e.damping_coeff * velocity)
+                        .sum::<f64>();
+
+        let fx = force * dx / distance;
+        let fy = force * dy / distance;
+        let fz = force * dz / distance;
+
+        self.damper_length = distance;
+
+        self.output_bus[0].set_val(fx);
+        self.output_bus[1].set_val(fy);
+        self.output_bus[2].set_val(fz);
+        self.output_bus[3].set_val(-fx);
+        self.output_bus[4].set_val(-fy);
+        self.output_bus[5].set_val(-fz);
+
+        self.force_check.check("ParallelSpringDamper", &self.output_bus);
     }
 }
 
@@ -105,7 +265,78 @@ mod damper_models_test {
     
     use crate::prelude::{*};
 
-    use super::SimpleSpringDamper;
+    use super::{SimpleSpringDamper, ParallelSpringDamper, SpringDamperElement};
+
+    /// 壁とおもり（1kg、初期位置1.5m、初速1m/s）の間をばねダンパモデルでつなぎ、おもりの位置の記録を返す
+    fn run_wall_mass<T: ModelCore>(make_model: impl FnOnce(Vec<SigDef>, Vec<SigDef>) -> T, end_time: f64) -> Vec<f64> {
+        let wall = ConstantFunc::new(
+            MakeSigList![("wall_x", "m"), ("wall_y", "m"), ("wall_z", "m")],
+            &[0.0, 0.0, 0.0]
+        ).unwrap();
+
+        let mut ball = MassModel::new(
+            MakeSigList![("ball_fx", "N"), ("ball_fy", "N"), ("ball_fz", "N")],
+            MakeSigList![("ball_x", "m"), ("ball_y", "m"), ("ball_z", "m"), ("ball_vx", "m/s"), ("ball_vy", "m/s"), ("ball_vz", "m/s")],
+            1.0,
+            (1.5, 0.0, 0.0),
+            (1.0, 0.0, 0.0),
+            SolverType::RungeKutta
+        ).unwrap();
+
+        let mut mdl = make_model(
+            MakeSigList![("wall_x", "m"), ("wall_y", "m"), ("wall_z", "m"), ("ball_x", "m"), ("ball_y", "m"), ("ball_z", "m")],
+            MakeSigList![("wall_fx", "N"), ("wall_fy", "N"), ("wall_fz", "N"), ("ball_fx", "N"), ("ball_fy", "N"), ("ball_fz", "N")],
+        );
+        let mut scp = SimRecorder::new(MakeSigList![("ball_x", "m")]).unwrap();
+
+        connect_models(&wall, &["wall_x", "wall_y", "wall_z"], &mut mdl, &["wall_x", "wall_y", "wall_z"]).unwrap();
+        connect_models(&ball, &["ball_x", "ball_y", "ball_z"], &mut mdl, &["ball_x", "ball_y", "ball_z"]).unwrap();
+        connect_models(&mdl, &["ball_fx", "ball_fy", "ball_fz"], &mut ball, &["ball_fx", "ball_fy", "ball_fz"]).unwrap();
+        connect_models(&ball, &["ball_x"], &mut scp, &["ball_x"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, end_time, 0.001);
+        sys.regist_model(wall);
+        sys.regist_model(ball);
+        sys.regist_model(mdl);
+        sys.regist_recorder("scp1", scp);
+        sys.run();
+
+        sys.get_result("scp1").unwrap().get_signal("ball_x").unwrap().to_vec()
+    }
+
+    /// 自然長の等しい要素の並列接続は、ばね定数・減衰係数の和を持つSimpleSpringDamperと同じ動きになる
+    /// 自然長の異なる要素の並列接続は、ばね定数で重み付けした自然長の位置に静定する
+    #[test]
+    fn parallel_spring_damper_test() {
+        let parallel = run_wall_mass(|i, o| {
+            let mut mdl = ParallelSpringDamper::new(i, o, vec![SpringDamperElement::new(0.8, 1.0, 0.4), SpringDamperElement::new(0.8, 2.0, 0.2)]).unwrap();
+            mdl.enable_force_check(1e-9);
+            mdl
+        }, 5.0);
+        let simple = run_wall_mass(|i, o| SimpleSpringDamper::new(i, o, 0.8, 3.0, 0.6).unwrap(), 5.0);
+        assert_eq!(parallel.len(), simple.len());
+        parallel.iter().zip(simple.iter()).for_each(|(p, s)| assert!((p - s).abs() < 1e-9, "{} != {}", p, s));
+
+        let elements = vec![SpringDamperElement::new(0.8, 1.0, 0.5), SpringDamperElement::new(1.2, 3.0, 1.5)];
+        let mdl = ParallelSpringDamper::new(
+            MakeSigList![("x1", "m"), ("y1", "m"), ("z1", "m"), ("x2", "m"), ("y2", "m"), ("z2", "m")],
+            MakeSigList![("fx1", "N"), ("fy1", "N"), ("fz1", "N"), ("fx2", "N"), ("fy2", "N"), ("fz2", "N")],
+            elements.clone(),
+        ).unwrap();
+        assert_eq!(mdl.total_spring_constant(), 4.0);
+        assert_eq!(mdl.total_damping_coeff(), 2.0);
+        let eq = mdl.equilibrium_length().unwrap();
+        assert!((eq - 1.1).abs() < 1e-12);
+
+        let settled = run_wall_mass(|i, o| ParallelSpringDamper::new(i, o, elements).unwrap(), 30.0);
+        assert!((settled.last().unwrap() - eq).abs() < 1e-6, "{}", settled.last().unwrap());
+
+        let sig6 = |p: &str| (0..6).map(|k| SigDef::new(format!("{}{}", p, k), "-")).collect::<Vec<SigDef>>();
+        assert!(ParallelSpringDamper::new(sig6("i"), sig6("o"), vec![]).is_err());
+        assert!(ParallelSpringDamper::new(sig6("i"), sig6("o"), vec![SpringDamperElement::new(-1.0, 1.0, 0.0)]).is_err());
+        assert!(ParallelSpringDamper::new(sig6("i"), sig6("o"), vec![SpringDamperElement::new(1.0, -1.0, 0.0)]).is_err());
+        assert!(ParallelSpringDamper::new(sig6("i"), sig6("o")[..3].to_vec(), vec![SpringDamperElement::new(1.0, 1.0, 0.0)]).is_err());
+    }
 
     /// ダンパ（減衰係数1.0N/(m/s))におもり（1kg)を接続　おもりに初速10m/sを与えてスタートするシミュレーション
     /// ダンパの効果により速度が徐々に落ちていく動きとなる
@@ -132,6 +363,7 @@ mod damper_models_test {
             1.0,
             0.4,
         ).unwrap();
+        mdl.enable_force_check(1e-9); // 作用反作用が崩れていないことを確認
 
         let mut scp = SimRecorder::new(
             MakeSigList![("ball_x", "m"), ("ball_fx", "N")]
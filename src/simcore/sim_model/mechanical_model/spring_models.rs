@@ -6,6 +6,8 @@ use crate::prelude::{*, sim_signal::signal::SigTrait};
 
 use anyhow::{anyhow, Context};
 
+use super::{ForceCheck, ForceCheckable};
+
 /// 単純なばねモデル（自然長からの変位（ばね端1, 2の距離）に応じて力を出力する単純なもの）
 /// ばねの変形は伸縮する方向のみであると仮定したモデル
 #[derive(Debug, Clone)]
@@ -14,6 +16,7 @@ pub struct SimpleSpring {
     spring_constant: f64, // ばね定数[N/m]
     input_bus: RefBus, // 必ず6要素で使用する( ばね端1の座標(x1, y1, z1)、ばね端2の座標（x2, y2, z2) )
     output_bus: Bus, // 必ず6要素で使用する( ばね端1側の力(Fx1, Fy1, Fz1), ばね端2側の力(Fx2, Fy2, Fz2) この力は反対方向を向いている＝合力は0)
+    force_check: ForceCheck, // 力の釣り合いチェックの設定
 }
 
 impl SimpleSpring {
@@ -41,10 +44,17 @@ impl SimpleSpring {
             spring_constant: spring_constant,
             input_bus: inbus,
             output_bus: outbus,
+            force_check: ForceCheck::default(),
         })
     }
 }
 
+impl ForceCheckable for SimpleSpring {
+    fn force_check_mut(&mut self) -> &mut ForceCheck {
+        &mut self.force_check
+    }
+}
+
 impl ModelCore for SimpleSpring {
     fn initialize(&mut self, _sim_time: &sim_system::SimTime) { }
 
@@ -77,6 +87,7 @@ impl ModelCore for SimpleSpring {
         self.output_bus[4].set_val(-fy);
         self.output_bus[5].set_val(-fz);
 
+        self.force_check.check("SimpleSpring", &self.output_bus);
     }
 }
 
@@ -109,6 +120,7 @@ mod spring_models_test {
             0.8,
             1.0
         ).unwrap();
+        spring.enable_force_check(1e-9); // 作用反作用が崩れていないことを確認
 
         let mut scp = SimRecorder::new(
             MakeSigList![("ball_x", "m"), ("ball_fx", "N")]
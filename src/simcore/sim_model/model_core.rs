@@ -1,6 +1,7 @@
 
 use crate::simcore::{sim_signal, sim_system};
 use sim_signal::bus::{Bus, RefBus};
+use sim_signal::signal::SigDef;
 
 use sim_system::SimTime;
 
@@ -15,6 +16,12 @@ pub trait ModelCore {
     /// シミュレーション時間を1ステップ進める
     fn nextstate(&mut self, sim_time: &SimTime);
 
+    /// 全モデルのinitialize・nextstateが終わった後に呼び出される処理
+    /// 実行順に依らず、そのステップで確定した入力値を取り込みたいモデル（単位遅延等）のみオーバーライドする
+    fn post_step(&mut self, _sim_time: &SimTime) {
+        // 処理なし
+    }
+
     /// 終了処理
     fn finalize(&mut self);
 
@@ -23,14 +30,90 @@ pub trait ModelCore {
 
     /// 出力インターフェース
     fn interface_out(&self) -> Option<&Bus>;
+
+    /// 時刻tにおける出力を計算する（モデルの状態は進めない）
+    /// 出力信号のサンプラ（Signal::set_sampler）から計算するため、ソルバの中間段で入力に使われる値と一致する。
+    /// サンプラを設定していないモデル（状態を持つモデル等）はNoneを返し、呼び出し側は現在の出力値（固定値）を使用する
+    fn eval_output_at(&self, t: f64) -> Option<Bus> {
+        let outbus = self.interface_out()?;
+        if !outbus.iter().any(|sig| sig.has_sampler()) {
+            return None;
+        }
+        let mut bus = Bus::try_from(outbus.get_sigdef()).ok()?;
+        bus.iter_mut().zip(outbus.to_vec_f64_at(t)).for_each(|(sig, val)| sig.set_val(val));
+        Some(bus)
+    }
+
+    /// 内部状態の一覧（状態名, 値）
+    /// 出力バスに現れない状態を記録・デバッグするために使用する（SimRecorder::subscribe_statesを参照）
+    /// 状態を持つモデルのみオーバーライドする
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        Vec::new()
+    }
+
+    /// 出力が同じステップの入力に直接依存する（直達がある）かどうか
+    /// 積分器など出力が内部状態のみで決まるモデルはfalseを返す。SimSystem::resolve_order・check_feedback_loopsで代数ループの判定に使用する
+    /// 既定値は安全側のtrueとし、直達が無いことが分かっているモデルのみオーバーライドする
+    fn direct_feedthrough(&self) -> bool {
+        true
+    }
+
+    /// 接続しなくてもよい入力信号名の一覧。SimSystem::validateの未接続チェックの対象外とする
+    /// 未接続の入力は既定値で動作するモデル（RefSignal::val_orで参照する）のみオーバーライドする
+    fn optional_inputs(&self) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// 入力信号の接続一覧（信号名, 接続元の信号名）。入力インターフェースが無いモデルは空
+    fn connection_report(&mut self) -> Vec<(String, Option<String>)> {
+        self.interface_in().map_or(Vec::new(), |inbus| inbus.connection_report())
+    }
+
+    /// 直近のnextstateで発生したエラーを取り出す（取り出した後はNoneに戻す）
+    /// nextstateは戻り値を持たないため、ソルバの試行回数超過などでステップを進められなかった場合はモデルにエラーを保持しておき、
+    /// SimSystemが各モデルのnextstateの後に確認してtry_run等のエラーとして返す。エラーが発生し得るモデルのみオーバーライドする
+    fn take_step_error(&mut self) -> Option<anyhow::Error> {
+        None
+    }
+
+    /// 現在のパラメータでモデルを生成するRustの式（SimSystem::to_rust_sourceで使用する）
+    /// 生成したコードはuse mint_sim::prelude::*;を前提とする。パラメータを出力できるモデルのみオーバーライドし、既定値はNone（生成できない）とする
+    fn to_source(&self) -> Option<String> {
+        None
+    }
+}
+
+/// 信号定義の一覧を生成するRustの式（ModelCore::to_sourceの実装で使用する）
+pub(crate) fn sigdefs_source(defs: &[SigDef]) -> String {
+    format!("vec![{}]", defs.iter().map(|def| format!("SigDef::new({:?}, {:?})", def.name(), def.unit())).collect::<Vec<String>>().join(", "))
+}
+
+/// 数値の一覧をスライスとして生成するRustの式（ModelCore::to_sourceの実装で使用する）
+pub(crate) fn values_source<'a>(values: impl Iterator<Item = &'a f64>) -> String {
+    format!("&[{}]", values.map(|v| format!("{:?}", v)).collect::<Vec<String>>().join(", "))
 }
 
 /// 信号の接続
+/// 接続元と接続先の信号の単位が異なる場合はエラーとする（RefBus::connect_toを参照）
 pub fn connect_models<S: ModelCore, D:ModelCore>(srcmodel: &S, srclist: &[&str], dstmodel: &mut D, dstlist: &[&str]) -> anyhow::Result<()>{
+    connect_models_with(srcmodel, srclist, dstmodel, dstlist, true)
+}
+
+/// 単位を確認しない信号の接続
+/// 単位の表記が異なるだけで同じ量を表す信号（"sec"と"s"など）を接続する時に使用する
+pub fn connect_models_unchecked<S: ModelCore, D:ModelCore>(srcmodel: &S, srclist: &[&str], dstmodel: &mut D, dstlist: &[&str]) -> anyhow::Result<()>{
+    connect_models_with(srcmodel, srclist, dstmodel, dstlist, false)
+}
+
+fn connect_models_with<S: ModelCore, D:ModelCore>(srcmodel: &S, srclist: &[&str], dstmodel: &mut D, dstlist: &[&str], check_units: bool) -> anyhow::Result<()>{
     if let Some(srcbus) = srcmodel.interface_out() {
         
         if let Some(inbus) = dstmodel.interface_in() {
-            inbus.connect_to(srcbus, srclist, dstlist)?
+            if check_units {
+                inbus.connect_to(srcbus, srclist, dstlist)?
+            } else {
+                inbus.connect_to_unchecked(srcbus, srclist, dstlist)?
+            }
         } else {
             return Err(anyhow!("入力インターフェースが定義されていないモデルです。信号の接続はできません。"));
         }
@@ -40,4 +123,26 @@ pub fn connect_models<S: ModelCore, D:ModelCore>(srcmodel: &S, srclist: &[&str],
     }
 
     Ok(())
+}
+
+/// 同じ信号名どうしの信号の接続
+/// 接続先の入力信号をすべて、接続元の出力バスの同じ名前の信号に接続する
+/// 接続元に同じ名前の信号が無い入力信号がある場合はエラーとする（いずれの信号も接続しない）
+pub fn connect_models_auto<S: ModelCore, D:ModelCore>(srcmodel: &S, dstmodel: &mut D) -> anyhow::Result<()> {
+    let Some(srcbus) = srcmodel.interface_out() else {
+        return Err(anyhow!("引数に指定されているモデルは出力インターフェースが定義されていないモデルです。信号の接続はできません。"));
+    };
+    let Some(inbus) = dstmodel.interface_in() else {
+        return Err(anyhow!("入力インターフェースが定義されていないモデルです。信号の接続はできません。"));
+    };
+
+    let names = inbus.get_sigdef().iter().map(|sig| sig.name().to_string()).collect::<Vec<String>>();
+    let notfoundlist = names.iter().filter(|name| srcbus.get_by_name(name.as_str()).is_none())
+                                   .map(|name| format!("\"{}\"", name)).collect::<Vec<String>>();
+    if !notfoundlist.is_empty() {
+        return Err(anyhow!("connect_models_auto: 接続元の出力に下記の信号が見つかりませんでした。\n{}", notfoundlist.join(", ")));
+    }
+
+    let siglist = names.iter().map(|name| name.as_str()).collect::<Vec<&str>>();
+    inbus.connect_to(srcbus, &siglist, &siglist)
 }
\ No newline at end of file
@@ -0,0 +1,448 @@
+/// # 非理想要素モデル
+/// 理想的なモデルに現実の非理想要素（遅れ、ノイズ、量子化など）を付加するモデルを実装する
+///
+/// - センサモデル（むだ時間 + ノイズ + 一次遅れフィルタ + 量子化）
+/// - アクチュエータモデル（レートリミット + 一次遅れ + 出力飽和）
+use anyhow::{anyhow, Context};
+
+use crate::simcore::{sim_model, sim_signal, sim_system};
+use sim_model::model_core::{ModelCore};
+
+use sim_signal::signal::{SigDef};
+use sim_signal::bus::{Bus, RefBus};
+
+use sim_system::SimTime;
+
+use super::super::sim_common::Saturation;
+
+use std::collections::VecDeque;
+use std::f64::consts::PI;
+
+/// 一次遅れ要素を1ステップ進める（離散化は厳密解による）
+/// time_constantが0以下の時は遅れなしとして入力をそのまま返す
+fn first_order_lag(y: f64, u: f64, time_constant: f64, delta_t: f64) -> f64 {
+    if time_constant <= 0.0 {
+        return u;
+    }
+    y + (u - y) * (1.0 - (-delta_t.abs() / time_constant).exp())
+}
+
+/// 量子化（resolutionの整数倍に丸める）
+/// resolutionが0以下の時は量子化しない
+fn quantize(val: f64, resolution: f64) -> f64 {
+    if resolution <= 0.0 {
+        return val;
+    }
+    (val / resolution).round() * resolution
+}
+
+/// 正規分布ノイズの生成器（xorshift64* + Box-Muller法）
+/// 外部クレートに依存せず、シードを固定すれば同じ系列を再現できる
+#[derive(Debug, Clone)]
+pub(crate) struct NoiseGen {
+    seed: u64,
+    state: u64,
+}
+
+impl NoiseGen {
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut gen = Self { seed, state: 0 };
+        gen.reset();
+        gen
+    }
+
+    /// 乱数系列をシードの状態に戻す
+    pub(crate) fn reset(&mut self) {
+        self.state = if self.seed == 0 { 0x9E37_79B9_7F4A_7C15 } else { self.seed }; // 状態0では系列が0に張り付くため避ける
+    }
+
+    /// (0, 1]の一様乱数
+    pub(crate) fn uniform(&mut self) -> f64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        let r = self.state.wrapping_mul(0x2545_F491_4F6C_DD1D);
+        ((r >> 11) as f64 + 1.0) / (1u64 << 53) as f64
+    }
+
+    /// 平均0・標準偏差stdの正規乱数
+    pub(crate) fn gaussian(&mut self, std: f64) -> f64 {
+        if std <= 0.0 {
+            return 0.0;
+        }
+        let u1 = self.uniform();
+        let u2 = self.uniform();
+        std * (-2.0 * u1.ln()).sqrt() * (2.0 * PI * u2).cos()
+    }
+}
+
+/// センサモデルの設定
+/// 各要素は0を指定すると無効になる
+#[derive(Debug, Clone)]
+pub struct SensorSetting {
+    pub delay: f64,         // むだ時間[s]（刻み幅単位に丸められる）
+    pub noise_std: f64,     // 加算ノイズの標準偏差
+    pub time_constant: f64, // 一次遅れフィルタの時定数[s]
+    pub resolution: f64,    // 量子化の分解能
+    pub seed: u64,          // ノイズのシード
+}
+
+impl Default for SensorSetting {
+    fn default() -> Self {
+        Self {
+            delay: 0.0,
+            noise_std: 0.0,
+            time_constant: 0.0,
+            resolution: 0.0,
+            seed: 1,
+        }
+    }
+}
+
+/// # センサモデル
+/// 入力（真値）に対して、むだ時間 → 加算ノイズ → 一次遅れフィルタ → 量子化 の順に処理した値を出力する
+/// 入力バスと出力バスの要素数は等しく、各要素に同じ設定が適用される
+#[derive(Debug, Clone)]
+pub struct SensorModel {
+    input_bus: RefBus,
+    output_bus: Bus,
+    setting: SensorSetting,
+    delay_buf: VecDeque<Vec<f64>>, // むだ時間用のバッファ（先頭が最も古い値）
+    filter_state: Vec<f64>,        // 一次遅れフィルタの状態
+    noise: NoiseGen,
+}
+
+impl SensorModel {
+    /// ## SensorModelの引数定義
+    /// 1. 第1引数：入力バスの定義
+    /// 1. 第2引数：出力バスの定義（入力と同じ要素数）
+    /// 1. 第3引数：センサの設定
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, setting: SensorSetting) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("SensorModelの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("SensorModelの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("SensorModel: 入力と出力の要素数は一致している必要があります。\ninbus.len = {}, outbus.len = {}", inbus.len(), outbus.len()));
+        }
+        if [setting.delay, setting.noise_std, setting.time_constant, setting.resolution].iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(anyhow!("SensorModel: 設定値は0以上の有限値である必要があります。\n{:?}", setting));
+        }
+
+        let len = outbus.len();
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            noise: NoiseGen::new(setting.seed),
+            setting,
+            delay_buf: VecDeque::new(),
+            filter_state: vec![0.0; len],
+        })
+    }
+
+    /// むだ時間・ノイズ・フィルタ・量子化を順に適用して出力を更新する
+    fn update_output(&mut self, delta_t: f64) {
+        let delayed = self.delay_buf.pop_front().unwrap_or_else(|| self.input_bus.to_vec_f64());
+
+        for (idx, val) in delayed.iter().enumerate() {
+            let noisy = val + self.noise.gaussian(self.setting.noise_std);
+            self.filter_state[idx] = first_order_lag(self.filter_state[idx], noisy, self.setting.time_constant, delta_t);
+            self.output_bus[idx].set_val(quantize(self.filter_state[idx], self.setting.resolution));
+        }
+    }
+}
+
+impl ModelCore for SensorModel {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.noise.reset();
+
+        // むだ時間の間は初期入力値を出力する
+        let init = self.input_bus.to_vec_f64();
+        let delay_steps = (self.setting.delay / sim_time.delta_t().abs()).round() as usize;
+        self.delay_buf = std::iter::repeat_n(init.clone(), delay_steps).collect();
+        self.filter_state = init.clone();
+        self.output_bus.iter_mut().zip(init.iter()).for_each(|(sig, &val)| sig.set_val(quantize(val, self.setting.resolution)));
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        if !self.delay_buf.is_empty() {
+            self.delay_buf.push_back(self.input_bus.to_vec_f64());
+        }
+        self.update_output(sim_time.delta_t());
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        self.filter_state.iter().enumerate().map(|(i, v)| (format!("filter{}", i), *v)).collect()
+    }
+}
+
+/// アクチュエータモデルの設定
+#[derive(Debug, Clone)]
+pub struct ActuatorSetting {
+    pub rate_limit: f64,     // 指令値の変化率の上限[/s]（0で無効）
+    pub time_constant: f64,  // 一次遅れの時定数[s]（0で無効）
+    pub minmax: (f64, f64),  // 出力の上下限 (min, max)
+}
+
+impl Default for ActuatorSetting {
+    fn default() -> Self {
+        Self {
+            rate_limit: 0.0,
+            time_constant: 0.0,
+            minmax: (f64::NEG_INFINITY, f64::INFINITY),
+        }
+    }
+}
+
+/// # アクチュエータモデル
+/// 指令値に対して、レートリミット → 一次遅れ → 出力飽和 の順に処理した値を出力する
+/// 入力バスと出力バスの要素数は等しく、各要素に同じ設定が適用される
+#[derive(Debug, Clone)]
+pub struct ActuatorModel {
+    input_bus: RefBus,
+    output_bus: Bus,
+    setting: ActuatorSetting,
+    rate_state: Vec<f64>, // レートリミット後の指令値
+    lag_state: Vec<f64>,  // 一次遅れの状態
+    sat_steps: Vec<u64>,  // 出力が上下限で制限されたステップ数（信号ごと）
+    total_steps: u64,     // 実行ステップ数
+}
+
+impl ActuatorModel {
+    /// ## ActuatorModelの引数定義
+    /// 1. 第1引数：入力バス（指令値）の定義
+    /// 1. 第2引数：出力バスの定義（入力と同じ要素数）
+    /// 1. 第3引数：アクチュエータの設定
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, setting: ActuatorSetting) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("ActuatorModelの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("ActuatorModelの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("ActuatorModel: 入力と出力の要素数は一致している必要があります。\ninbus.len = {}, outbus.len = {}", inbus.len(), outbus.len()));
+        }
+        if [setting.rate_limit, setting.time_constant].iter().any(|v| !v.is_finite() || *v < 0.0) {
+            return Err(anyhow!("ActuatorModel: 変化率の上限と時定数は0以上の有限値である必要があります。\n{:?}", setting));
+        }
+        if setting.minmax.0.is_nan() || setting.minmax.1.is_nan() || setting.minmax.0 > setting.minmax.1 {
+            return Err(anyhow!("ActuatorModel: 出力の下限が上限より大きくなっています。minmax = {:?}", setting.minmax));
+        }
+
+        let len = outbus.len();
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            setting,
+            rate_state: vec![0.0; len],
+            lag_state: vec![0.0; len],
+            sat_steps: vec![0; len],
+            total_steps: 0,
+        })
+    }
+
+    /// 出力が上下限で制限されたステップ数（信号ごと）
+    pub fn saturated_steps(&self) -> &[u64] {
+        &self.sat_steps
+    }
+
+    /// 出力が上下限で制限されたステップの割合（信号ごと、0～1、未実行の時は0）
+    pub fn saturation_fraction(&self) -> Vec<f64> {
+        self.sat_steps.iter().map(|n| if self.total_steps == 0 { 0.0 } else { *n as f64 / self.total_steps as f64 }).collect()
+    }
+}
+
+impl ModelCore for ActuatorModel {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        // 初期指令値で静定している状態から開始する
+        let init = self.input_bus.to_vec_f64();
+        self.rate_state = init.clone();
+        self.lag_state = init.clone();
+        self.sat_steps = vec![0; init.len()];
+        self.total_steps = 0;
+        self.output_bus.iter_mut().zip(init.iter()).for_each(|(sig, &val)| sig.set_val(val.guard_minmax(self.setting.minmax)));
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        let delta_t = sim_time.delta_t();
+        let max_delta = self.setting.rate_limit * delta_t.abs(); // 1ステップ当たりの変化量の上限
+
+        for (idx, cmd) in self.input_bus.to_vec_f64().into_iter().enumerate() {
+            self.rate_state[idx] = if self.setting.rate_limit > 0.0 {
+                let prev = self.rate_state[idx];
+                prev + (cmd - prev).guard_minmax((-max_delta, max_delta))
+            } else {
+                cmd
+            };
+            self.lag_state[idx] = first_order_lag(self.lag_state[idx], self.rate_state[idx], self.setting.time_constant, delta_t);
+            let out = self.lag_state[idx].guard_minmax(self.setting.minmax);
+            if out != self.lag_state[idx] {
+                self.sat_steps[idx] += 1;
+            }
+            self.output_bus[idx].set_val(out);
+        }
+        self.total_steps += 1;
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn internal_states(&self) -> Vec<(String, f64)> {
+        let rate = self.rate_state.iter().enumerate().map(|(i, v)| (format!("rate{}", i), *v));
+        let lag = self.lag_state.iter().enumerate().map(|(i, v)| (format!("lag{}", i), *v));
+        rate.chain(lag).collect()
+    }
+}
+
+#[cfg(test)]
+mod nonideal_model_test {
+    use super::*;
+    use sim_signal::signal::{SigTrait};
+
+    fn run_sensor(setting: SensorSetting, input: impl Fn(f64) -> f64, end_time: f64) -> Vec<f64> {
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m")]).unwrap();
+        let mut sensor = SensorModel::new(vec![SigDef::new("x", "m")], vec![SigDef::new("x_meas", "m")], setting).unwrap();
+        sensor.interface_in().unwrap().connect_to(&bus, &["x"], &["x"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, end_time, 0.1);
+        bus[0].set_val(input(0.0));
+        sensor.initialize(&sim_time);
+
+        let mut out = vec![sensor.interface_out().unwrap()[0].val()];
+        while let Some((_, t)) = sim_time.next() {
+            bus[0].set_val(input(t));
+            sensor.nextstate(&sim_time);
+            out.push(sensor.interface_out().unwrap()[0].val());
+        }
+        out
+    }
+
+    #[test]
+    fn sensor_ideal_test() {
+        // 設定がすべて無効の時は入力をそのまま出力する
+        let out = run_sensor(SensorSetting::default(), |t| 2.0 * t, 1.0);
+        out.iter().enumerate().for_each(|(k, &v)| assert!((v - 0.2 * k as f64).abs() < 1e-12));
+    }
+
+    #[test]
+    fn sensor_delay_quantize_test() {
+        let setting = SensorSetting { delay: 0.3, resolution: 0.5, ..Default::default() };
+        let out = run_sensor(setting, |t| if t > 0.05 { 1.2 } else { 0.0 }, 1.0);
+
+        assert_eq!(&out[0..4], &[0.0, 0.0, 0.0, 0.0]); // 3ステップ遅れて変化する
+        assert_eq!(out[4], 1.0); // 1.2は分解能0.5で1.0に丸められる
+    }
+
+    #[test]
+    fn sensor_filter_noise_test() {
+        // 一次遅れ：時定数経過後に約63.2%に達する
+        let setting = SensorSetting { time_constant: 1.0, ..Default::default() };
+        let out = run_sensor(setting, |t| if t > 0.05 { 1.0 } else { 0.0 }, 1.0);
+        assert!((out[10] - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        // ノイズ：平均0、標準偏差が設定値程度、シードが同じなら同じ系列になる
+        let setting = SensorSetting { noise_std: 0.1, seed: 42, ..Default::default() };
+        let out = run_sensor(setting.clone(), |_| 0.0, 1000.0);
+        let mean = out.iter().sum::<f64>() / out.len() as f64;
+        let std = (out.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / out.len() as f64).sqrt();
+        assert!(mean.abs() < 0.01);
+        assert!((std - 0.1).abs() < 0.01);
+        assert_eq!(out, run_sensor(setting, |_| 0.0, 1000.0));
+    }
+
+    fn run_actuator(setting: ActuatorSetting, cmd: f64, end_time: f64) -> Vec<f64> {
+        let mut bus = Bus::try_from(vec![SigDef::new("cmd", "Nm")]).unwrap();
+        let mut act = ActuatorModel::new(vec![SigDef::new("cmd", "Nm")], vec![SigDef::new("trq", "Nm")], setting).unwrap();
+        act.interface_in().unwrap().connect_to(&bus, &["cmd"], &["cmd"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, end_time, 0.1);
+        act.initialize(&sim_time);
+
+        let mut out = vec![act.interface_out().unwrap()[0].val()];
+        bus[0].set_val(cmd);
+        while sim_time.next().is_some() {
+            act.nextstate(&sim_time);
+            out.push(act.interface_out().unwrap()[0].val());
+        }
+        out
+    }
+
+    #[test]
+    fn actuator_test() {
+        // 設定がすべて無効の時は指令値をそのまま出力する
+        let out = run_actuator(ActuatorSetting::default(), 3.0, 0.5);
+        assert_eq!(out[1], 3.0);
+
+        // レートリミット（2.0/s → 1ステップ0.2）と出力飽和
+        let setting = ActuatorSetting { rate_limit: 2.0, minmax: (-1.0, 1.0), ..Default::default() };
+        let out = run_actuator(setting, 3.0, 1.0);
+        assert!((out[1] - 0.2).abs() < 1e-12);
+        assert!((out[4] - 0.8).abs() < 1e-12);
+        assert_eq!(out[5], 1.0);
+        assert_eq!(out[10], 1.0);
+
+        // 一次遅れ
+        let setting = ActuatorSetting { time_constant: 0.5, ..Default::default() };
+        let out = run_actuator(setting, 1.0, 0.5);
+        assert!((out[5] - (1.0 - (-1.0f64).exp())).abs() < 1e-9);
+
+        assert!(ActuatorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], ActuatorSetting { minmax: (1.0, -1.0), ..Default::default() }).is_err());
+        assert!(ActuatorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], ActuatorSetting { rate_limit: f64::NAN, ..Default::default() }).is_err());
+        assert!(ActuatorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], ActuatorSetting { time_constant: f64::NAN, ..Default::default() }).is_err());
+        assert!(ActuatorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], ActuatorSetting { time_constant: f64::INFINITY, ..Default::default() }).is_err());
+        assert!(ActuatorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], ActuatorSetting { minmax: (f64::NAN, 1.0), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn actuator_saturation_count_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("cmd", "Nm")]).unwrap();
+        let setting = ActuatorSetting { minmax: (-1.0, 1.0), ..Default::default() };
+        let mut act = ActuatorModel::new(vec![SigDef::new("cmd", "Nm")], vec![SigDef::new("trq", "Nm")], setting).unwrap();
+        act.interface_in().unwrap().connect_to(&bus, &["cmd"], &["cmd"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        act.initialize(&sim_time);
+        assert_eq!(act.saturation_fraction(), vec![0.0]);
+
+        // 上限を超える指令を3ステップ、範囲内の指令を7ステップ
+        for k in 0..10 {
+            sim_time.next();
+            bus[0].set_val(if k < 3 { 3.0 } else { 0.5 });
+            act.nextstate(&sim_time);
+        }
+        assert_eq!(act.saturated_steps(), &[3]);
+        assert!((act.saturation_fraction()[0] - 0.3).abs() < 1e-12);
+
+        act.initialize(&sim_time);
+        assert_eq!(act.saturated_steps(), &[0]);
+    }
+
+    #[test]
+    fn sensor_new_error_test() {
+        assert!(SensorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-"), SigDef::new("c", "-")], SensorSetting::default()).is_err());
+        let setting = SensorSetting { delay: -1.0, ..Default::default() };
+        assert!(SensorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], setting).is_err());
+        let setting = SensorSetting { delay: f64::INFINITY, ..Default::default() };
+        assert!(SensorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], setting).is_err());
+        let setting = SensorSetting { time_constant: f64::NAN, ..Default::default() };
+        assert!(SensorModel::new(vec![SigDef::new("a", "-")], vec![SigDef::new("b", "-")], setting).is_err());
+    }
+}
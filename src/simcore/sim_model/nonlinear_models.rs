@@ -0,0 +1,369 @@
+/// # 非線形要素モデル
+/// 入力に対して静的な非線形特性を与えるモデルを実装する
+///
+/// - 飽和（リミッタ）モデル
+/// - 滑らかな飽和モデル（tanhによる制限）
+/// - 不感帯モデル
+use anyhow::{anyhow, Context};
+
+use crate::simcore::{sim_model, sim_signal, sim_system};
+use sim_model::model_core::{ModelCore};
+
+use sim_signal::signal::{SigDef};
+use sim_signal::bus::{Bus, RefBus};
+
+use sim_system::SimTime;
+
+use super::super::sim_common::Saturation as _;
+
+/// 飽和モデル
+/// 入力を要素ごとに(min, max)の範囲に制限して出力する。入力と出力は要素ごとに対応する
+#[derive(Debug)]
+pub struct Saturation {
+    input_bus: RefBus,
+    output_bus: Bus,
+    limits: Vec<(f64, f64)>, // 要素ごとの(下限, 上限)
+}
+
+impl Saturation {
+    /// ## Saturationの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス（入力バスと同じ要素数）
+    /// 1. 第3, 4引数：全要素共通の下限・上限
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, min: f64, max: f64) -> anyhow::Result<Self> {
+        let len = input_def.len();
+        Self::with_limits(input_def, output_def, vec![(min, max); len])
+    }
+
+    /// 要素ごとに(下限, 上限)を指定してSaturationを作成する
+    /// limitsの要素数は入出力バスの要素数と同じである必要がある
+    pub fn with_limits(input_def: Vec<SigDef>, output_def: Vec<SigDef>, limits: Vec<(f64, f64)>) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("Saturationの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("Saturationの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("Saturation: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if limits.len() != outbus.len() {
+            return Err(anyhow!("Saturation: 制限値の数は出力バスの長さと同じである必要があります。\nlimits.len = {}, output.len = {}", limits.len(), outbus.len()));
+        }
+        if let Some((min, max)) = limits.iter().find(|(min, max)| min > max || min.is_nan() || max.is_nan()) {
+            return Err(anyhow!("Saturation: 下限は上限以下で設定してください。min = {}, max = {}", min, max));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            limits,
+        })
+    }
+}
+
+impl ModelCore for Saturation {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let input = self.input_bus.to_vec_f64();
+        self.output_bus.iter_mut().zip(input.iter().zip(self.limits.iter()))
+            .for_each(|(sig, (u, limit))| sig.set_val(u.guard_minmax(*limit)));
+    }
+}
+
+/// 滑らかな飽和モデル
+/// 入力を要素ごとにtanhで滑らかに(min, max)の範囲へ制限して出力する。入力と出力は要素ごとに対応する
+/// y = c + h * tanh(sharpness * (u - c) / h)（c = (min + max) / 2、h = (max - min) / 2）
+/// 出力は常に(min, max)の開区間内で、全域で微分可能（中央での傾きがsharpness）。Saturationのような微分の不連続が無いため、
+/// ソルバや微分を使う制御器・線形化を乱さない
+#[derive(Debug)]
+pub struct SoftSaturation {
+    input_bus: RefBus,
+    output_bus: Bus,
+    limits: Vec<(f64, f64)>, // 要素ごとの(下限, 上限)
+    sharpness: f64,          // 遷移の鋭さ（範囲中央での傾き）
+}
+
+impl SoftSaturation {
+    /// ## SoftSaturationの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス（入力バスと同じ要素数）
+    /// 1. 第3, 4引数：全要素共通の下限・上限（min < max）
+    /// 1. 第5引数：sharpness: 遷移の鋭さ（正の値。1で範囲中央の傾きが1になり、小さな入力はそのまま出力される）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, min: f64, max: f64, sharpness: f64) -> anyhow::Result<Self> {
+        let len = input_def.len();
+        Self::with_limits(input_def, output_def, vec![(min, max); len], sharpness)
+    }
+
+    /// 要素ごとに(下限, 上限)を指定してSoftSaturationを作成する
+    /// limitsの要素数は入出力バスの要素数と同じである必要がある
+    pub fn with_limits(input_def: Vec<SigDef>, output_def: Vec<SigDef>, limits: Vec<(f64, f64)>, sharpness: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("SoftSaturationの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("SoftSaturationの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("SoftSaturation: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if limits.len() != outbus.len() {
+            return Err(anyhow!("SoftSaturation: 制限値の数は出力バスの長さと同じである必要があります。\nlimits.len = {}, output.len = {}", limits.len(), outbus.len()));
+        }
+        if let Some((min, max)) = limits.iter().find(|(min, max)| {
+            let valid = min < max && min.is_finite() && max.is_finite();
+            !valid
+        }) {
+            return Err(anyhow!("SoftSaturation: 下限は上限未満の有限値で設定してください。min = {}, max = {}", min, max));
+        }
+        let valid = sharpness > 0.0 && sharpness.is_finite();
+        if !valid {
+            return Err(anyhow!("SoftSaturation: sharpnessは正の値で設定してください。sharpness = {}", sharpness));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            limits,
+            sharpness,
+        })
+    }
+}
+
+/// 滑らかな飽和の出力
+fn soft_saturation(u: f64, (min, max): (f64, f64), sharpness: f64) -> f64 {
+    let center = 0.5 * (min + max);
+    let half = 0.5 * (max - min);
+    center + half * (sharpness * (u - center) / half).tanh()
+}
+
+impl ModelCore for SoftSaturation {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let input = self.input_bus.to_vec_f64();
+        let sharpness = self.sharpness;
+        self.output_bus.iter_mut().zip(input.iter().zip(self.limits.iter()))
+            .for_each(|(sig, (u, limit))| sig.set_val(soft_saturation(*u, *limit, sharpness)));
+    }
+}
+
+/// 不感帯モデル
+/// 入力が[start, end]の範囲内の時は0を出力し、endより大きい時はinput - end、startより小さい時はinput - startを出力する
+/// 機械的なバックラッシュやバルブの不感帯を表現する。入力と出力は要素ごとに対応し、全要素で同じ不感帯を使用する
+#[derive(Debug)]
+pub struct DeadZone {
+    input_bus: RefBus,
+    output_bus: Bus,
+    start: f64, // 不感帯の下端
+    end: f64,   // 不感帯の上端
+}
+
+impl DeadZone {
+    /// ## DeadZoneの引数定義
+    /// 1. 第1引数：入力バス
+    /// 1. 第2引数：出力バス（入力バスと同じ要素数）
+    /// 1. 第3, 4引数：不感帯の下端・上端（start <= end）
+    pub fn new(input_def: Vec<SigDef>, output_def: Vec<SigDef>, start: f64, end: f64) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("DeadZoneの入力バスが不正です。")?;
+        let outbus = Bus::try_from(output_def).context("DeadZoneの出力バスが不正です。")?;
+
+        if inbus.len() != outbus.len() {
+            return Err(anyhow!("DeadZone: 入出力バスの長さは互いに同じである必要があります。\ninput.len = {}, output.len = {}", inbus.len(), outbus.len()));
+        }
+        if start > end || start.is_nan() || end.is_nan() {
+            return Err(anyhow!("DeadZone: 不感帯の下端は上端以下で設定してください。start = {}, end = {}", start, end));
+        }
+
+        Ok(Self {
+            input_bus: inbus,
+            output_bus: outbus,
+            start,
+            end,
+        })
+    }
+}
+
+/// 不感帯の出力
+fn dead_zone(u: f64, start: f64, end: f64) -> f64 {
+    if u > end {
+        u - end
+    } else if u < start {
+        u - start
+    } else {
+        0.0
+    }
+}
+
+impl ModelCore for DeadZone {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.output_bus.set_all(0.0);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.output_bus)
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let input = self.input_bus.to_vec_f64();
+        let (start, end) = (self.start, self.end);
+        self.output_bus.iter_mut().zip(input.iter())
+            .for_each(|(sig, u)| sig.set_val(dead_zone(*u, start, end)));
+    }
+}
+
+#[cfg(test)]
+mod nonlinear_model_test {
+    use super::*;
+    use crate::simcore::sim_model::{source_models::RampFunc, sink_models::SimRecorder, model_core::connect_models};
+    use crate::simcore::sim_system::SimSystem;
+
+    #[test]
+    fn saturation_test() {
+        // u1 = 2t は上限1.0で、u2 = -2t は下限-0.5で頭打ちになる
+        let src = RampFunc::new(
+            vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")],
+            vec![(0.0, 0.0, false, 0.0, 2.0), (0.0, 0.0, false, 0.0, -2.0)],
+        ).unwrap();
+        let mut sat = Saturation::with_limits(
+            vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")],
+            vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")],
+            vec![(-1.0, 1.0), (-0.5, 0.5)],
+        ).unwrap();
+        connect_models(&src, &["u1", "u2"], &mut sat, &["u1", "u2"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")]).unwrap();
+        connect_models(&sat, &["y1", "y2"], &mut rcd, &["y1", "y2"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(src);
+        sys.regist_model(sat);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let time = rcd.get_time().to_vec();
+        let y1 = rcd.get_signal("y1").unwrap().to_vec();
+        let y2 = rcd.get_signal("y2").unwrap().to_vec();
+        for ((t, y1), y2) in time.iter().zip(y1.iter()).zip(y2.iter()).skip(1) {
+            assert!((y1 - (2.0 * t).min(1.0)).abs() < 1e-12, "t = {}, y1 = {}", t, y1);
+            assert!((y2 - (-2.0 * t).max(-0.5)).abs() < 1e-12, "t = {}, y2 = {}", t, y2);
+        }
+        assert_eq!(*y1.last().unwrap(), 1.0);
+        assert_eq!(*y2.last().unwrap(), -0.5);
+
+        assert!(Saturation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 1.0, -1.0).is_err());
+        assert!(Saturation::new(vec![SigDef::new("u", "-")], vec![], -1.0, 1.0).is_err());
+        assert!(Saturation::with_limits(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], vec![]).is_err());
+    }
+
+    #[test]
+    fn soft_saturation_test() {
+        // u = -4 + 4t（-4 → 4）を(-1, 1)と(0, 2)の範囲に滑らかに制限する
+        let src = RampFunc::new(vec![SigDef::new("u", "-")], vec![(-4.0, 0.0, false, 0.0, 4.0)]).unwrap();
+        let mut sat = SoftSaturation::with_limits(
+            vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")],
+            vec![SigDef::new("y1", "-"), SigDef::new("y2", "-")],
+            vec![(-1.0, 1.0), (0.0, 2.0)],
+            1.0,
+        ).unwrap();
+        connect_models(&src, &["u", "u"], &mut sat, &["u1", "u2"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-"), SigDef::new("y1", "-"), SigDef::new("y2", "-")]).unwrap();
+        connect_models(&src, &["u"], &mut rcd, &["u"]).unwrap();
+        connect_models(&sat, &["y1", "y2"], &mut rcd, &["y1", "y2"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 2.0, 0.015625);
+        sys.regist_model(src);
+        sys.regist_model(sat);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_result("rcd").unwrap();
+        let u = &rcd.get_signal("u").unwrap()[1..];
+        let y1 = &rcd.get_signal("y1").unwrap()[1..];
+        let y2 = &rcd.get_signal("y2").unwrap()[1..];
+        for ((u, y1), y2) in u.iter().zip(y1.iter()).zip(y2.iter()) {
+            assert!(y1.abs() < 1.0 && *y2 > 0.0 && *y2 < 2.0, "u = {}, y1 = {}, y2 = {}", u, y1, y2); // 範囲内
+            assert!((y1 - u.tanh()).abs() < 1e-12);
+        }
+        // 単調増加で、傾き（差分）も連続的に変化する
+        let slope = y1.windows(2).map(|w| w[1] - w[0]).collect::<Vec<f64>>();
+        assert!(slope.iter().all(|d| *d > 0.0));
+        assert!(slope.windows(2).all(|w| (w[1] - w[0]).abs() < 1e-2));
+        // 中央付近は入力をほぼそのまま出力し、範囲外では制限値に近づく
+        assert_eq!(soft_saturation(1.0, (0.0, 2.0), 1.0), 1.0);
+        assert!((soft_saturation(1.01, (0.0, 2.0), 1.0) - 1.01).abs() < 1e-6);
+        assert!((y2.last().unwrap() - 2.0).abs() < 1e-2);
+        let center_slope = (soft_saturation(1e-6, (-1.0, 1.0), 5.0) - soft_saturation(-1e-6, (-1.0, 1.0), 5.0)) / 2e-6;
+        assert!((center_slope - 5.0).abs() < 1e-6, "{}", center_slope); // 中央の傾きはsharpness
+
+        assert!(SoftSaturation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 1.0, 1.0, 1.0).is_err());
+        assert!(SoftSaturation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], -1.0, 1.0, 0.0).is_err());
+        assert!(SoftSaturation::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], f64::NEG_INFINITY, 1.0, 1.0).is_err());
+        assert!(SoftSaturation::with_limits(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], vec![], 1.0).is_err());
+    }
+
+    #[test]
+    fn dead_zone_test() {
+        // u = -2 + 2t（-2 → 2）を(-0.5, 0.5)の不感帯に通す
+        let src = RampFunc::new(vec![SigDef::new("u", "-")], vec![(-2.0, 0.0, false, 0.0, 2.0)]).unwrap();
+        let mut dz = DeadZone::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], -0.5, 0.5).unwrap();
+        connect_models(&src, &["u"], &mut dz, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-"), SigDef::new("y", "-")]).unwrap();
+        connect_models(&src, &["u"], &mut rcd, &["u"]).unwrap();
+        connect_models(&dz, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 2.0, 0.05);
+        sys.regist_model(src);
+        sys.regist_model(dz);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let u = rcd.get_signal("u").unwrap().to_vec();
+        let y = rcd.get_signal("y").unwrap().to_vec();
+        for (u, y) in u.iter().zip(y.iter()).skip(1) {
+            if u.abs() <= 0.5 {
+                assert_eq!(*y, 0.0, "u = {}", u); // 不感帯内は0
+            } else {
+                assert!((y - (u - 0.5 * u.signum())).abs() < 1e-12, "u = {}, y = {}", u, y);
+            }
+        }
+        // 不感帯の外では傾き1に戻る
+        rcd.assert_final_approx("y", 1.5, 1e-9).unwrap();
+        assert!((y[1] + 1.4).abs() < 1e-9);
+
+        assert!(DeadZone::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 0.5, -0.5).is_err());
+        assert!(DeadZone::new(vec![SigDef::new("u", "-")], vec![], -0.5, 0.5).is_err());
+    }
+}
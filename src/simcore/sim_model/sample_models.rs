@@ -47,11 +47,52 @@ pub fn make_rlc_circuit_model(r: f64, l: f64, c: f64, init_i: f64, init_q: f64,
 mod sample_model_test {
     use super::*;
     
-    use crate::simcore::sim_model::model_core::connect_models;
-    use crate::simcore::sim_model::{sink_models::SimRecorder, source_models::StepFunc};
+    use crate::simcore::sim_model::model_core::{connect_models, connect_models_auto};
+    use crate::simcore::sim_model::{sink_models::SimRecorder, source_models::{StepFunc, ConstantFunc}};
     use crate::simcore::sim_system::SimSystem;
     use sim_signal::signal::{SigDef};
 
+    /// 固有角周波数1e4[rad/s]のRLC回路をルンゲクッタ法で解き、誤差推定値が刻み幅とともに大きくなることを確認する
+    #[test]
+    fn rlc_rk4_error_estimate_test() {
+        use std::{cell::Cell, rc::Rc};
+
+        let run = |delta_t: f64, tol: f64| {
+            let mut rlc = make_rlc_circuit_model(2.0, 1e-3, 10e-6, 0.0, 0.0, SolverType::RungeKutta);
+            let input = ConstantFunc::new(vec![SigDef::new("v_in", "V")], &[1.0]).unwrap();
+            connect_models(&input, &["v_in"], &mut rlc, &["v_in"]).unwrap();
+
+            let max_err = Rc::new(Cell::new(0.0f64));
+            let warnings = Rc::new(Cell::new(0));
+            let (e, w) = (max_err.clone(), warnings.clone());
+            rlc.set_error_check(tol, move |_t, err| {
+                e.set(e.get().max(err));
+                w.set(w.get() + 1);
+            }).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 1e-3, delta_t);
+            sys.regist_model(input);
+            sys.regist_model(rlc);
+            sys.run();
+            (max_err.get(), warnings.get())
+        };
+
+        // 許容誤差0では全ステップで通知され、推定誤差は刻み幅の5乗に比例して大きくなる
+        let errs = [1e-6, 2e-6, 4e-6, 8e-6].iter().map(|dt| run(*dt, 0.0)).collect::<Vec<(f64, usize)>>();
+        assert_eq!(errs.iter().map(|(_, w)| *w).collect::<Vec<usize>>(), vec![1000, 500, 250, 125]);
+        for pair in errs.windows(2) {
+            let ratio = pair[1].0 / pair[0].0;
+            assert!(ratio > 16.0 && ratio < 48.0, "ratio = {}", ratio);
+        }
+
+        // 推定誤差が許容誤差以下であれば通知しない
+        assert_eq!(run(1e-6, 1.0).1, 0);
+
+        let mut rlc = make_rlc_circuit_model(2.0, 1e-3, 10e-6, 0.0, 0.0, SolverType::RungeKutta);
+        assert!(rlc.set_error_check(-1.0, |_, _| {}).is_err());
+        assert!(rlc.last_error_estimate().is_none());
+    }
+
     #[test]
     fn rlc_cirsuit_test() {
         // モデルの作成
@@ -106,6 +147,49 @@ mod sample_model_test {
 
 
     }
+
+    #[test]
+    fn ball_and_beam_auto_connect_test() {
+        let signames = ["ball_r", "ball_v", "beam_t", "beam_w"];
+        let run = |auto: bool| {
+            let trq = ConstantFunc::new(vec![SigDef::new("trq", "Nm")], &[0.01]).unwrap();
+            let mut bb = BallAndBeam::new(0.015, 0.11, 9.9e-6, 0.02, 0.1, 0.0, 0.0, 0.0);
+            let mut rcd = SimRecorder::new(vec![
+                SigDef::new("ball_r", "m"),
+                SigDef::new("ball_v", "m/s"),
+                SigDef::new("beam_t", "deg"),
+                SigDef::new("beam_w", "deg/s"),
+            ]).unwrap();
+
+            if auto {
+                connect_models_auto(&trq, &mut bb).unwrap();
+                connect_models_auto(&bb, &mut rcd).unwrap();
+            } else {
+                connect_models(&trq, &["trq"], &mut bb, &["trq"]).unwrap();
+                connect_models(&bb, &signames, &mut rcd, &signames).unwrap();
+            }
+
+            let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+            sys.regist_model(trq);
+            sys.regist_model(bb);
+            sys.regist_recorder("rcd", rcd);
+            sys.run();
+            let rcd = sys.get_result("rcd").unwrap();
+            signames.iter().map(|name| rcd.get_signal(name).unwrap().to_vec()).collect::<Vec<Vec<f64>>>()
+        };
+
+        // 同じ名前の信号を一度に接続した結果は、信号名を明示して接続した結果と一致する
+        let explicit = run(false);
+        assert_eq!(run(true), explicit);
+        assert!(explicit[2].last().unwrap().abs() > 0.0); // トルクでビームが回転している
+
+        // 接続元に無い信号名がある場合はエラー
+        let bb = BallAndBeam::new(0.015, 0.11, 9.9e-6, 0.02, 0.1, 0.0, 0.0, 0.0);
+        let mut rcd = SimRecorder::new(vec![SigDef::new("ball_r", "m"), SigDef::new("ball_x", "m")]).unwrap();
+        let err = connect_models_auto(&bb, &mut rcd).unwrap_err().to_string();
+        assert!(err.contains("\"ball_x\"") && !err.contains("\"ball_r\""), "{}", err);
+        assert!(rcd.interface_in().unwrap().iter().all(|sig| !sig.is_connected())); // 何も接続しない
+    }
 }
 
 /// ボールアンドビームのサンプル
@@ -183,6 +267,10 @@ impl ModelCore for BallAndBeam {
         Some(&self.outbus)
     }
 
+    /// 出力は状態（ボール位置・速度、ビーム角度・角速度）のみ
+    fn direct_feedthrough(&self) -> bool {
+        false
+    }
 }
 
 impl DEModel for BallAndBeam {
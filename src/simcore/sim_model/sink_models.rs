@@ -2,6 +2,7 @@
 /// Sinkモデルには、下記のモデルを実装する
 /// 
 /// - Recorderモデル
+/// - イベントレコーダモデル（トリガ信号がしきい値を横切った時刻のみ記録する）
 
 use crate::simcore::{sim_model, sim_signal, sim_system};
 
@@ -17,35 +18,415 @@ use plotters::coord::Shift;
 
 use std::fs::File;
 use std::io::{Write, BufWriter};
+use std::time::{SystemTime, UNIX_EPOCH};
+use std::path::{Path, PathBuf};
 
 use anyhow::{anyhow, Context};
 
+extern crate nalgebra as na;
+use na::{DMatrix};
+
+/// 初期化時に事前確保するデータ数の上限
+const PREALLOC_MAX: usize = 1 << 20;
+
+/// Parquet出力時の行グループの行数
+#[cfg(feature = "parquet")]
+const PARQUET_ROW_GROUP: usize = 1 << 16;
+
+/// 記録した信号の統計量（SimRecorder::statsで取得する）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalStats {
+    pub min: f64,         // 最小値
+    pub max: f64,         // 最大値
+    pub mean: f64,        // 平均値（サンプルの単純平均）
+    pub rms: f64,         // 二乗平均平方根
+    pub final_value: f64, // 最終値
+    pub time_of_max: f64, // 最大値をとった時刻[s]（最初に最大値をとった時刻）
+}
+
+/// レコーダの記録方式
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RecordMode {
+    All,             // 全ステップを記録する
+    Decimate(usize), // Nステップごとに記録する
+    Window(usize),   // 直近のNサンプルのみ保持する
+    Fixed(usize),    // 事前確保したN個の領域に記録する（実行中にメモリを確保しない）
+}
+
+/// 先頭の古いデータを削除してcap個以下にする
+fn keep_last(data: &mut Vec<f64>, cap: usize) {
+    if data.len() > cap {
+        data.drain(..data.len() - cap);
+    }
+}
+
 #[derive(Debug)]
 pub struct SimRecorder {
     timedata: Vec<f64>,     // 時刻情報保管用
     storage: Vec<Vec<f64>>, // データストレージ
     signum: usize,
     input_bus: RefBus, 
+    state_subs: Vec<(usize, String)>, // 記録するモデルの内部状態 (モデル番号, 状態名)
+    state_storage: Vec<Vec<f64>>,     // 内部状態のデータストレージ
+    export_scales: Vec<(String, f64, f64, String)>, // CSV出力時の変換 (信号名, 倍率, オフセット, 出力単位)
+    smoothing: Vec<Option<f64>>,      // 記録時の平滑化係数alpha（バスの信号毎、Noneは平滑化しない）
+    styles: Vec<(String, RGBColor, Option<String>)>, // プロット時の線のスタイル (信号名, 色, 凡例の表示名)
+    mode: RecordMode, // 記録方式
+    step_count: usize, // 初期化からのステップ数（間引き用）
+    record_now: bool,  // 現在のステップを記録したかどうか（内部状態の記録で使用）
+    overflowed: bool,  // 固定容量の記録方式で容量を超えたかどうか
+    description: Option<String>, // CSV出力時のメタデータに記載する説明（Noneの時はメタデータを出力しない）
+    sim_range: Option<(f64, f64, f64)>, // 直近の初期化時のシミュレーション時間設定 (開始時刻, 終了時刻, 刻み幅)
+    output_dir: Option<PathBuf>, // ファイル名のみ指定した時の出力先ディレクトリ
+    warmup: Option<f64>, // 記録を始めるまでのウォームアップ時間[s]（Noneの時は開始時刻から記録する）
 }
 
 impl SimRecorder {
     pub fn new(input_def: Vec<SigDef>) -> anyhow::Result<Self>  {
         let inbus = RefBus::try_from(input_def).context(format!("SimRecorderの入力バスが不正です。"))?;
+        let signum = inbus.len();
 
         Ok(Self {
             timedata: Vec::new(),
             storage: Vec::new(),
-            signum: inbus.len(),
+            signum,
             input_bus: inbus,
+            state_subs: Vec::new(),
+            state_storage: Vec::new(),
+            export_scales: Vec::new(),
+            smoothing: vec![None; signum],
+            styles: Vec::new(),
+            mode: RecordMode::All,
+            step_count: 0,
+            record_now: true,
+            overflowed: false,
+            description: None,
+            sim_range: None,
+            output_dir: None,
+            warmup: None,
         })
     }
 
+    /// ファイル出力（export, export_parquet, timeplot_all, timeplot_overlay）の出力先ディレクトリを設定する
+    /// ファイル名のみ（ディレクトリを含まない相対パス）を指定した場合はこのディレクトリに出力する（無ければ作成する）
+    /// ディレクトリを含むパスや絶対パスを指定した場合は設定に関わらずそのパスに出力する
+    pub fn set_output_dir(&mut self, dir: impl Into<PathBuf>) {
+        self.output_dir = Some(dir.into());
+    }
+
+    /// 出力先ディレクトリ（未設定の場合はNone）
+    pub fn output_dir(&self) -> Option<&Path> {
+        self.output_dir.as_deref()
+    }
+
+    /// 出力ファイルのパスを決める（出力先ディレクトリを使う場合はディレクトリを作成する）
+    fn output_path(&self, filepath: &str) -> anyhow::Result<PathBuf> {
+        let path = Path::new(filepath);
+        match &self.output_dir {
+            Some(dir) if path.is_relative() && path.parent().is_none_or(|p| p.as_os_str().is_empty()) => {
+                std::fs::create_dir_all(dir).context(format!("SimRecorder: 出力先ディレクトリを作成できません。dir = {}", dir.display()))?;
+                Ok(dir.join(path))
+            },
+            _ => Ok(path.to_path_buf()),
+        }
+    }
+
+    /// シミュレーション開始からwarmup_time[s]の間のデータを記録しない（初期値も記録しない）
+    /// 初期の過渡応答を除いて、定常状態やリミットサイクルの統計量・プロットを得たい時に使用する
+    /// 統計量（stats等）・peek_signal・発散監視も記録したデータのみを対象とする
+    pub fn set_warmup(&mut self, warmup_time: f64) -> anyhow::Result<()> {
+        let valid = warmup_time >= 0.0 && warmup_time.is_finite();
+        if !valid {
+            return Err(anyhow!("SimRecorder: ウォームアップ時間は0以上の値で設定してください。warmup_time = {}", warmup_time));
+        }
+        self.warmup = Some(warmup_time);
+        Ok(())
+    }
+
+    /// ウォームアップ時間[s]（未設定の場合はNone）
+    pub fn warmup(&self) -> Option<f64> {
+        self.warmup
+    }
+
+    /// 時刻timeがウォームアップ期間を過ぎているか（刻み幅の丸め誤差を考慮する）
+    fn warmed_up(&self, sim_time: &SimTime) -> bool {
+        let warmup = self.warmup.unwrap_or(0.0);
+        (sim_time.time() - sim_time.start_time()).abs() >= warmup - sim_time.delta_t().abs() * 1e-6
+    }
+
+    /// CSV出力（export）の先頭に#で始まるメタデータのコメント行を付加する
+    /// メタデータは説明（descriptionの各行）、シミュレーションの開始・終了時刻と刻み幅、出力日時（UNIX時刻[s]）
+    /// LookupFuncのCSV読み込みは#で始まる行を読み飛ばすため、出力したファイルをそのまま入力に使用できる
+    pub fn set_description(&mut self, description: impl Into<String>) {
+        self.description = Some(description.into());
+    }
+
+    /// every_nステップごとに記録するレコーダを作成する（初期値は常に記録する）
+    /// 長時間・細かい刻み幅のシミュレーションでメモリ使用量を抑えたい時に使用する
+    pub fn new_decimated(input_def: Vec<SigDef>, every_n: usize) -> anyhow::Result<Self> {
+        if every_n == 0 {
+            return Err(anyhow!("SimRecorder: 間引き間隔は1以上で設定してください。"));
+        }
+        let mut rcd = Self::new(input_def)?;
+        rcd.mode = RecordMode::Decimate(every_n);
+        Ok(rcd)
+    }
+
+    /// 直近のmax_samples個のデータのみ保持するレコーダを作成する（古いデータから削除する）
+    /// 記録のたびに先頭のデータを詰めるため、max_samplesを大きくするほど1ステップの処理が重くなる
+    pub fn new_windowed(input_def: Vec<SigDef>, max_samples: usize) -> anyhow::Result<Self> {
+        if max_samples == 0 {
+            return Err(anyhow!("SimRecorder: 保持するデータ数は1以上で設定してください。"));
+        }
+        let mut rcd = Self::new(input_def)?;
+        rcd.mode = RecordMode::Window(max_samples);
+        Ok(rcd)
+    }
+
+    /// 容量capacity個（初期値を含む）の領域を事前確保し、実行中にメモリを確保せずに記録するレコーダを作成する
+    /// 領域は作成時（内部状態は初回の初期化時）に確保し、再実行時も同じ領域を使う。記録数が容量を超えた時点で記録を止め、
+    /// SimSystemの実行はエラーで中断する（容量までの記録データは参照できる）。リアルタイム用途などメモリ確保を避けたい時に使用する
+    pub fn with_capacity(input_def: Vec<SigDef>, capacity: usize) -> anyhow::Result<Self> {
+        if capacity == 0 {
+            return Err(anyhow!("SimRecorder: 容量は1以上で設定してください。"));
+        }
+        let mut rcd = Self::new(input_def)?;
+        rcd.mode = RecordMode::Fixed(capacity);
+        rcd.timedata = Vec::with_capacity(capacity);
+        rcd.storage = (0..rcd.signum).map(|_| Vec::with_capacity(capacity)).collect();
+        Ok(rcd)
+    }
+
+    /// 入力バス（SimSystemのマニフェスト作成で接続関係を参照する）
+    pub(crate) fn input_bus(&self) -> &RefBus {
+        &self.input_bus
+    }
+
+    /// 固定容量の記録方式で記録数が容量を超えていないか確認する（SimSystemから呼び出される）
+    pub(crate) fn check_capacity(&self) -> anyhow::Result<()> {
+        match self.mode {
+            RecordMode::Fixed(cap) if self.overflowed => Err(anyhow!("SimRecorder: 記録数が容量{}を超えました。", cap)),
+            _ => Ok(()),
+        }
+    }
+
+    /// プロット時の信号（内部状態を含む）の線の色と凡例の表示名を設定する
+    /// 凡例の表示名を設定した信号のグラフには凡例を表示する。未設定の信号は赤（重ねて描く場合は既定の色の順）で描画する
+    /// 同じ信号に再設定した場合は上書きする。例：rcd.set_style("x", BLUE, Some("run1"))?.set_style("v", GREEN, None)?;
+    pub fn set_style(&mut self, signame: &str, color: RGBColor, label: Option<&str>) -> anyhow::Result<&mut Self> {
+        let recorded = self.input_bus.iter().any(|sig| sig.name() == signame) || self.state_subs.iter().any(|(_, name)| name == signame);
+        if !recorded {
+            return Err(anyhow!("SimRecorder: 信号{}は記録されていません。", signame));
+        }
+
+        let style = (signame.to_string(), color, label.map(|l| l.to_string()));
+        match self.styles.iter_mut().find(|(name, ..)| name == signame) {
+            Some(s) => *s = style,
+            None => self.styles.push(style),
+        }
+        Ok(self)
+    }
+
+    /// 信号を指数移動平均（y = alpha * u + (1 - alpha) * y_prev）で平滑化して記録するよう設定する
+    /// alphaは0より大きく1以下（1で平滑化なし）。ノイズの多い信号をきれいにプロットしたい時に使用する
+    /// 変わるのは記録されるデータのみで、モデルが計算する信号の値は変更しない
+    pub fn set_smoothing(&mut self, signame: &str, alpha: f64) -> anyhow::Result<()> {
+        let idx = self.input_bus.iter().position(|sig| sig.name() == signame)
+                                       .ok_or(anyhow!("SimRecorder: 信号{}は記録されていません。", signame))?;
+        if !(alpha > 0.0 && alpha <= 1.0) {
+            return Err(anyhow!("SimRecorder: 平滑化係数alphaは0より大きく1以下である必要があります。alpha = {}", alpha));
+        }
+        self.smoothing[idx] = Some(alpha);
+        Ok(())
+    }
+
+    /// モデルの内部状態（ModelCore::internal_states）を記録対象に追加する
+    /// model_idxはSimSystem::regist_modelの戻り値（登録順の番号）。記録データは状態名で取得できる
+    /// 内部状態の記録はSimSystemに登録して実行した場合のみ行われる
+    pub fn subscribe_states(&mut self, model_idx: usize, statenames: &[&str]) -> anyhow::Result<()> {
+        for name in statenames {
+            let dup_sig = self.input_bus.iter().any(|sig| sig.name() == *name);
+            let dup_state = self.state_subs.iter().any(|(_, n)| n == name);
+            if dup_sig || dup_state {
+                return Err(anyhow!("SimRecorder: 記録名{}が重複しています。", name));
+            }
+            self.state_subs.push((model_idx, name.to_string()));
+        }
+        Ok(())
+    }
+
+    /// 記録対象の内部状態の一覧 (モデル番号, 状態名)
+    pub fn state_subscriptions(&self) -> &[(usize, String)] {
+        &self.state_subs
+    }
+
+    /// 登録済みモデルから内部状態を取得して記録する（SimSystemから呼び出される）
+    /// 状態が見つからない場合はNaNを記録する
+    pub(crate) fn record_states(&mut self, models: &[Box<dyn ModelCore + '_>]) {
+        if !self.record_now {
+            return;
+        }
+        for ((idx, name), data) in self.state_subs.iter().zip(self.state_storage.iter_mut()) {
+            let val = models.get(*idx)
+                            .and_then(|mdl| mdl.internal_states().into_iter().find(|(n, _)| n == name))
+                            .map_or(f64::NAN, |(_, v)| v);
+            data.push(val);
+            if let RecordMode::Window(cap) = self.mode {
+                keep_last(data, cap);
+            }
+        }
+    }
+
+    /// 記録している全系列の(信号名・状態名, 最新値)
+    pub fn latest_values(&self) -> Vec<(String, f64)> {
+        let sigs = self.input_bus.iter().map(|sig| sig.name()).zip(self.storage.iter());
+        let states = self.state_subs.iter().map(|(_, name)| name.clone()).zip(self.state_storage.iter());
+        sigs.chain(states).filter_map(|(name, data)| data.last().map(|v| (name, *v))).collect()
+    }
+
+    /// 記録している全系列（バスの信号 → 内部状態の順）の(表示名, データ)
+    fn all_series(&self) -> Vec<(String, &Vec<f64>)> {
+        let sigs = self.input_bus.get_sigdef().into_iter().map(|sig| sig.to_string()).zip(self.storage.iter());
+        let states = self.state_subs.iter().map(|(_, name)| name.clone()).zip(self.state_storage.iter());
+        sigs.chain(states).collect()
+    }
+
+    /// 記録した時刻データを取得する
+    pub fn get_time(&self) -> &[f64] {
+        &self.timedata
+    }
+
+    /// 信号名を指定して記録したデータを取得する
+    pub fn get_signal(&self, signame: &str) -> Option<&[f64]> {
+        self.input_bus.iter().position(|sig| sig.name() == signame)
+                             .and_then(|idx| self.storage.get(idx))
+                             .or_else(|| self.state_subs.iter().position(|(_, name)| name == signame).and_then(|idx| self.state_storage.get(idx)))
+                             .map(|data| data.as_slice())
+    }
+
+    /// 記録データが存在しない場合はエラーとする
+    fn signal_data(&self, signame: &str) -> anyhow::Result<&[f64]> {
+        let data = self.get_signal(signame).ok_or(anyhow!("SimRecorder: 信号{}は記録されていません。", signame))?;
+        if data.is_empty() {
+            return Err(anyhow!("SimRecorder: 信号{}のデータがありません。", signame));
+        }
+        Ok(data)
+    }
+
+    /// 信号の最終値が期待値±tolの範囲内であることを検証する（テスト用）
+    pub fn assert_final_approx(&self, signame: &str, expected: f64, tol: f64) -> anyhow::Result<()> {
+        let data = self.signal_data(signame)?;
+        let last = data[data.len() - 1];
+        if (last - expected).abs() > tol {
+            return Err(anyhow!("SimRecorder: 信号{}の最終値が期待値と一致しません。\nfinal = {}, expected = {}, tol = {}", signame, last, expected, tol));
+        }
+        Ok(())
+    }
+
+    /// 信号（内部状態を含む）の統計量を記録データから計算する
+    pub fn stats(&self, signame: &str) -> anyhow::Result<SignalStats> {
+        let data = self.signal_data(signame)?;
+        let n = data.len() as f64;
+        let (idx, max) = data.iter().enumerate().fold((0, f64::NEG_INFINITY), |acc, (i, &v)| if v > acc.1 { (i, v) } else { acc });
+
+        Ok(SignalStats {
+            min: data.iter().fold(f64::INFINITY, |m, v| m.min(*v)),
+            max,
+            mean: data.iter().sum::<f64>() / n,
+            rms: (data.iter().map(|v| v * v).sum::<f64>() / n).sqrt(),
+            final_value: data[data.len() - 1],
+            time_of_max: self.timedata[idx],
+        })
+    }
+
+    /// 整定時間：信号がfinal_value±tolの範囲に入り、以降範囲から出なくなった時刻[s]
+    /// 最後のサンプルが範囲外の場合（整定していない場合）はNoneを返す
+    pub fn settling_time(&self, signame: &str, final_value: f64, tol: f64) -> anyhow::Result<Option<f64>> {
+        let data = self.signal_data(signame)?;
+        let settled = |v: &f64| (v - final_value).abs() <= tol;
+        match data.iter().rposition(|v| !settled(v)) {
+            None => Ok(Some(self.timedata[0])),
+            Some(idx) if idx + 1 < data.len() => Ok(Some(self.timedata[idx + 1])),
+            Some(_) => Ok(None),
+        }
+    }
+
+    /// オーバーシュート量：初期値から最終値への変化量に対する、最終値を行き過ぎた量の最大値の比（0.1で10%）
+    /// 減少する応答の場合は下側への行き過ぎを評価する。行き過ぎが無い場合は0を返す
+    pub fn overshoot(&self, signame: &str) -> anyhow::Result<f64> {
+        let data = self.signal_data(signame)?;
+        let (init, fin) = (data[0], data[data.len() - 1]);
+        let step = fin - init;
+        if step == 0.0 {
+            return Err(anyhow!("SimRecorder: 信号{}の初期値と最終値が等しいため、オーバーシュート量を計算できません。", signame));
+        }
+
+        let peak = data.iter().map(|v| (v - fin) / step).fold(0.0, f64::max);
+        Ok(peak)
+    }
+
+    /// 信号の最大値がlimit未満であることを検証する（テスト用）
+    pub fn assert_max_below(&self, signame: &str, limit: f64) -> anyhow::Result<()> {
+        let data = self.signal_data(signame)?;
+        let (idx, max) = data.iter().enumerate().fold((0, f64::NEG_INFINITY), |acc, (i, &v)| if v > acc.1 { (i, v) } else { acc });
+        if max >= limit {
+            return Err(anyhow!("SimRecorder: 信号{}の最大値が上限以上です。\nmax = {} (time = {}), limit = {}", signame, max, self.timedata[idx], limit));
+        }
+        Ok(())
+    }
+
+    /// CSV出力時に信号（内部状態を含む）の値を 値 * scale + offset に変換して出力するよう設定する
+    /// ヘッダの単位はunitで置き換える（例：rad → deg は scale = 180/π, offset = 0, unit = "deg"）
+    /// 記録データ自体は変更しない。同じ信号に再設定した場合は上書きする
+    pub fn set_export_scale(&mut self, signame: &str, scale: f64, offset: f64, unit: impl Into<String>) -> anyhow::Result<()> {
+        let recorded = self.input_bus.iter().any(|sig| sig.name() == signame) || self.state_subs.iter().any(|(_, name)| name == signame);
+        if !recorded {
+            return Err(anyhow!("SimRecorder: 信号{}は記録されていません。", signame));
+        }
+
+        let setting = (signame.to_string(), scale, offset, unit.into());
+        match self.export_scales.iter_mut().find(|(name, ..)| name == signame) {
+            Some(s) => *s = setting,
+            None => self.export_scales.push(setting),
+        }
+        Ok(())
+    }
+
+    /// ファイル出力する全系列の(列名, データ, (倍率, オフセット))
+    /// 出力時の変換（倍率, オフセット）を系列ごとに決める。変換を設定した系列は信号名と単位を差し替える
+    fn export_series(&self) -> Vec<(String, &Vec<f64>, (f64, f64))> {
+        let names = self.input_bus.iter().map(|sig| sig.name()).chain(self.state_subs.iter().map(|(_, name)| name.clone()));
+        self.all_series().into_iter().zip(names).map(|((dispname, data), name)| {
+            match self.export_scales.iter().find(|(n, ..)| *n == name) {
+                Some((_, scale, offset, unit)) => (format!("{}[{}]", name, unit), data, (*scale, *offset)),
+                None => (dispname, data, (1.0, 0.0)),
+            }
+        }).collect()
+    }
+
     pub fn export(&self, filepath: &str) -> anyhow::Result<()> {
-        let mut file = BufWriter::new(File::create(filepath).unwrap());
+        let filepath = self.output_path(filepath)?;
+        let mut file = BufWriter::new(File::create(&filepath).context(format!("SimRecorder: ファイルを作成できません。path = {}", filepath.display()))?);
         
-        // 一行目の信号名の部分を作成
+        let series = self.export_series();
+
+        // メタデータ（コメント行）
+        if let Some(description) = &self.description {
+            for line in description.lines() {
+                writeln!(file, "# {}", line)?;
+            }
+            if let Some((start, end, delta_t)) = self.sim_range {
+                writeln!(file, "# start_time[s]: {}", start)?;
+                writeln!(file, "# end_time[s]: {}", end)?;
+                writeln!(file, "# delta_t[s]: {}", delta_t)?;
+            }
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs());
+            writeln!(file, "# exported_at[unix]: {}", now)?;
+        }
+
+        // 信号名の行を作成
         let mut seriesname = vec!["time[s]".to_string()];
-        self.input_bus.get_sigdef().iter().for_each(|sig| seriesname.push( sig.to_string() ) );
+        series.iter().for_each(|(name, _, _)| seriesname.push( name.clone() ) );
         
         writeln!(file, "{}", seriesname.join(","))?;
 
@@ -55,8 +436,8 @@ impl SimRecorder {
         for idx in 0..siglen {
             let mut line = vec![self.timedata[idx].to_string()];
 
-            for sigidx in 0..self.signum {
-                line.push(self.storage[sigidx][idx].to_string());
+            for (_, data, (scale, offset)) in series.iter() {
+                line.push(data.get(idx).map_or(String::new(), |v| (v * scale + offset).to_string()));
             }
 
             writeln!(file, "{}", line.join(","))?;
@@ -65,37 +446,132 @@ impl SimRecorder {
         Ok(())
     }
 
+    /// 記録データをParquet形式で出力する（parquetフィーチャが有効な場合のみ）
+    /// 列はexportと同じ（"time[s]", "信号名[単位]", ...、set_export_scaleの変換も適用）で、全てf64とする
+    /// PARQUET_ROW_GROUP行ごとに行グループとして書き出すため、長時間の記録でも全データの表を一度に作成しない
+    /// 記録数が時刻より少ない系列の不足分はNaNとする
+    #[cfg(feature = "parquet")]
+    pub fn export_parquet(&self, filepath: &str) -> anyhow::Result<()> {
+        use std::sync::Arc;
+        use arrow_array::{ArrayRef, Float64Array, RecordBatch};
+        use arrow_schema::{DataType, Field, Schema};
+        use parquet::arrow::ArrowWriter;
+        use parquet::file::properties::WriterProperties;
+
+        let series = self.export_series();
+        let fields = std::iter::once("time[s]".to_string()).chain(series.iter().map(|(name, ..)| name.clone()))
+                                                             .map(|name| Field::new(name, DataType::Float64, false))
+                                                             .collect::<Vec<Field>>();
+        let schema = Arc::new(Schema::new(fields));
+
+        let filepath = self.output_path(filepath)?;
+        let file = File::create(&filepath).context(format!("SimRecorder: ファイルを作成できません。path = {}", filepath.display()))?;
+        let props = WriterProperties::builder().set_max_row_group_size(PARQUET_ROW_GROUP).build();
+        let mut writer = ArrowWriter::try_new(file, schema.clone(), Some(props))?;
+
+        let len = self.timedata.len();
+        for start in (0..len).step_by(PARQUET_ROW_GROUP) {
+            let end = (start + PARQUET_ROW_GROUP).min(len);
+            let mut columns: Vec<ArrayRef> = vec![Arc::new(Float64Array::from(self.timedata[start..end].to_vec()))];
+            series.iter().for_each(|(_, data, (scale, offset))| {
+                let vals = (start..end).map(|idx| data.get(idx).map_or(f64::NAN, |v| v * scale + offset));
+                columns.push(Arc::new(Float64Array::from_iter_values(vals)));
+            });
+
+            writer.write(&RecordBatch::try_new(schema.clone(), columns)?)?;
+            writer.flush()?; // 行グループを確定して書き出す
+        }
+        writer.close()?;
+
+        Ok(())
+    }
+
+    /// 記録データ全体を行列として取得する（列名, データ行列）
+    /// 0列目が時刻、以降の列は記録している信号・内部状態（exportと同じ並び）。行は記録した各時刻に対応する
+    /// 列名はexportのヘッダと同じ表記（例："time[s]", "motor_trq[Nm]"）。データが不足している要素はNaNとする
+    pub fn to_matrix(&self) -> (Vec<String>, DMatrix<f64>) {
+        let series = self.all_series();
+        let colnames = std::iter::once("time[s]".to_string()).chain(series.iter().map(|(name, _)| name.clone())).collect::<Vec<String>>();
+
+        let matrix = DMatrix::from_fn(self.timedata.len(), colnames.len(), |row, col| {
+            if col == 0 {
+                self.timedata[row]
+            } else {
+                series[col - 1].1.get(row).copied().unwrap_or(f64::NAN)
+            }
+        });
+
+        (colnames, matrix)
+    }
+
     pub fn timeplot_all(&self, filename: &str, pltsize: (u32, u32), pltdivide: (usize, usize)) -> anyhow::Result<()>{
-        let root_area = BitMapBackend::new(filename, pltsize).into_drawing_area();
+        let filename = self.output_path(filename)?;
+        let root_area = BitMapBackend::new(&filename, pltsize).into_drawing_area();
         let child_areas = root_area.split_evenly(pltdivide);
 
         root_area.fill(&WHITE).unwrap();
 
-        if self.signum > pltdivide.0 * pltdivide.1 {
+        let series = self.all_series();
+        if series.len() > pltdivide.0 * pltdivide.1 {
             return Err(anyhow!("プロットの分割数が不足しています。"));
         }
 
-        self.storage.iter().enumerate().for_each( |(idx, data)| {
-            let sig = &self.input_bus.get_sigdef()[idx];
-            self.timeplot_subfn(&child_areas[idx], &sig.to_string(), &data);
+        let names = self.input_bus.iter().map(|sig| sig.name()).chain(self.state_subs.iter().map(|(_, name)| name.clone()));
+        series.iter().zip(names).enumerate().for_each( |(idx, ((dispname, data), name))| {
+            self.timeplot_subfn(&child_areas[idx], dispname, &[(name.as_str(), data)]);
         });
 
         Ok(())
     }
 
-    fn timeplot_subfn(&self, plt: &DrawingArea<BitMapBackend, Shift>, caption: &str, data: &Vec<f64>) {
+    /// 複数の信号を1つの軸に重ねてプロットする
+    /// groupsの要素ごとに1つのグラフ（縦に並べる）を作成し、グループ内の信号を色を変えて凡例付きで描画する
+    /// 例：timeplot_overlay("out.png", (500, 500), &[&["target_angle", "beam_angle"], &["ball_pos"]])
+    pub fn timeplot_overlay(&self, filename: &str, pltsize: (u32, u32), groups: &[&[&str]]) -> anyhow::Result<()> {
+        if groups.is_empty() || groups.iter().any(|group| group.is_empty()) {
+            return Err(anyhow!("SimRecorder: 重ねてプロットする信号のグループが空です。"));
+        }
+
+        let mut plots = Vec::with_capacity(groups.len());
+        for group in groups.iter() {
+            let mut series = Vec::with_capacity(group.len());
+            for signame in group.iter() {
+                let data = self.get_signal(signame).ok_or(anyhow!("SimRecorder: 信号{}は記録されていません。", signame))?;
+                series.push((*signame, data));
+            }
+            plots.push(series);
+        }
+
+        let filename = self.output_path(filename)?;
+        let root_area = BitMapBackend::new(&filename, pltsize).into_drawing_area();
+        let child_areas = root_area.split_evenly((groups.len(), 1));
+
+        root_area.fill(&WHITE).unwrap();
+
+        plots.iter().zip(child_areas.iter()).for_each(|(series, area)| {
+            let caption = series.iter().map(|(name, _)| *name).collect::<Vec<&str>>().join(", ");
+            self.timeplot_subfn(area, &caption, series);
+        });
+
+        Ok(())
+    }
+
+    /// 1つのグラフに系列（信号名, データ）を描画する（y軸の範囲は全系列の最小値～最大値）
+    /// 系列が複数の場合、または凡例の表示名が設定されている場合は凡例を表示する
+    fn timeplot_subfn(&self, plt: &DrawingArea<BitMapBackend, Shift>, caption: &str, series: &[(&str, &[f64])]) {
         
         plt.fill(&WHITE).unwrap();
     
         let font = ("sans-serif", 20);
 
-        let (y_min, y_max) = data.iter()
+        let (y_min, y_max) = series.iter().flat_map(|(_, data)| data.iter())
                          .fold(
                            (0.0/0.0, 0.0/0.0),
                            |(m,n), v| (v.min(m), v.max(n))
                           ); // f64はNaNがあるためordが実装されていない。min, maxを使うための工夫が必要⇒https://qiita.com/lo48576/items/343ca40a03c3b86b67cb
         let datalen = self.timedata.len();
-        let xrange = 0.0..self.timedata[datalen - 1]; 
+        let (t_first, t_last) = (self.timedata[0], self.timedata[datalen - 1]);
+        let xrange = t_first.min(t_last)..t_first.max(t_last); // 逆向きに実行した場合は時刻が降順に並ぶ
         let yrange = y_min..y_max;
       
         let mut chart = ChartBuilder::on(&plt)
@@ -111,26 +587,69 @@ impl SimRecorder {
         // x軸y軸、グリッド線などを描画
         chart.configure_mesh().draw().unwrap();
 
-        let line_series = LineSeries::new(
-            self.timedata.iter()
-                    .zip(data.iter())
-                    .map(|(x, y)| (*x, *y)),
-                &RED);
+        let colors = [RED, BLUE, GREEN, MAGENTA, CYAN, BLACK];
+        let mut labeled = false;
+        for (idx, (name, data)) in series.iter().enumerate() {
+            let style = self.styles.iter().find(|(n, ..)| n == name);
+            let color = style.map_or(colors[idx % colors.len()], |(_, color, _)| *color);
+            let label = match style.and_then(|(_, _, label)| label.as_ref()) {
+                Some(label) => { labeled = true; label.as_str() },
+                None => name,
+            };
+            let line_series = LineSeries::new(
+                self.timedata.iter()
+                        .zip(data.iter())
+                        .map(|(x, y)| (*x, *y)),
+                    &color);
 
-        chart.draw_series(line_series).unwrap();
+            chart.draw_series(line_series).unwrap()
+                 .label(label)
+                 .legend(move |(x, y)| PathElement::new(vec![(x, y), (x + 20, y)], color));
+        }
+
+        if series.len() > 1 || labeled {
+            chart.configure_series_labels()
+                 .border_style(BLACK)
+                 .draw().unwrap();
+        }
 
     }
 }
 
 impl ModelCore for SimRecorder {
     fn initialize(&mut self, sim_time: &SimTime) {
-        let stepnum = sim_time.step_num();
+        self.step_count = 0;
+        self.record_now = self.warmed_up(sim_time);
+        self.overflowed = false;
+        self.sim_range = Some((sim_time.start_time(), sim_time.end_time(), sim_time.delta_t()));
 
-        self.timedata = Vec::with_capacity(stepnum);
-        self.storage = (0..self.signum).map(|_| Vec::with_capacity(stepnum) ).collect::<Vec<Vec<f64>>>();
-        self.storage.iter_mut().enumerate().for_each(|(idx, sig)| sig.push(self.input_bus[idx].val())); // 0秒のデータはbusの初期値を入れる（たいていの場合は0)
-        
-        self.timedata.push(sim_time.start_time()); // 初期時間を設定する
+        if let RecordMode::Fixed(cap) = self.mode {
+            // 事前確保した領域を空にして再利用する（不足している場合のみ確保する）
+            self.timedata.clear();
+            self.storage.iter_mut().for_each(|data| data.clear());
+            self.state_storage.resize_with(self.state_subs.len(), Vec::new);
+            self.state_storage.iter_mut().for_each(|data| {
+                data.clear();
+                data.reserve_exact(cap);
+            });
+        } else {
+            // ウォームアップ期間を除いた記録区間のステップ数
+            let skipped = (self.warmup.unwrap_or(0.0) / sim_time.delta_t().abs()).floor() as usize;
+            let horizon = sim_time.step_num().saturating_sub(skipped) + 1;
+            let stepnum = match self.mode {
+                RecordMode::Decimate(n) => horizon / n + 1,
+                RecordMode::Window(cap) => horizon.min(cap),
+                _ => horizon,
+            }.min(PREALLOC_MAX); // 長時間シミュレーションで巨大な領域を一度に確保しないよう上限を設ける
+
+            self.timedata = Vec::with_capacity(stepnum);
+            self.storage = (0..self.signum).map(|_| Vec::with_capacity(stepnum) ).collect::<Vec<Vec<f64>>>();
+            self.state_storage = self.state_subs.iter().map(|_| Vec::with_capacity(stepnum)).collect(); // 内部状態はSimSystemがrecord_statesで記録する
+        }
+        if self.record_now {
+            self.storage.iter_mut().enumerate().for_each(|(idx, sig)| sig.push(self.input_bus[idx].val())); // 0秒のデータはbusの初期値を入れる（たいていの場合は0)
+            self.timedata.push(sim_time.start_time()); // 初期時間を設定する
+        }
         
     }
 
@@ -139,12 +658,37 @@ impl ModelCore for SimRecorder {
     }
 
     fn nextstate(&mut self, sim_time: &SimTime) {
-        // 処理なし
+        self.step_count += 1;
+        if !self.warmed_up(sim_time) {
+            self.record_now = false;
+            return;
+        }
+        self.record_now = match self.mode {
+            RecordMode::Decimate(n) => self.step_count.is_multiple_of(n),
+            RecordMode::Fixed(cap) => {
+                self.overflowed |= self.timedata.len() >= cap;
+                !self.overflowed
+            },
+            _ => true,
+        };
+        if !self.record_now {
+            return;
+        }
+
         self.timedata.push(sim_time.time());
         
         self.input_bus.iter().enumerate().for_each(|(idx, sig)| {
-            self.storage[idx].push(sig.val());
-        })
+            let val = match (self.smoothing[idx], self.storage[idx].last()) {
+                (Some(alpha), Some(prev)) => alpha * sig.val() + (1.0 - alpha) * prev,
+                _ => sig.val(),
+            };
+            self.storage[idx].push(val);
+        });
+
+        if let RecordMode::Window(cap) = self.mode {
+            keep_last(&mut self.timedata, cap);
+            self.storage.iter_mut().for_each(|data| keep_last(data, cap));
+        }
 
     }
 
@@ -157,7 +701,118 @@ impl ModelCore for SimRecorder {
     }
 }
 
+/// イベントのトリガ条件（しきい値を横切る向き）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerEdge {
+    Rising,  // しきい値を下から上に横切った時
+    Falling, // しきい値を上から下に横切った時
+    Both,    // どちらの向きでも
+}
+
+/// イベントレコーダ
+/// 毎ステップではなく、トリガ信号がしきい値を横切った時のみ入力バス全体を記録する
+/// イベント時刻と各信号の値は前後のステップの値から線形補間して求める（ボールが跳ねた瞬間や振動のゼロクロス時の状態の取得など）
+/// SimSystem::regist_event_recorderで登録して使用する
+#[derive(Debug)]
+pub struct EventRecorder {
+    input_bus: RefBus,
+    trigger: usize,        // トリガ信号の入力バス内の位置
+    threshold: f64,        // しきい値
+    edge: TriggerEdge,     // トリガ条件
+    prev_time: f64,        // 前ステップの時刻
+    prev: Vec<f64>,        // 前ステップの入力
+    event_times: Vec<f64>, // イベント時刻
+    storage: Vec<Vec<f64>>, // 信号ごとのイベント時の値
+}
+
+impl EventRecorder {
+    /// ## EventRecorderの引数定義
+    /// 1. 第1引数：入力バス（トリガ信号を含む）
+    /// 1. 第2引数：trigger: トリガ信号の信号名（入力バス内の信号名）
+    /// 1. 第3引数：threshold: しきい値
+    /// 1. 第4引数：edge: トリガ条件
+    pub fn new(input_def: Vec<SigDef>, trigger: &str, threshold: f64, edge: TriggerEdge) -> anyhow::Result<Self> {
+        let inbus = RefBus::try_from(input_def).context("EventRecorderの入力バスが不正です。")?;
+        let trigger = inbus.iter().position(|sig| sig.name() == trigger)
+                           .ok_or_else(|| anyhow!("EventRecorder: トリガ信号{}が入力バスにありません。", trigger))?;
+
+        Ok(Self {
+            storage: vec![Vec::new(); inbus.len()],
+            input_bus: inbus,
+            trigger,
+            threshold,
+            edge,
+            prev_time: 0.0,
+            prev: Vec::new(),
+            event_times: Vec::new(),
+        })
+    }
+
+    /// 記録したイベント時刻を取得する
+    pub fn get_time(&self) -> &[f64] {
+        &self.event_times
+    }
+
+    /// 記録したイベントの数
+    pub fn event_count(&self) -> usize {
+        self.event_times.len()
+    }
+
+    /// 信号名を指定してイベント時の値を取得する
+    pub fn get_signal(&self, signame: &str) -> Option<&[f64]> {
+        self.input_bus.iter().position(|sig| sig.name() == signame)
+                             .and_then(|idx| self.storage.get(idx))
+                             .map(|data| data.as_slice())
+    }
+
+    /// 前ステップの値prevから現在値curの間でトリガ条件を満たしたか
+    fn triggered(&self, prev: f64, cur: f64) -> bool {
+        let rising = prev < self.threshold && cur >= self.threshold;
+        let falling = prev > self.threshold && cur <= self.threshold;
+        match self.edge {
+            TriggerEdge::Rising => rising,
+            TriggerEdge::Falling => falling,
+            TriggerEdge::Both => rising || falling,
+        }
+    }
+}
+
+impl ModelCore for EventRecorder {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.prev_time = sim_time.start_time();
+        self.prev = self.input_bus.to_vec_f64();
+        self.event_times.clear();
+        self.storage.iter_mut().for_each(|data| data.clear());
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
 
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        let cur = self.input_bus.to_vec_f64();
+        let (p, c) = (self.prev[self.trigger], cur[self.trigger]);
+
+        if self.triggered(p, c) {
+            // しきい値を横切った位置を前後のステップの間で線形補間する
+            let ratio = if c != p { (self.threshold - p) / (c - p) } else { 1.0 };
+            self.event_times.push(self.prev_time + ratio * (sim_time.time() - self.prev_time));
+            self.storage.iter_mut().zip(self.prev.iter().zip(cur.iter()))
+                .for_each(|(data, (p, c))| data.push(p + ratio * (c - p)));
+        }
+
+        self.prev_time = sim_time.time();
+        self.prev = cur;
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.input_bus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        None
+    }
+}
 
 #[cfg(test)]
 mod scope_test {
@@ -209,6 +864,408 @@ mod scope_test {
         assert_eq!(scope.storage[1][10], 20.0);
         assert_eq!(scope.storage[2][10], 30.0);
 
+        let last = scope.get_time().len() as f64 - 1.0;
+        scope.assert_final_approx("motor_trq", last, 1e-12).unwrap();
+        assert!(scope.assert_final_approx("motor_trq", last + 1.0, 0.5).is_err());
+        scope.assert_max_below("motor_volt", last * 2.0 + 0.1).unwrap();
+        assert!(scope.assert_max_below("motor_volt", last * 2.0).is_err());
+        assert!(scope.assert_max_below("unknown", 0.0).is_err());
+
         
     }
+
+    #[test]
+    fn export_scale_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("angle", "rad"), SigDef::new("temp", "K")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("angle", "rad"), SigDef::new("temp", "K")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["angle", "temp"], &["angle", "temp"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.5);
+        bus[0].set_val(std::f64::consts::PI);
+        bus[1].set_val(300.0);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            scope.nextstate(&sim_time);
+        }
+
+        scope.set_export_scale("angle", 180.0 / std::f64::consts::PI, 0.0, "deg").unwrap();
+        scope.set_export_scale("temp", 2.0, 0.0, "-").unwrap();
+        scope.set_export_scale("temp", 1.0, -273.15, "degC").unwrap(); // 上書き
+        assert!(scope.set_export_scale("unknown", 1.0, 0.0, "-").is_err());
+
+        let filepath = "test_output\\export_scale.csv";
+        scope.export(filepath).unwrap();
+        let csv = std::fs::read_to_string(filepath).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[0], "time[s],angle[deg],temp[degC]");
+        let vals = lines[1].split(',').map(|v| v.parse::<f64>().unwrap()).collect::<Vec<f64>>();
+        assert!((vals[1] - 180.0).abs() < 1e-9);
+        assert!((vals[2] - 26.85).abs() < 1e-9);
+
+        assert_eq!(scope.get_signal("angle").unwrap()[0], std::f64::consts::PI); // 記録データは変更しない
+    }
+
+    #[test]
+    fn smoothing_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("raw", "-"), SigDef::new("smooth", "-")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("raw", "-"), SigDef::new("smooth", "-")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["raw", "smooth"], &["raw", "smooth"]).unwrap();
+        scope.set_smoothing("smooth", 0.5).unwrap();
+        assert!(scope.set_smoothing("smooth", 0.0).is_err());
+        assert!(scope.set_smoothing("smooth", 1.5).is_err());
+        assert!(scope.set_smoothing("unknown", 0.5).is_err());
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.25);
+        bus[0].set_val(1.0);
+        bus[1].set_val(1.0);
+        scope.initialize(&sim_time);
+        while let Some((i, _)) = sim_time.next() {
+            let val = if i % 2 == 1 { 0.0 } else { 1.0 };
+            bus[0].set_val(val);
+            bus[1].set_val(val);
+            scope.nextstate(&sim_time);
+        }
+
+        assert_eq!(scope.get_signal("raw").unwrap(), &[1.0, 0.0, 1.0, 0.0, 1.0]);
+        assert_eq!(scope.get_signal("smooth").unwrap(), &[1.0, 0.5, 0.75, 0.375, 0.6875]);
+        assert_eq!(bus[1].val(), 1.0); // 信号の値は変更しない
+    }
+
+    #[test]
+    fn plot_style_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["x", "v"], &["x", "v"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.01);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            bus[0].set_val(sim_time.time().sin());
+            bus[1].set_val(sim_time.time().cos());
+            scope.nextstate(&sim_time);
+        }
+
+        let baseline = "test_output\\plot_style_base.png";
+        scope.timeplot_all(baseline, (500, 500), (2, 1)).unwrap();
+
+        scope.set_style("x", BLUE, Some("position")).unwrap()
+             .set_style("v", GREEN, Some("velocity")).unwrap();
+        assert!(scope.set_style("unknown", BLUE, None).is_err());
+        let styled = "test_output\\plot_style.png";
+        scope.timeplot_all(styled, (500, 500), (2, 1)).unwrap();
+
+        let base_len = std::fs::metadata(baseline).unwrap().len();
+        let styled_len = std::fs::metadata(styled).unwrap().len();
+        assert!(base_len > 0);
+        assert!(styled_len > base_len, "{} <= {}", styled_len, base_len); // 凡例の分だけ描画内容が増える
+    }
+
+    #[test]
+    fn timeplot_overlay_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s"), SigDef::new("trq", "Nm")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s"), SigDef::new("trq", "Nm")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["x", "v", "trq"], &["x", "v", "trq"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.01);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            bus[0].set_val(sim_time.time().sin());
+            bus[1].set_val(sim_time.time().cos());
+            bus[2].set_val(10.0 * sim_time.time());
+            scope.nextstate(&sim_time);
+        }
+
+        // 単位の異なる信号も1つの軸に重ねて描画できる（y軸は全系列の範囲）
+        let single = "test_output\\overlay_single.png";
+        scope.timeplot_overlay(single, (500, 300), &[&["x"]]).unwrap();
+        let overlay = "test_output\\overlay.png";
+        scope.timeplot_overlay(overlay, (500, 600), &[&["x", "v", "trq"], &["v"]]).unwrap();
+        let single_len = std::fs::metadata(single).unwrap().len();
+        let overlay_len = std::fs::metadata(overlay).unwrap().len();
+        assert!(single_len > 0);
+        assert!(overlay_len > single_len, "{} <= {}", overlay_len, single_len);
+
+        // 記録されていない信号・空のグループはエラー
+        let err = scope.timeplot_overlay(overlay, (500, 300), &[&["x", "unknown"]]).unwrap_err();
+        assert!(err.to_string().contains("unknown"));
+        assert!(scope.timeplot_overlay(overlay, (500, 300), &[]).is_err());
+        assert!(scope.timeplot_overlay(overlay, (500, 300), &[&["x"], &[]]).is_err());
+    }
+
+    #[cfg(feature = "parquet")]
+    #[test]
+    fn export_parquet_test() {
+        use parquet::file::reader::{FileReader, SerializedFileReader};
+
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m"), SigDef::new("angle", "rad")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("x", "m"), SigDef::new("angle", "rad")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["x", "angle"], &["x", "angle"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.01);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            bus[0].set_val(sim_time.time());
+            bus[1].set_val(std::f64::consts::PI);
+            scope.nextstate(&sim_time);
+        }
+        scope.set_export_scale("angle", 180.0 / std::f64::consts::PI, 0.0, "deg").unwrap();
+
+        let filepath = "test_output\\export_parquet.parquet";
+        scope.export_parquet(filepath).unwrap();
+
+        let reader = SerializedFileReader::new(File::open(filepath).unwrap()).unwrap();
+        let meta = reader.metadata().file_metadata();
+        assert_eq!(meta.num_rows() as usize, scope.get_time().len());
+        let names = meta.schema_descr().columns().iter().map(|col| col.name().to_string()).collect::<Vec<String>>();
+        assert_eq!(names, vec!["time[s]", "x[m]", "angle[deg]"]);
+    }
+
+    #[test]
+    fn bounded_recorder_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m")]).unwrap();
+        let mut decimated = SimRecorder::new_decimated(vec![SigDef::new("x", "m")], 10).unwrap();
+        let mut windowed = SimRecorder::new_windowed(vec![SigDef::new("x", "m")], 50).unwrap();
+        decimated.interface_in().unwrap().connect_to(&bus, &["x"], &["x"]).unwrap();
+        windowed.interface_in().unwrap().connect_to(&bus, &["x"], &["x"]).unwrap();
+        assert!(SimRecorder::new_decimated(vec![SigDef::new("x", "m")], 0).is_err());
+        assert!(SimRecorder::new_windowed(vec![SigDef::new("x", "m")], 0).is_err());
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.001);
+        decimated.initialize(&sim_time);
+        windowed.initialize(&sim_time);
+        let mut steps = 0;
+        while let Some((i, _)) = sim_time.next() {
+            bus[0].set_val(i as f64);
+            decimated.nextstate(&sim_time);
+            windowed.nextstate(&sim_time);
+            assert!(windowed.get_time().len() <= 50);
+            assert!(windowed.get_signal("x").unwrap().len() <= 50);
+            steps += 1;
+        }
+
+        // 間引き：初期値 + 10ステップごと
+        assert_eq!(decimated.get_time().len(), steps / 10 + 1);
+        assert_eq!(decimated.get_signal("x").unwrap()[1], 10.0);
+
+        // 窓：直近の50サンプル
+        assert_eq!(windowed.get_time().len(), 50);
+        assert_eq!(*windowed.get_signal("x").unwrap().last().unwrap(), steps as f64);
+        assert_eq!(windowed.get_signal("x").unwrap()[0], (steps - 49) as f64);
+
+        decimated.export("test_output\\decimated.csv").unwrap();
+        windowed.export("test_output\\windowed.csv").unwrap();
+        let csv = std::fs::read_to_string("test_output\\windowed.csv").unwrap();
+        assert_eq!(csv.lines().count(), 51);
+        windowed.timeplot_all("test_output\\windowed.png", (500, 300), (1, 1)).unwrap();
+    }
+
+    #[test]
+    fn export_metadata_test() {
+        use crate::simcore::sim_model::source_models::{LookupFunc, OutOfRangeMode};
+
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("x", "m")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["x"], &["x"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.5);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            bus[0].set_val(sim_time.time() * 2.0);
+            scope.nextstate(&sim_time);
+        }
+
+        let filepath = "test_output\\export_metadata.csv";
+        scope.export(filepath).unwrap();
+        assert!(!std::fs::read_to_string(filepath).unwrap().starts_with('#')); // 既定ではメタデータなし
+
+        scope.set_description("ステップ応答\n試行1");
+        scope.export(filepath).unwrap();
+        let csv = std::fs::read_to_string(filepath).unwrap();
+        let lines = csv.lines().collect::<Vec<&str>>();
+        assert_eq!(lines[..5], ["# ステップ応答", "# 試行1", "# start_time[s]: 0", "# end_time[s]: 1", "# delta_t[s]: 0.5"]);
+        assert!(lines[5].starts_with("# exported_at[unix]: "));
+        assert_eq!(lines[6], "time[s],x[m]");
+
+        // メタデータ付きのCSVをそのまま読み込める
+        let lf = LookupFunc::new(vec![SigDef::new("x", "m")], filepath, OutOfRangeMode::Hold).unwrap();
+        assert_eq!(lf.eval_output_at(0.75).unwrap()[0].val(), 1.5);
+    }
+
+    #[test]
+    fn signal_stats_test() {
+        use crate::simcore::sim_model::{source_models::ConstantFunc, de_models::{SpaceStateModel, SolverType}, model_core::connect_models};
+        use crate::simcore::sim_system::SimSystem;
+
+        // 2次系のステップ応答（固有角周波数wn = 2π[rad/s]、減衰比0.2の不足減衰）
+        let (wn, zeta) = (2.0 * std::f64::consts::PI, 0.2);
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 2, SolverType::RungeKutta).unwrap();
+        ssm.set_mtrx_a(&[0.0, 1.0, -wn * wn, -2.0 * zeta * wn]).unwrap();
+        ssm.set_mtrx_b(&[0.0, wn * wn]).unwrap();
+        ssm.set_mtrx_c(&[1.0, 0.0]).unwrap();
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        connect_models(&ssm, &["y"], &mut rcd, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 10.0, 0.001);
+        sys.regist_model(input);
+        sys.regist_model(ssm);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+        let rcd = sys.get_result("rcd").unwrap();
+
+        // 理論値：オーバーシュート exp(-πζ/√(1-ζ²))、ピーク時刻 π/(wn√(1-ζ²))
+        let damped = (1.0 - zeta * zeta).sqrt();
+        let os = rcd.overshoot("y").unwrap();
+        assert!(os > 0.0);
+        assert!((os - (-std::f64::consts::PI * zeta / damped).exp()).abs() < 1e-3, "{}", os);
+
+        let stats = rcd.stats("y").unwrap();
+        assert_eq!(stats.min, 0.0);
+        assert!((stats.time_of_max - std::f64::consts::PI / (wn * damped)).abs() < 2e-3, "{}", stats.time_of_max);
+        assert!((stats.final_value - 1.0).abs() < 1e-3);
+        assert!(stats.mean > 0.9 && stats.mean < 1.1);
+        assert!(stats.rms >= stats.mean);
+
+        // 2%整定時間はおよそ 4/(ζwn) = 3.2[s]
+        let ts = rcd.settling_time("y", 1.0, 0.02).unwrap().unwrap();
+        assert!(ts > 2.0 && ts < 4.0, "{}", ts);
+        assert!(rcd.settling_time("y", 2.0, 0.02).unwrap().is_none());
+        assert!(rcd.stats("unknown").is_err());
+    }
+
+    #[test]
+    fn fixed_capacity_test() {
+        use crate::simcore::sim_model::{source_models::RampFunc, model_core::connect_models};
+        use crate::simcore::sim_system::SimSystem;
+
+        let make_system = |capacity: usize| {
+            let ramp = RampFunc::new(vec![SigDef::new("y", "-")], vec![(0.0, 0.0, false, 0.0, 1.0)]).unwrap();
+            let mut rcd = SimRecorder::with_capacity(vec![SigDef::new("y", "-")], capacity).unwrap();
+            connect_models(&ramp, &["y"], &mut rcd, &["y"]).unwrap();
+            let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+            sys.regist_model(ramp);
+            sys.regist_recorder("rcd", rcd);
+            sys
+        };
+
+        // 容量に収まる場合は事前確保した領域のまま記録する（再実行しても同じ領域を使う）
+        let mut sys = make_system(9);
+        let ptr = sys.get_recorder("rcd").unwrap().storage[0].as_ptr();
+        sys.try_run().unwrap();
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_signal("y").unwrap(), &[0.0, 0.125, 0.25, 0.375, 0.5, 0.625, 0.75, 0.875, 1.0]);
+        assert_eq!(rcd.storage[0].as_ptr(), ptr);
+        assert_eq!(rcd.storage[0].capacity(), 9);
+        sys.reset();
+        sys.try_run().unwrap();
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_time().len(), 9);
+        assert_eq!(rcd.storage[0].as_ptr(), ptr);
+
+        // 容量を超えるとエラーで中断し、容量までのデータは残る
+        let mut sys = make_system(5);
+        let err = sys.try_run().unwrap_err().to_string();
+        assert!(err.contains("容量5"), "{}", err);
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_time(), &[0.0, 0.125, 0.25, 0.375, 0.5]);
+        assert_eq!(rcd.storage[0].capacity(), 5);
+
+        assert!(SimRecorder::with_capacity(vec![SigDef::new("y", "-")], 0).is_err());
+    }
+
+    #[test]
+    fn warmup_test() {
+        use crate::simcore::sim_model::{source_models::RampFunc, model_core::connect_models};
+        use crate::simcore::sim_system::SimSystem;
+
+        let make_recorder = || SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        let ramp = RampFunc::new(vec![SigDef::new("y", "-")], vec![(0.0, 0.0, false, 0.0, 1.0)]).unwrap();
+        let (mut rcd1, mut rcd2, mut rcd3) = (make_recorder(), make_recorder(), make_recorder());
+        rcd1.set_warmup(0.5).unwrap();
+        rcd3.set_warmup(0.25).unwrap(); // レコーダ側の設定はSimSystemの設定より優先する
+        connect_models(&ramp, &["y"], &mut rcd1, &["y"]).unwrap();
+        connect_models(&ramp, &["y"], &mut rcd2, &["y"]).unwrap();
+        connect_models(&ramp, &["y"], &mut rcd3, &["y"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(ramp);
+        sys.regist_recorder("rcd1", rcd1);
+        sys.set_warmup(0.75).unwrap();
+        sys.regist_recorder("rcd2", rcd2);
+        sys.regist_recorder("rcd3", rcd3);
+        sys.run();
+
+        // ウォームアップ期間のデータは記録せず、記録区間分だけ領域を確保する
+        let rcd1 = sys.get_result("rcd1").unwrap();
+        assert_eq!(rcd1.get_time(), &[0.75, 0.875, 1.0]); // 登録済みのレコーダにも反映される
+        let rcd2 = sys.get_result("rcd2").unwrap();
+        assert_eq!(rcd2.get_time(), &[0.75, 0.875, 1.0]);
+        assert_eq!(rcd2.get_signal("y").unwrap(), &[0.75, 0.875, 1.0]);
+        assert_eq!(rcd2.timedata.capacity(), 3);
+        assert_eq!(rcd2.stats("y").unwrap().min, 0.75);
+        let rcd3 = sys.get_result("rcd3").unwrap();
+        assert_eq!(rcd3.get_time().len(), 7);
+        assert_eq!(rcd3.get_time()[0], 0.25);
+
+        assert!(make_recorder().set_warmup(-1.0).is_err());
+        assert!(make_recorder().set_warmup(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn to_matrix_test() {
+        let mut bus = Bus::try_from(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s")]).unwrap();
+        let mut scope = SimRecorder::new(vec![SigDef::new("x", "m"), SigDef::new("v", "m/s")]).unwrap();
+        scope.interface_in().unwrap().connect_to(&bus, &["x", "v"], &["x", "v"]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.5);
+        scope.initialize(&sim_time);
+        while sim_time.next().is_some() {
+            bus[0].set_val(sim_time.time() * 2.0);
+            bus[1].set_val(2.0);
+            scope.nextstate(&sim_time);
+        }
+
+        let (names, mat) = scope.to_matrix();
+        assert_eq!(names, vec!["time[s]", "x[m]", "v[m/s]"]);
+        assert_eq!(mat.shape(), (scope.get_time().len(), 3));
+        assert_eq!(mat.column(0).iter().copied().collect::<Vec<f64>>(), scope.get_time());
+        assert_eq!(mat.column(1).iter().copied().collect::<Vec<f64>>(), scope.get_signal("x").unwrap());
+        assert_eq!(mat[(mat.nrows() - 1, 2)], 2.0);
+    }
+
+    #[test]
+    fn event_recorder_test() {
+        use crate::simcore::sim_model::{source_models::{WaveFunc, WaveFuncSetting, WaveFuncType, ClockFunc}, model_core::connect_models};
+        use crate::simcore::sim_system::SimSystem;
+
+        // sin(2πt - 0.5)が0を下から上に横切る時刻は t = k + 0.5 / 2π
+        let wave = WaveFunc::new(vec![SigDef::new("x", "m")], vec![WaveFuncSetting::new(WaveFuncType::Sin, 1.0, -0.5, 1.0, 0.0)]).unwrap();
+        let clock = ClockFunc::new(vec![SigDef::new("t", "s")], 1.0, 0.0).unwrap();
+        let mut rising = EventRecorder::new(vec![SigDef::new("x", "m"), SigDef::new("t", "s")], "x", 0.0, TriggerEdge::Rising).unwrap();
+        let mut both = EventRecorder::new(vec![SigDef::new("x", "m")], "x", 0.0, TriggerEdge::Both).unwrap();
+        connect_models(&wave, &["x"], &mut rising, &["x"]).unwrap();
+        connect_models(&clock, &["t"], &mut rising, &["t"]).unwrap();
+        connect_models(&wave, &["x"], &mut both, &["x"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 3.0, 0.01);
+        sys.regist_model(wave);
+        sys.regist_model(clock);
+        sys.regist_event_recorder("rising", rising);
+        sys.regist_event_recorder("both", both);
+        sys.run();
+
+        let rising = sys.get_event_recorder("rising").unwrap();
+        assert_eq!(rising.event_count(), 3);
+        let offset = 0.5 / (2.0 * std::f64::consts::PI);
+        for (k, t) in rising.get_time().iter().enumerate() {
+            assert!((t - (k as f64 + offset)).abs() < 1e-4, "t = {}", t);
+        }
+        assert_eq!(rising.get_signal("t").unwrap(), rising.get_time()); // 値もイベント時刻で補間される
+        assert!(rising.get_signal("x").unwrap().iter().all(|x| x.abs() < 1e-12));
+
+        assert_eq!(sys.get_event_recorder("both").unwrap().event_count(), 6);
+        assert!(sys.get_event_recorder("none").is_err());
+        assert!(EventRecorder::new(vec![SigDef::new("x", "m")], "y", 0.0, TriggerEdge::Both).is_err());
+    }
 }
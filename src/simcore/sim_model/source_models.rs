@@ -3,23 +3,30 @@
 /// # Sourceモデル
 /// Sourceモデルには、下記のモデルを実装する
 /// 
-/// - 定数モデル
-/// - Step関数
+/// - 定数モデル（ソフトスタート対応）
+/// - Step関数（ソフトスタート対応）
 /// - Ramp関数
+/// - 目標値到達型Ramp関数
 /// - 三角波関数
 /// - 矩形波
-/// - Lookup（CSVファイル読み込み）　時間に足りない分の選択肢（0にするか、繰り返すか）　時間の間は線形補完
+/// - 時刻出力（クロック）
+/// - 多項式関数
+/// - Lookup（CSVファイル読み込み）　時間に足りない分の選択肢（保持するか、0にするか、繰り返すか）　時間の間は線形補完
+/// - ノイズ（一様白色ノイズ、正規白色ノイズ、PRBS）
 use anyhow::{anyhow, Context};
 use std::f64::consts::{PI};
+use std::rc::Rc;
 
 use crate::simcore::{sim_model, sim_signal, sim_system};
-use sim_model::model_core::{ModelCore};
+use sim_model::model_core::{ModelCore, sigdefs_source, values_source};
 
-use sim_signal::signal::{SigDef, SigTrait};
+use sim_signal::signal::SigDef;
 use sim_signal::bus::{Bus, RefBus};
 
 use sim_system::SimTime;
 
+use super::nonideal_models::NoiseGen;
+
 //　モデルを追加した時に実装するメソッド(ModelCoreトレイト)
 
 // /// 初期化処理
@@ -38,9 +45,13 @@ use sim_system::SimTime;
 // fn interface_out(&self) -> Option<&Bus>;
 
 /// 定数モデル
+/// set_soft_startでソフトスタートを設定すると、開始時刻から指定時間かけて0から設定値まで直線的に増加させる
 #[derive(Debug)]
 pub struct ConstantFunc {
     outbus: Bus,
+    values: Vec<f64>,
+    soft_start: f64, // ソフトスタートの時間[s]（0の時は無効）
+    start_time: f64, // シミュレーション開始時刻（initializeで設定）
 }
 
 impl ConstantFunc {
@@ -54,21 +65,57 @@ impl ConstantFunc {
 
         Ok(Self {
             outbus: outbus,
+            values: values.to_vec(),
+            soft_start: 0.0,
+            start_time: 0.0,
         })
     }
+
+    /// ソフトスタートの時間[s]を設定する（0で無効）
+    /// 開始直後に出力がステップ状に変化してプラントに衝撃を与えないようにする時に使用する
+    pub fn set_soft_start(&mut self, duration: f64) -> anyhow::Result<()> {
+        self.soft_start = check_soft_start(duration)?;
+        self.set_samplers();
+        if self.soft_start == 0.0 {
+            // 無効にした時はソフトスタート途中の出力を残さず設定値に戻す
+            self.outbus.iter_mut().zip(self.values.iter()).for_each(|(sig, &val)| sig.set_val(val));
+        }
+        Ok(())
+    }
+
+    /// ソフトスタート中は時刻の関数になるため、ソルバの中間段でも任意時刻の値を取得できるようにする
+    /// ソフトスタートが無効の時はサンプラを解除する（出力は固定値）
+    fn set_samplers(&mut self) {
+        let (start_time, soft_start) = (self.start_time, self.soft_start);
+        self.outbus.iter_mut().zip(self.values.iter()).for_each(|(sig, &val)| {
+            if soft_start > 0.0 {
+                sig.set_sampler(move |t| val * soft_start_gain(t, start_time, soft_start));
+            } else {
+                sig.clear_sampler();
+            }
+        });
+    }
 }
 
 impl ModelCore for ConstantFunc {
-    fn initialize(&mut self, _sim_time: &SimTime) {
-        
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.start_time = sim_time.start_time();
+        self.set_samplers();
+        let (start_time, soft_start) = (self.start_time, self.soft_start);
+        self.outbus.iter_mut().zip(self.values.iter()).for_each(|(sig, &val)| {
+            sig.set_val(val * soft_start_gain(start_time, start_time, soft_start));
+        });
     }
 
     fn finalize(&mut self) {
         // 処理なし
     }
 
-    fn nextstate(&mut self, _sim_time: &SimTime) {
-        // 処理なし
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        if self.soft_start > 0.0 {
+            let gain = soft_start_gain(sim_time.time(), self.start_time, self.soft_start);
+            self.outbus.iter_mut().zip(self.values.iter()).for_each(|(sig, val)| sig.set_val(val * gain));
+        }
     }
 
     fn interface_in(&mut self) -> Option<&mut RefBus> {
@@ -78,15 +125,45 @@ impl ModelCore for ConstantFunc {
     fn interface_out(&self) -> Option<&Bus> {
         Some(&self.outbus)
     }
+
+    fn to_source(&self) -> Option<String> {
+        let new = format!("ConstantFunc::new({}, {}).unwrap()", sigdefs_source(&self.outbus.get_sigdef()), values_source(self.values.iter()));
+        if self.soft_start > 0.0 {
+            Some(format!("{{\n    let mut model = {};\n    model.set_soft_start({:?}).unwrap();\n    model\n}}", new, self.soft_start))
+        } else {
+            Some(new)
+        }
+    }
+}
+
+/// ソフトスタートの時間の確認（負・NaNはエラー）
+fn check_soft_start(duration: f64) -> anyhow::Result<f64> {
+    if duration < 0.0 || duration.is_nan() {
+        return Err(anyhow!("ソフトスタートの時間は0以上で設定してください。duration = {}", duration));
+    }
+    Ok(duration)
+}
+
+/// 時刻tにおけるソフトスタートの係数
+/// 開始時刻からdurationかけて0から1まで直線的に増加し、以降は1。durationが0の時は常に1
+fn soft_start_gain(t: f64, start_time: f64, duration: f64) -> f64 {
+    if duration > 0.0 {
+        ((t - start_time) / duration).clamp(0.0, 1.0)
+    } else {
+        1.0
+    }
 }
 
 /// # STEP関数モデル
 /// 指定した時刻未満の時は初期値を出力し、指定時刻以降は最終値を出力する
 /// settingsには(init_value, final_value, step_time)で指定する
+/// set_soft_startでソフトスタートを設定すると、出力に開始時刻から指定時間かけて0から1まで増加する係数を掛ける
 #[derive(Debug)]
 pub struct StepFunc {
     outbus: Bus,
     settings: Vec<(f64, f64, f64)>,
+    soft_start: f64, // ソフトスタートの時間[s]（0の時は無効）
+    start_time: f64, // シミュレーション開始時刻（initializeで設定）
 }
 
 impl StepFunc {
@@ -101,17 +178,51 @@ impl StepFunc {
             return Err(anyhow!("outbusとsettingsの要素数は一致している必要があります。\noutbus.len = {}, settings.len = {} ", outbus.len(), settings.len()))
         }
 
-        Ok(Self {
+        let mut model = Self {
             outbus: outbus,
             settings: settings,
-        })
+            soft_start: 0.0,
+            start_time: 0.0,
+        };
+        model.set_samplers();
+
+        Ok(model)
+    }
+
+    /// ソフトスタートの時間[s]を設定する（0で無効）
+    /// 開始直後に出力がステップ状に変化してプラントに衝撃を与えないようにする時に使用する
+    pub fn set_soft_start(&mut self, duration: f64) -> anyhow::Result<()> {
+        self.soft_start = check_soft_start(duration)?;
+        self.set_samplers();
+        Ok(())
+    }
+
+    /// 時刻tにおける出力値
+    fn value_at(&self, set: &(f64, f64, f64), t: f64) -> f64 {
+        step_value(set, t) * soft_start_gain(t, self.start_time, self.soft_start)
+    }
+
+    /// ソルバの中間段でも任意時刻の値を取得できるようにする
+    fn set_samplers(&mut self) {
+        let (start_time, soft_start) = (self.start_time, self.soft_start);
+        self.outbus.iter_mut().zip(self.settings.iter()).for_each(|(sig, &set)| {
+            sig.set_sampler(move |t| step_value(&set, t) * soft_start_gain(t, start_time, soft_start));
+        });
     }
 }
 
+/// 時刻tにおけるSTEP関数の値
+fn step_value(set: &(f64, f64, f64), t: f64) -> f64 {
+    if t >= set.2 { set.1 } else { set.0 }
+}
+
 impl ModelCore for StepFunc {
-    fn initialize(&mut self, _sim_time: &SimTime) {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.start_time = sim_time.start_time();
+        self.set_samplers();
+        let gain = soft_start_gain(self.start_time, self.start_time, self.soft_start);
         self.outbus.iter_mut().enumerate().for_each(|(idx, sig)| {
-            sig.set_val(self.settings[idx].0)
+            sig.set_val(self.settings[idx].0 * gain)
         });
     }
 
@@ -120,12 +231,8 @@ impl ModelCore for StepFunc {
     }
 
     fn nextstate(&mut self, sim_time: &SimTime) {
-        self.outbus.iter_mut().enumerate().for_each(|(idx, sig)| {
-            let set = self.settings[idx];
-            if sim_time.time() >= set.2 {
-                sig.set_val(set.1);
-            }
-        });
+        let vals = self.settings.iter().map(|set| self.value_at(set, sim_time.time())).collect::<Vec<f64>>();
+        self.outbus.iter_mut().zip(vals).for_each(|(sig, val)| sig.set_val(val));
     }
 
     fn interface_in(&mut self) -> Option<&mut RefBus> {
@@ -139,6 +246,8 @@ impl ModelCore for StepFunc {
 
 /// Ramp関数モデル
 /// Ramp関数は初期値、増加開始時刻、増加の傾きによって決まる関数
+/// 時刻t（>= start_time）の出力は init + slope * (t - start_time)（上限が有効な場合はlimitで制限）で、ソルバの中間段のサンプリングと同じ値になる
+/// 例：init=0.5, start_time=0.2, slope=2.0, delta_t=0.1の時、t=0.2で0.5、t=0.3で0.7、t=0.4で0.9
 #[derive(Debug)]
 pub struct RampFunc {
     outbus: Bus, 
@@ -152,11 +261,16 @@ impl RampFunc {
     /// ## 注意事項
     /// Busの要素数とsettingsの要素数は等しい必要があります。
     pub fn new(output_def: Vec<SigDef>, settings: Vec<(f64, f64, bool, f64, f64)>) -> anyhow::Result<Self> {
-        let outbus = Bus::try_from(output_def).context(format!("RampFuncの出力バスが不正です。"))?;
+        let mut outbus = Bus::try_from(output_def).context(format!("RampFuncの出力バスが不正です。"))?;
         if outbus.len() != settings.len() {
             return Err(anyhow!("outbusとsettingsの要素数は一致している必要があります。\noutbus.len = {}, settings.len = {} ", outbus.len(), settings.len()))
         }
 
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus.iter_mut().zip(settings.iter()).for_each(|(sig, &set)| {
+            sig.set_sampler(move |t| ramp_value(&set, t));
+        });
+
         Ok(Self {
             outbus: outbus,
             settings: settings,
@@ -164,6 +278,24 @@ impl RampFunc {
     }
 }
 
+/// 時刻tにおけるRamp関数の値
+/// 刻み幅に依存しないよう、増加開始時刻からの経過時間で解析的に計算する
+fn ramp_value(set: &(f64, f64, bool, f64, f64), t: f64) -> f64 {
+    let (init, limit, limit_enable, start_time, slope) = *set;
+    if t < start_time {
+        return init;
+    }
+
+    let val = init + slope * (t - start_time);
+    if limit_enable && slope >= 0.0 { // 上限が有効であれば上限を設定する
+        val.min(limit)
+    } else if limit_enable {
+        val.max(limit)
+    } else {
+        val
+    }
+}
+
 impl ModelCore for RampFunc {
     fn initialize(&mut self, _sim_time: &SimTime) {
         self.outbus.iter_mut().enumerate().for_each(|(idx, sig)| {
@@ -177,17 +309,7 @@ impl ModelCore for RampFunc {
 
     fn nextstate(&mut self, sim_time: &SimTime) {
         self.outbus.iter_mut().enumerate().for_each(|(idx, sig)| {
-            let set = self.settings[idx];
-            let delta = set.4 * sim_time.delta_t(); // 1ステップ当たりの増加量
-            if sim_time.time() >= set.3 {
-                let mut val = sig.val() + delta;
-                if set.2 == true && delta >= 0.0 { // 上限が有効であれば上限を設定する
-                    val = val.min(set.1);
-                } else if set.2 == true && delta < 0.0 {
-                    val = val.max(set.1);
-                }
-                sig.set_val(val);
-            }
+            sig.set_val(ramp_value(&self.settings[idx], sim_time.time()));
         });
     }
 
@@ -200,18 +322,119 @@ impl ModelCore for RampFunc {
     }
 }
 
-/// # 波の関数の種類定義
+/// # 目標値到達型Ramp関数の設定用構造体
+/// 初期値から目標値まで一定の変化率で変化し、目標値で停止する
+#[derive(Debug, Clone)]
+pub struct RampToTargetSetting {
+    init: f64,       // 初期値
+    target: f64,     // 目標値
+    start_time: f64, // 変化開始時刻[s]
+    rate: f64,       // 変化率の大きさ[/s]（正の値、変化の向きは初期値と目標値から決まる）
+}
+
+impl RampToTargetSetting {
+    pub fn new(init: f64, target: f64, start_time: f64, rate: f64) -> Self {
+        Self {
+            init,
+            target,
+            start_time,
+            rate,
+        }
+    }
+
+    /// 目標値に到達する時刻[s]
+    pub fn arrival_time(&self) -> f64 {
+        self.start_time + (self.target - self.init).abs() / self.rate
+    }
+
+    /// 時刻timeにおける値
+    /// 到達時刻以降は目標値そのものを返すため、最後のステップで目標値を行き過ぎたり丸め誤差が残ったりしない
+    fn value_at(&self, time: f64) -> f64 {
+        if time <= self.start_time {
+            self.init
+        } else if time >= self.arrival_time() {
+            self.target
+        } else {
+            self.init + (self.target - self.init).signum() * self.rate * (time - self.start_time)
+        }
+    }
+}
+
+/// 目標値到達型Ramp関数モデル
+/// 位置指令など、最終値が目標値と厳密に一致する必要がある場合に使用する
 #[derive(Debug)]
+pub struct RampToTarget {
+    outbus: Bus,
+    settings: Vec<RampToTargetSetting>,
+}
+
+impl RampToTarget {
+    /// ## RampToTargetの引数定義
+    /// 1. 第1引数：Bus
+    /// 1. 第2引数：settings: Vec<RampToTargetSetting>
+    /// ## 注意事項
+    /// Busの要素数とsettingsの要素数は等しい必要があります。変化率は正の値で設定してください。
+    pub fn new(output_def: Vec<SigDef>, settings: Vec<RampToTargetSetting>) -> anyhow::Result<Self> {
+        let mut outbus = Bus::try_from(output_def).context("RampToTargetの出力バスが不正です。")?;
+        if outbus.len() != settings.len() {
+            return Err(anyhow!("outbusとsettingsの要素数は一致している必要があります。\noutbus.len = {}, settings.len = {} ", outbus.len(), settings.len()));
+        }
+        if let Some(set) = settings.iter().find(|set| set.rate <= 0.0 || set.rate.is_nan()) {
+            return Err(anyhow!("RampToTargetの変化率は正の値で設定してください。rate = {}", set.rate));
+        }
+
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus.iter_mut().zip(settings.iter()).for_each(|(sig, set)| {
+            let set = set.clone();
+            sig.set_sampler(move |t| set.value_at(t));
+        });
+
+        Ok(Self {
+            outbus,
+            settings,
+        })
+    }
+}
+
+impl ModelCore for RampToTarget {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.settings.iter()).for_each(|(sig, set)| {
+            sig.set_val(set.value_at(sim_time.start_time()));
+        });
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.settings.iter()).for_each(|(sig, set)| {
+            sig.set_val(set.value_at(sim_time.time()));
+        });
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        None
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+}
+
+/// # 波の関数の種類定義
+#[derive(Debug, Clone, Copy)]
 pub enum WaveFuncType {
     Sin,        // 正弦波
     Triangle,   // 三角波
     Square,     // 矩形波（Duty50の矩形波のみ）
+    Chirp { f_start: f64, f_end: f64, duration: f64 }, // 正弦波スイープ（周波数をdurationの間にf_start[Hz]からf_end[Hz]まで線形に変化させる。以降はf_endを保持。periodは使用しない）
 }
 
 /// # 波の関数の設定用構造体
 /// 振幅・位相・周期（周波数）・オフセットをメンバ持つ構造体。
 /// 正弦波、三角波、矩形波などの周期関数の設定で共通使用する
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct WaveFuncSetting {
     fn_type: WaveFuncType,
     amplitude: f64, // 振幅
@@ -220,6 +443,36 @@ pub struct WaveFuncSetting {
     offset: f64, // オフセット
 }
 
+impl WaveFuncSetting {
+    pub fn new(fn_type: WaveFuncType, amplitude: f64, phase: f64, period: f64, offset: f64) -> Self {
+        Self {
+            fn_type,
+            amplitude,
+            phase,
+            period,
+            offset,
+        }
+    }
+
+    /// 時刻timeにおける値
+    fn value_at(&self, time: f64) -> f64 {
+        self.amplitude * wave_func(self, self.angle_at(time)) + self.offset
+    }
+
+    /// 時刻timeにおける位相角[rad]
+    fn angle_at(&self, time: f64) -> f64 {
+        match self.fn_type {
+            WaveFuncType::Chirp { f_start, f_end, duration } => {
+                // 瞬時周波数 f(t) = f_start + (f_end - f_start) * t / duration を積分した位相
+                let t = time.min(duration);
+                let sweep = f_start * t + (f_end - f_start) / (2.0 * duration) * t * t;
+                2.0 * PI * (sweep + f_end * (time - t)) + self.phase
+            },
+            _ => 2.0 * time / self.period * PI + self.phase,
+        }
+    }
+}
+
 /// 正弦波関数モデル
 #[derive(Debug)]
 pub struct WaveFunc {
@@ -232,11 +485,26 @@ impl WaveFunc {
     /// 1. 第1引数：Bus
     /// 1. 第2引数：settings: Vec<WafeFuncSetting>
     pub fn new(output_def: Vec<SigDef>, settings: Vec<WaveFuncSetting>) -> anyhow::Result<Self> {
-        let outbus = Bus::try_from(output_def).context(format!("WaveFuncの出力バスが不正です。"))?;
+        let mut outbus = Bus::try_from(output_def).context(format!("WaveFuncの出力バスが不正です。"))?;
         if outbus.len() != settings.len() {
             return Err(anyhow!("outbusとsettingsの要素数は一致している必要があります。\noutbus.len = {}, settings.len = {} ", outbus.len(), settings.len()));
         }
 
+        for set in settings.iter() {
+            if let WaveFuncType::Chirp { f_start, f_end, duration } = set.fn_type {
+                let valid = duration > 0.0 && f_start >= 0.0 && f_end >= 0.0; // NaNも不正とする
+                if !valid {
+                    return Err(anyhow!("Chirpのdurationは正、f_start・f_endは0以上である必要があります。\nf_start = {}, f_end = {}, duration = {}", f_start, f_end, duration));
+                }
+            }
+        }
+
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus.iter_mut().zip(settings.iter()).for_each(|(sig, set)| {
+            let set = set.clone();
+            sig.set_sampler(move |t| set.value_at(t));
+        });
+
         Ok(Self{
             outbus: outbus,
             settings: settings,
@@ -248,7 +516,7 @@ impl WaveFunc {
 /// 振幅1 周期2πの波関数定義
 fn wave_func(set: &WaveFuncSetting, t: f64) -> f64 {
     match set.fn_type {
-        WaveFuncType::Sin => t.sin(),
+        WaveFuncType::Sin | WaveFuncType::Chirp { .. } => t.sin(),
         WaveFuncType::Triangle => {
             let t1 = t / (2.0 * PI); // 0～2πを0～1の範囲に正規化する
             let t2 = t1 - (t1 as i32) as f64; // 小数点以下のみを抽出する
@@ -290,12 +558,397 @@ impl ModelCore for WaveFunc {
 
     fn nextstate(&mut self, sim_time: &SimTime) {
         self.outbus.iter_mut().enumerate().for_each(|(idx, sig)| {
-            let set = &self.settings[idx];
-            let t = 2.0 * sim_time.time() / set.period * PI + set.phase;
-            let val = set.amplitude * wave_func(set, t) + set.offset;
-            sig.set_val(val);
+            sig.set_val(self.settings[idx].value_at(sim_time.time()));
+        })
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        None
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+}
+
+/// # クロックモデル
+/// シミュレーション時刻を出力する（出力 = scale * 時刻 + offset）
+#[derive(Debug)]
+pub struct ClockFunc {
+    outbus: Bus,
+    scale: f64,  // 時刻に掛けるゲイン
+    offset: f64, // オフセット
+}
+
+impl ClockFunc {
+    /// ## ClockFuncの引数定義
+    /// 1. 第1引数：Bus（要素数は1）
+    /// 1. 第2引数：scale: 時刻に掛けるゲイン（そのまま時刻を出力する場合は1.0）
+    /// 1. 第3引数：offset: オフセット
+    pub fn new(output_def: Vec<SigDef>, scale: f64, offset: f64) -> anyhow::Result<Self> {
+        let mut outbus = Bus::try_from(output_def).context("ClockFuncの出力バスが不正です。")?;
+        if outbus.len() != 1 {
+            return Err(anyhow!("ClockFunc: 出力信号の要素数は1個で設定してください"));
+        }
+
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus[0].set_sampler(move |t| scale * t + offset);
+
+        Ok(Self {
+            outbus,
+            scale,
+            offset,
+        })
+    }
+}
+
+impl ModelCore for ClockFunc {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.outbus[0].set_val(self.scale * sim_time.start_time() + self.offset);
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        self.outbus[0].set_val(self.scale * sim_time.time() + self.offset);
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        None
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+}
+
+/// 時刻tにおける多項式の値（係数は0次から、ホーナー法で計算する）
+fn polynomial_value(coef: &[f64], t: f64) -> f64 {
+    coef.iter().rev().fold(0.0, |acc, c| acc * t + c)
+}
+
+/// # 多項式関数モデル
+/// 信号ごとに sum(c_i * t^i) を出力する（定数・ランプ・放物線などの軌道指令を表現できる）
+#[derive(Debug)]
+pub struct PolynomialFunc {
+    outbus: Bus,
+    coefs: Vec<Vec<f64>>, // 信号ごとの係数 [c_0, c_1, c_2, ...]
+}
+
+impl PolynomialFunc {
+    /// ## PolynomialFuncの引数定義
+    /// 1. 第1引数：Bus
+    /// 1. 第2引数：coefs: 信号ごとの係数 Vec<[c_0, c_1, ..., c_n]>（0次から順に指定する）
+    /// ## 注意事項
+    /// Busの要素数とcoefsの要素数は等しい必要があります。
+    pub fn new(output_def: Vec<SigDef>, coefs: Vec<Vec<f64>>) -> anyhow::Result<Self> {
+        let mut outbus = Bus::try_from(output_def).context("PolynomialFuncの出力バスが不正です。")?;
+        if outbus.len() != coefs.len() {
+            return Err(anyhow!("outbusとcoefsの要素数は一致している必要があります。\noutbus.len = {}, coefs.len = {} ", outbus.len(), coefs.len()));
+        }
+
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus.iter_mut().zip(coefs.iter()).for_each(|(sig, coef)| {
+            let coef = coef.clone();
+            sig.set_sampler(move |t| polynomial_value(&coef, t));
+        });
+
+        Ok(Self {
+            outbus,
+            coefs,
+        })
+    }
+}
+
+impl ModelCore for PolynomialFunc {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.coefs.iter()).for_each(|(sig, coef)| {
+            sig.set_val(polynomial_value(coef, sim_time.start_time()));
+        });
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.coefs.iter()).for_each(|(sig, coef)| {
+            sig.set_val(polynomial_value(coef, sim_time.time()));
+        });
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        None
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+}
+
+/// Lookupの範囲外（最後のデータの時刻より後）の扱い
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum OutOfRangeMode {
+    Hold,   // 最後の値を保持する
+    Zero,   // 0を出力する
+    Repeat, // 先頭から繰り返す（周期は最初と最後のデータの時刻の差）
+}
+
+/// Lookupのデータ表（時刻と信号ごとの値）
+#[derive(Debug)]
+struct LookupTable {
+    times: Vec<f64>,       // 時刻（昇順）
+    values: Vec<Vec<f64>>, // 信号ごとの値
+    mode: OutOfRangeMode,
+}
+
+impl LookupTable {
+    /// 時刻tにおけるcol番目の信号の値（データ点の間は線形補間する）
+    /// 最初のデータの時刻より前は最初の値を出力する
+    fn value_at(&self, col: usize, t: f64) -> f64 {
+        let (t_first, t_last) = (self.times[0], self.times[self.times.len() - 1]);
+        let vals = &self.values[col];
+
+        let t = if t > t_last {
+            match self.mode {
+                OutOfRangeMode::Hold => return vals[vals.len() - 1],
+                OutOfRangeMode::Zero => return 0.0,
+                OutOfRangeMode::Repeat if t_last > t_first => t_first + (t - t_first).rem_euclid(t_last - t_first),
+                OutOfRangeMode::Repeat => return vals[vals.len() - 1],
+            }
+        } else {
+            t
+        };
+
+        if t <= t_first {
+            return vals[0];
+        }
+        let idx = self.times.partition_point(|x| *x <= t).min(self.times.len() - 1); // times[idx - 1] <= t < times[idx]
+        let (t0, t1) = (self.times[idx - 1], self.times[idx]);
+        if t1 <= t0 {
+            return vals[idx];
+        }
+        vals[idx - 1] + (vals[idx] - vals[idx - 1]) * (t - t0) / (t1 - t0)
+    }
+}
+
+/// # Lookupモデル
+/// CSVファイルから読み込んだ時系列データを出力する。データ点の間は線形補間する
+/// CSVは1列目が時刻[s]（昇順）、2列目以降が出力バスの各信号の値とする。
+/// 最初の行が数値でない場合はヘッダとして、#で始まる行はコメントとして読み飛ばす（SimRecorder::exportで出力したファイルをそのまま使用できる）
+#[derive(Debug)]
+pub struct LookupFunc {
+    outbus: Bus,
+    table: Rc<LookupTable>,
+}
+
+impl LookupFunc {
+    /// ## LookupFuncの引数定義
+    /// 1. 第1引数：Bus
+    /// 1. 第2引数：csv_path: CSVファイルのパス
+    /// 1. 第3引数：out_of_range: 最後のデータの時刻より後の扱い
+    /// ## 注意事項
+    /// CSVの列数は 出力バスの要素数 + 1（時刻の列）である必要があります。
+    pub fn new(output_def: Vec<SigDef>, csv_path: &str, out_of_range: OutOfRangeMode) -> anyhow::Result<Self> {
+        let text = std::fs::read_to_string(csv_path).context(format!("LookupFunc: CSVファイルを読み込めません。path = {}", csv_path))?;
+        Self::from_csv_str(output_def, &text, out_of_range).context(format!("LookupFunc: CSVファイルが不正です。path = {}", csv_path))
+    }
+
+    /// CSV形式の文字列からLookupFuncを作成する
+    pub fn from_csv_str(output_def: Vec<SigDef>, text: &str, out_of_range: OutOfRangeMode) -> anyhow::Result<Self> {
+        let mut outbus = Bus::try_from(output_def).context("LookupFuncの出力バスが不正です。")?;
+        let colnum = outbus.len() + 1;
+
+        let mut times = Vec::new();
+        let mut values = vec![Vec::new(); outbus.len()];
+        let mut header = false;
+        // 空行と#で始まるコメント行（SimRecorder::exportのメタデータなど）は読み飛ばす
+        for (lineno, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty() && !l.trim_start().starts_with('#')) {
+            let fields = line.split(',').map(|x| x.trim().parse::<f64>()).collect::<Result<Vec<f64>, _>>();
+            let fields = match fields {
+                Ok(f) => f,
+                Err(_) if times.is_empty() && !header => { header = true; continue }, // ヘッダ行
+                Err(_) => return Err(anyhow!("{}行目に数値でない値があります。: {}", lineno + 1, line)),
+            };
+            if fields.len() != colnum {
+                return Err(anyhow!("{}行目の列数が出力バスと一致しません。列数は 出力バスの要素数 + 1（時刻） = {} である必要があります。列数:{}", lineno + 1, colnum, fields.len()));
+            }
+            if times.last().is_some_and(|t| fields[0] < *t) {
+                return Err(anyhow!("{}行目の時刻が前の行より小さくなっています。時刻は昇順で記載してください。", lineno + 1));
+            }
+
+            times.push(fields[0]);
+            values.iter_mut().zip(fields[1..].iter()).for_each(|(col, v)| col.push(*v));
+        }
+
+        if times.is_empty() {
+            return Err(anyhow!("データがありません。"));
+        }
+
+        let table = Rc::new(LookupTable { times, values, mode: out_of_range });
+
+        // ソルバの中間段でも任意時刻の値を取得できるようにする
+        outbus.iter_mut().enumerate().for_each(|(col, sig)| {
+            let table = Rc::clone(&table);
+            sig.set_sampler(move |t| table.value_at(col, t));
+        });
+
+        Ok(Self {
+            outbus,
+            table,
         })
     }
+}
+
+impl ModelCore for LookupFunc {
+    fn initialize(&mut self, sim_time: &SimTime) {
+        let table = &self.table;
+        self.outbus.iter_mut().enumerate().for_each(|(col, sig)| sig.set_val(table.value_at(col, sim_time.start_time())));
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, sim_time: &SimTime) {
+        let table = &self.table;
+        self.outbus.iter_mut().enumerate().for_each(|(col, sig)| sig.set_val(table.value_at(col, sim_time.time())));
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        None
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+}
+
+/// # ノイズの種類定義
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NoiseType {
+    WhiteUniform,  // 一様白色ノイズ（-amplitude～amplitude）
+    WhiteGaussian, // 正規白色ノイズ（平均0、標準偏差amplitude）
+    PRBS,          // M系列の擬似ランダム2値信号（±amplitude）
+}
+
+/// # ノイズの設定用構造体
+/// 種類・振幅・シードをメンバに持つ。シードが同じであれば同じ系列を出力する
+#[derive(Debug, Clone)]
+pub struct NoiseSetting {
+    noise_type: NoiseType,
+    amplitude: f64, // 振幅（WhiteGaussianの場合は標準偏差）
+    seed: u64,      // 乱数のシード
+}
+
+impl NoiseSetting {
+    pub fn new(noise_type: NoiseType, amplitude: f64, seed: u64) -> Self {
+        Self {
+            noise_type,
+            amplitude,
+            seed,
+        }
+    }
+}
+
+/// PRBSの初期レジスタ値（シードの下位16bit、0の場合はレジスタが0に張り付くため避ける）
+fn prbs_init(seed: u64) -> u16 {
+    match seed as u16 {
+        0 => 0xACE1,
+        reg => reg,
+    }
+}
+
+/// 1チャンネル分のノイズ生成器
+#[derive(Debug, Clone)]
+struct NoiseChannel {
+    setting: NoiseSetting,
+    gen: NoiseGen,
+    lfsr: u16, // PRBS用の線形帰還シフトレジスタ
+}
+
+impl NoiseChannel {
+    fn new(setting: NoiseSetting) -> Self {
+        Self {
+            gen: NoiseGen::new(setting.seed),
+            lfsr: prbs_init(setting.seed),
+            setting,
+        }
+    }
+
+    /// 系列をシードの状態に戻す
+    fn reset(&mut self) {
+        self.gen.reset();
+        self.lfsr = prbs_init(self.setting.seed);
+    }
+
+    /// 次の値を生成する
+    fn sample(&mut self) -> f64 {
+        let amp = self.setting.amplitude;
+        match self.setting.noise_type {
+            NoiseType::WhiteUniform => amp * (2.0 * self.gen.uniform() - 1.0),
+            NoiseType::WhiteGaussian => self.gen.gaussian(amp),
+            NoiseType::PRBS => {
+                // 16bitガロア型LFSR（x^16 + x^14 + x^13 + x^11 + 1 周期65535のM系列）
+                let bit = self.lfsr & 1;
+                self.lfsr >>= 1;
+                if bit == 1 {
+                    self.lfsr ^= 0xB400;
+                }
+                if bit == 1 { amp } else { -amp }
+            },
+        }
+    }
+}
+
+/// # ノイズモデル
+/// システム同定の加振信号などに使用する擬似ランダム信号を出力する
+/// ステップ毎に新しい値を出力する。initialize時にシードの状態に戻すため、実行毎に同じ系列となる
+#[derive(Debug)]
+pub struct NoiseFunc {
+    outbus: Bus,
+    channels: Vec<NoiseChannel>,
+}
+
+impl NoiseFunc {
+    /// ## NoiseFuncの引数定義
+    /// 1. 第1引数：Bus
+    /// 1. 第2引数：settings: Vec<NoiseSetting>
+    /// ## 注意事項
+    /// Busの要素数とsettingsの要素数は等しい必要があります。振幅は0以上で設定してください。
+    pub fn new(output_def: Vec<SigDef>, settings: Vec<NoiseSetting>) -> anyhow::Result<Self> {
+        let outbus = Bus::try_from(output_def).context("NoiseFuncの出力バスが不正です。")?;
+        if outbus.len() != settings.len() {
+            return Err(anyhow!("outbusとsettingsの要素数は一致している必要があります。\noutbus.len = {}, settings.len = {} ", outbus.len(), settings.len()));
+        }
+        if let Some(set) = settings.iter().find(|set| set.amplitude < 0.0 || set.amplitude.is_nan()) {
+            return Err(anyhow!("NoiseFuncの振幅は0以上で設定してください。amplitude = {}", set.amplitude));
+        }
+
+        Ok(Self {
+            outbus,
+            channels: settings.into_iter().map(NoiseChannel::new).collect(),
+        })
+    }
+}
+
+impl ModelCore for NoiseFunc {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.channels.iter_mut()).for_each(|(sig, ch)| {
+            ch.reset();
+            sig.set_val(ch.sample());
+        });
+    }
+
+    fn finalize(&mut self) {
+        // 処理なし
+    }
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        self.outbus.iter_mut().zip(self.channels.iter_mut()).for_each(|(sig, ch)| sig.set_val(ch.sample()));
+    }
 
     fn interface_in(&mut self) -> Option<&mut RefBus> {
         None
@@ -313,7 +966,7 @@ mod source_model_test {
     use crate::simcore::sim_model::{sink_models::SimRecorder, model_core::connect_models};
     use crate::simcore::sim_system::SimSystem;
     use crate::simcore::sim_common::UnitTrans;
-    use sim_signal::signal::{SigDef};
+    use sim_signal::signal::{SigDef, SigTrait};
 
     #[test]
     fn const_test() {
@@ -341,6 +994,183 @@ mod source_model_test {
         ).unwrap();
     }
 
+    #[test]
+    fn soft_start_test() {
+        let mut con = ConstantFunc::new(vec![SigDef::new("con", "-")], &[2.0]).unwrap();
+        con.set_soft_start(0.4).unwrap();
+        let mut sf = StepFunc::new(vec![SigDef::new("sf", "-")], vec![(1.0, 3.0, 0.5)]).unwrap();
+        sf.set_soft_start(0.4).unwrap();
+
+        let mut sim_time = SimTime::new(1.0, 2.0, 0.1);
+        con.initialize(&sim_time);
+        sf.initialize(&sim_time);
+        assert_eq!(con.interface_out().unwrap()[0].val(), 0.0); // 開始時刻は0から始まる
+        assert_eq!(sf.interface_out().unwrap()[0].val(), 0.0);
+
+        sim_time.next();
+        con.nextstate(&sim_time);
+        sf.nextstate(&sim_time);
+        assert!((con.interface_out().unwrap()[0].val() - 0.5).abs() < 1e-12);
+        assert!((sf.interface_out().unwrap()[0].val() - 3.0 * 0.25).abs() < 1e-12); // 開始時刻基準でステップ後
+        assert!((con.interface_out().unwrap()[0].val_at(1.2) - 1.0).abs() < 1e-12);
+        assert!((con.eval_output_at(1.3).unwrap()[0].val() - 1.5).abs() < 1e-12);
+
+        // ソフトスタート期間後は設定値そのもの
+        assert_eq!(con.eval_output_at(1.5).unwrap()[0].val(), 2.0);
+        assert_eq!(sf.eval_output_at(1.9).unwrap()[0].val(), 3.0);
+
+        assert!(con.set_soft_start(-1.0).is_err());
+        assert!(sf.set_soft_start(f64::NAN).is_err());
+
+        // ソフトスタートを無効にすると、途中の出力・サンプラを残さず設定値に戻る
+        con.set_soft_start(0.0).unwrap();
+        assert_eq!(con.interface_out().unwrap()[0].val(), 2.0);
+        assert_eq!(con.interface_out().unwrap()[0].val_at(1.2), 2.0);
+        assert!(con.eval_output_at(1.2).is_none());
+
+        con.initialize(&sim_time);
+        sim_time.next();
+        con.nextstate(&sim_time);
+        assert_eq!(con.interface_out().unwrap()[0].val(), 2.0);
+        assert!(con.eval_output_at(1.3).is_none());
+    }
+
+    #[test]
+    fn polynomial_func_test() {
+        let mut pf = PolynomialFunc::new(
+            vec![SigDef::new("p1", "m"), SigDef::new("p2", "m"), SigDef::new("p3", "m")],
+            vec![vec![2.0], vec![1.0, -0.5, 3.0], vec![]],
+        ).unwrap();
+        let mut sim_time = SimTime::new(1.0, 2.0, 0.5);
+
+        pf.initialize(&sim_time);
+        assert_eq!(pf.interface_out().unwrap()[1].val(), 3.5);
+
+        sim_time.next();
+        pf.nextstate(&sim_time);
+        let out = pf.interface_out().unwrap();
+        assert_eq!(out[0].val(), 2.0);
+        assert_eq!(out[1].val(), 1.0 - 0.75 + 6.75);
+        assert_eq!(out[2].val(), 0.0);
+        assert_eq!(pf.eval_output_at(2.0).unwrap()[1].val(), 12.0);
+
+        assert!(PolynomialFunc::new(vec![SigDef::new("p1", "m")], vec![]).is_err());
+    }
+
+    #[test]
+    fn ramp_to_target_test() {
+        // 0.3[/s]で0.0→1.0（到達時刻 3.333...[s]は刻み幅0.5[s]の途中）、2.0→-1.0
+        let mut rt = RampToTarget::new(
+            vec![SigDef::new("pos", "m"), SigDef::new("neg", "m")],
+            vec![RampToTargetSetting::new(0.0, 1.0, 0.0, 0.3), RampToTargetSetting::new(2.0, -1.0, 1.0, 1.0)],
+        ).unwrap();
+        let mut sim_time = SimTime::new(0.0, 5.0, 0.5);
+        rt.initialize(&sim_time);
+        assert_eq!(rt.interface_out().unwrap()[0].val(), 0.0);
+        assert_eq!(rt.interface_out().unwrap()[1].val(), 2.0);
+
+        let mut out = Vec::new();
+        while sim_time.next().is_some() {
+            rt.nextstate(&sim_time);
+            let bus = rt.interface_out().unwrap();
+            out.push((sim_time.time(), bus[0].val(), bus[1].val()));
+        }
+        for (t, pos, neg) in out.iter() {
+            assert!(*pos <= 1.0 && *neg >= -1.0); // 目標値を行き過ぎない
+            if *t >= 10.0 / 3.0 {
+                assert_eq!(*pos, 1.0); // 到達後は目標値と厳密に一致
+            } else {
+                assert!((pos - 0.3 * t).abs() < 1e-12);
+            }
+        }
+        assert_eq!(out[7].2, -1.0); // t = 4.0[s]
+        assert!((out[2].2 - 1.5).abs() < 1e-12); // t = 1.5[s]
+        assert_eq!(rt.eval_output_at(3.4).unwrap()[0].val(), 1.0);
+
+        assert!(RampToTarget::new(vec![SigDef::new("a", "-")], vec![RampToTargetSetting::new(0.0, 1.0, 0.0, 0.0)]).is_err());
+        assert!(RampToTarget::new(vec![SigDef::new("a", "-")], vec![]).is_err());
+    }
+
+    #[test]
+    fn clock_func_test() {
+        let mut clk = ClockFunc::new(vec![SigDef::new("time", "ms")], 1000.0, 5.0).unwrap();
+        let mut sim_time = SimTime::new(1.0, 2.0, 0.5);
+
+        clk.initialize(&sim_time);
+        assert_eq!(clk.interface_out().unwrap()[0].val(), 1005.0);
+
+        sim_time.next();
+        clk.nextstate(&sim_time);
+        assert_eq!(clk.interface_out().unwrap()[0].val(), 1505.0);
+
+        assert!(ClockFunc::new(vec![SigDef::new("t1", "s"), SigDef::new("t2", "s")], 1.0, 0.0).is_err());
+    }
+
+    #[test]
+    fn lookup_func_test() {
+        // 0～2[s]で 0 → 2 → 0 と変化する三角形と、ステップ状のデータ
+        let filepath = "test_output\\lookup.csv";
+        std::fs::write(filepath, "time[s],a[-],b[-]\n0.0,0.0,1.0\n1.0,2.0,1.0\n2.0,0.0,3.0\n").unwrap();
+        let defs = || vec![SigDef::new("a", "-"), SigDef::new("b", "-")];
+
+        let mut lf = LookupFunc::new(defs(), filepath, OutOfRangeMode::Repeat).unwrap();
+        let mut sim_time = SimTime::new(0.0, 3.0, 0.25);
+        lf.initialize(&sim_time);
+        assert_eq!(lf.interface_out().unwrap()[0].val(), 0.0);
+        sim_time.next();
+        sim_time.next();
+        lf.nextstate(&sim_time);
+        assert_eq!(lf.interface_out().unwrap()[0].val(), 1.0); // t = 0.5 の中間値
+        assert_eq!(lf.interface_out().unwrap()[1].val(), 1.0);
+
+        let at = |lf: &LookupFunc, t: f64| lf.eval_output_at(t).unwrap().to_vec_f64();
+        assert_eq!(at(&lf, 1.5), vec![1.0, 2.0]);
+        assert_eq!(at(&lf, 2.5), at(&lf, 0.5)); // 先頭から繰り返す
+        assert_eq!(at(&lf, 5.0), at(&lf, 1.0));
+        assert_eq!(lf.interface_out().unwrap()[0].val_at(3.5), 1.0);
+
+        let hold = LookupFunc::new(defs(), filepath, OutOfRangeMode::Hold).unwrap();
+        assert_eq!(at(&hold, 2.5), vec![0.0, 3.0]);
+        let zero = LookupFunc::new(defs(), filepath, OutOfRangeMode::Zero).unwrap();
+        assert_eq!(at(&zero, 2.5), vec![0.0, 0.0]);
+        assert_eq!(at(&zero, 2.0), vec![0.0, 3.0]);
+
+        // 列数・時刻の順序の不正
+        assert!(LookupFunc::new(vec![SigDef::new("a", "-")], filepath, OutOfRangeMode::Hold).is_err());
+        assert!(LookupFunc::from_csv_str(vec![SigDef::new("a", "-")], "0.0,1.0\n1.0,x\n", OutOfRangeMode::Hold).is_err());
+        assert!(LookupFunc::from_csv_str(vec![SigDef::new("a", "-")], "1.0,1.0\n0.0,2.0\n", OutOfRangeMode::Hold).is_err());
+        assert!(LookupFunc::from_csv_str(vec![SigDef::new("a", "-")], "time,a\n", OutOfRangeMode::Hold).is_err());
+        assert!(LookupFunc::new(defs(), "test_output\\not_exist.csv", OutOfRangeMode::Hold).is_err());
+    }
+
+    #[test]
+    fn eval_output_at_test() {
+        let mut rf = RampFunc::new(vec![SigDef::new("rf", "-")], vec![(0.5, 1.5, true, 0.2, 2.0)]).unwrap();
+        let sf = StepFunc::new(vec![SigDef::new("sf", "-")], vec![(0.0, 1.0, 0.5)]).unwrap();
+        let wf = WaveFunc::new(vec![SigDef::new("wf", "-")], vec![WaveFuncSetting::new(WaveFuncType::Sin, 2.0, 0.0, 1.0, 1.0)]).unwrap();
+        let con = ConstantFunc::new(vec![SigDef::new("con", "-")], &[3.0]).unwrap();
+
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        rf.initialize(&sim_time);
+        for _ in 0..4 {
+            sim_time.next();
+            rf.nextstate(&sim_time);
+        }
+
+        // 出力は計算されるが、モデルの出力（状態）は変化しない
+        let out = rf.eval_output_at(0.45).unwrap();
+        assert!((out[0].val() - 1.0).abs() < 1e-12);
+        assert_eq!(out[0].name(), "rf");
+        assert!((rf.interface_out().unwrap()[0].val() - 0.9).abs() < 1e-12);
+        assert_eq!(rf.eval_output_at(2.0).unwrap()[0].val(), 1.5); // 上限値
+
+        assert_eq!(sf.eval_output_at(0.4).unwrap()[0].val(), 0.0);
+        assert_eq!(sf.eval_output_at(0.5).unwrap()[0].val(), 1.0);
+        assert!((wf.eval_output_at(0.25).unwrap()[0].val() - 3.0).abs() < 1e-12);
+
+        assert!(con.eval_output_at(0.5).is_none()); // 時刻の関数でないモデルは固定値を使う
+    }
+
     #[test]
     fn step_func_test() {
         let sf = StepFunc::new(
@@ -396,6 +1226,29 @@ mod source_model_test {
         ).unwrap();
     }
 
+    #[test]
+    fn ramp_func_analytic_test() {
+        // 出力は増加開始時刻からの経過時間で決まる（増加開始時刻のステップでは初期値のまま）
+        let mut rf = RampFunc::new(vec![SigDef::new("up", "-"), SigDef::new("down", "-")], vec![(0.5, 1.5, true, 0.2, 2.0), (-0.5, -1.0, true, 0.2, -2.0)]).unwrap();
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        rf.initialize(&sim_time);
+
+        let mut outputs = vec![rf.interface_out().unwrap().to_vec_f64()];
+        while sim_time.next().is_some() {
+            rf.nextstate(&sim_time);
+            outputs.push(rf.interface_out().unwrap().to_vec_f64());
+        }
+
+        let expected = [
+            [0.5, -0.5], [0.5, -0.5], [0.5, -0.5], [0.7, -0.7], [0.9, -0.9], [1.1, -1.0],
+            [1.3, -1.0], [1.5, -1.0], [1.5, -1.0], [1.5, -1.0], [1.5, -1.0],
+        ];
+        assert_eq!(outputs.len(), expected.len());
+        for (out, exp) in outputs.iter().zip(expected.iter()) {
+            assert!((out[0] - exp[0]).abs() < 1e-12 && (out[1] - exp[1]).abs() < 1e-12, "{:?} != {:?}", out, exp);
+        }
+    }
+
     #[test]
     fn ramp_func_test() {
         let sf = RampFunc::new(
@@ -547,6 +1400,91 @@ mod source_model_test {
 
     }
 
+    #[test]
+    fn chirp_test() {
+        let (f_start, f_end, duration) = (1.0, 2.0, 20.0);
+        let chirp = WaveFunc::new(
+            vec![SigDef::new("chirp", "-")],
+            vec![WaveFuncSetting::new(WaveFuncType::Chirp { f_start, f_end, duration }, 2.0, 0.0, 0.0, 1.0)],
+        ).unwrap();
+        let mut scp = SimRecorder::new(vec![SigDef::new("chirp", "-")]).unwrap();
+        connect_models(&chirp, &["chirp"], &mut scp, &["chirp"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, duration, 0.001);
+        sys.regist_model(chirp);
+        sys.regist_recorder("scp", scp);
+        sys.run();
+
+        // オフセットを中心とした上向きのゼロクロス時刻（線形補間）
+        let scp = sys.get_recorder("scp").unwrap();
+        let (time, val) = (scp.get_time(), scp.get_signal("chirp").unwrap());
+        let crossings = (1..time.len())
+            .filter(|&i| val[i - 1] < 1.0 && val[i] >= 1.0)
+            .map(|i| time[i - 1] + (time[i] - time[i - 1]) * (1.0 - val[i - 1]) / (val[i] - val[i - 1]))
+            .collect::<Vec<f64>>();
+
+        // 開始直後の周期は1/f_start、終了直前の周期は1/f_endに一致する
+        let first_period = crossings[0]; // 時刻0は位相0から上向きに立ち上がる
+        let last_period = crossings[crossings.len() - 1] - crossings[crossings.len() - 2];
+        assert!((first_period - 1.0 / f_start).abs() < 0.05 / f_start, "{}", first_period);
+        assert!((last_period - 1.0 / f_end).abs() < 0.05 / f_end, "{}", last_period);
+
+        assert!(WaveFunc::new(
+            vec![SigDef::new("chirp", "-")],
+            vec![WaveFuncSetting::new(WaveFuncType::Chirp { f_start, f_end, duration: 0.0 }, 1.0, 0.0, 0.0, 0.0)],
+        ).is_err());
+    }
+
+    #[test]
+    fn noise_func_test() {
+        let settings = vec![
+            NoiseSetting::new(NoiseType::WhiteGaussian, 1.0, 42),
+            NoiseSetting::new(NoiseType::WhiteGaussian, 1.0, 42),
+            NoiseSetting::new(NoiseType::WhiteUniform, 0.5, 7),
+            NoiseSetting::new(NoiseType::PRBS, 2.0, 1),
+            NoiseSetting::new(NoiseType::PRBS, 2.0, 1),
+        ];
+        let names = ["g1", "g2", "u", "p1", "p2"];
+        let mut noise = NoiseFunc::new(names.iter().map(|name| SigDef::new(*name, "-")).collect(), settings.clone()).unwrap();
+
+        let run = |noise: &mut NoiseFunc| {
+            let mut sim_time = SimTime::new(0.0, 1.25, 1.0 / 65536.0);
+            noise.initialize(&sim_time);
+            let mut out = vec![Vec::new(); names.len()];
+            while sim_time.next().is_some() {
+                noise.nextstate(&sim_time);
+                noise.interface_out().unwrap().iter().enumerate().for_each(|(idx, sig)| out[idx].push(sig.val()));
+            }
+            out
+        };
+        let out = run(&mut noise);
+
+        // 同じシードのチャンネルはビット単位で同じ系列
+        assert!(out[0].iter().zip(out[1].iter()).all(|(a, b)| a.to_bits() == b.to_bits()));
+        assert!(out[3].iter().zip(out[4].iter()).all(|(a, b)| a.to_bits() == b.to_bits()));
+
+        // 正規白色ノイズの平均はほぼ0
+        let n = out[0].len() as f64;
+        let mean = out[0].iter().sum::<f64>() / n;
+        let var = out[0].iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+        assert!(mean.abs() < 0.02, "{}", mean);
+        assert!((var - 1.0).abs() < 0.05, "{}", var);
+
+        assert!(out[2].iter().all(|v| v.abs() <= 0.5));
+
+        // PRBSは±amplitudeの2値で、1周期（65535ステップ）の中で+が1つ多い
+        assert!(out[3].iter().all(|v| *v == 2.0 || *v == -2.0));
+        let plus = out[3][..65535].iter().filter(|v| **v > 0.0).count();
+        assert_eq!(plus, 32768);
+        assert_eq!(out[3][..16], out[3][65535..65535 + 16]);
+
+        // 再実行しても同じ系列
+        assert_eq!(run(&mut noise), out);
+
+        assert!(NoiseFunc::new(vec![SigDef::new("n", "-")], vec![NoiseSetting::new(NoiseType::PRBS, -1.0, 1)]).is_err());
+        assert!(NoiseFunc::new(vec![SigDef::new("n", "-")], settings).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn sin_func_panic_test() {
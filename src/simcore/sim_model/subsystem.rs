@@ -3,18 +3,24 @@
 /// # SubSystemモデル
 /// - いくつかのモデルをひとまとまりにしてサブモデルとして定義
 /// - サブモデルはSimSystemに他のモデル同様に登録することができる
-/// - サブモデルは内部はSimSystemで設定しているΔtよりも短いΔtを持つことができる
+/// - サブモデルは内部はSimSystemで設定しているΔtよりも短いΔtを持つことができる（SimSystemの1ステップを分割して内部のモデルを複数回進める）
+/// - 内部の信号を記録用インターフェースに公開し、親のSimSystemのレコーダで記録することができる
 /// 
 use anyhow::{anyhow, Context};
 
 use crate::simcore::{sim_model, sim_signal, sim_system};
 
 use sim_model::model_core::{ModelCore};
-use sim_signal::signal::SigDef;
+use sim_signal::signal::{SigDef, SigTrait, Signal};
 use sim_signal::bus::{Bus, RefBus};
 
+use std::rc::Rc;
+
 use sim_system::SimTime;
 
+/// substep_num()で整数とみなす相対誤差
+const SUBSTEP_NUM_EPS: f64 = 1e-9;
+
 /// サブシステムモデル
 pub struct SubSystem<'a> {
     inbus: RefBus, // 入力バス
@@ -22,7 +28,8 @@ pub struct SubSystem<'a> {
     outbus: Bus, // 出力バス
     outbus_buf: RefBus, // 出力バスのバッファ 内部モデルの出力をここで参照し、outbusにコピーして出力する
     models: Vec<Box<dyn ModelCore + 'a>>, // 個々のモデルを管理するHashMap
-    delta_t: f64, // 時間刻み
+    delta_t: f64, // 内部の時間刻み（SimSystemの刻み幅より短い場合は1ステップを分割して進める）
+    recording_bus: Bus, // 記録用インターフェース（expose_for_recordingで公開した内部の信号を共有する）
 }
 
 impl<'a> SubSystem<'a> {
@@ -39,7 +46,8 @@ impl<'a> SubSystem<'a> {
             outbus: outbus,
             outbus_buf: RefBus::try_from(outbus_def).unwrap(),
             models: Vec::<Box<dyn ModelCore>>::new(),
-            delta_t: delta_t
+            delta_t: delta_t,
+            recording_bus: Bus::new(),
         })
     }
 
@@ -50,6 +58,36 @@ impl<'a> SubSystem<'a> {
         self.models.push(Box::new(model));
     }
 
+    /// SubSystem境界で入力信号の名前を付け替える
+    /// namelist: [(外部の信号名, 内部の信号名)]
+    /// 内部モデルはconnect_inbusで内部の信号名を指定して接続する
+    /// 内部モデルを接続する前に呼び出す必要がある
+    pub fn rename_inbus(&mut self, namelist: &[(&str, &str)]) -> anyhow::Result<()> {
+        if self.inbus_buf.iter().any(|sig| Rc::strong_count(sig.sig()) > 1) {
+            return Err(anyhow!("SubSystem: 内部モデルが既に入力バスに接続されているため、信号名を付け替えることができません。"));
+        }
+
+        let sigdef = rename_sigdef(&self.inbus.get_sigdef(), self.inbus_buf.get_sigdef(), namelist).context("SubSystem: 入力信号名の付け替えに失敗しました。")?;
+        self.inbus_buf = Bus::try_from(sigdef).context("SubSystem: 入力信号名の付け替えに失敗しました。")?;
+
+        Ok(())
+    }
+
+    /// SubSystem境界で出力信号の名前を付け替える
+    /// namelist: [(外部の信号名, 内部の信号名)]
+    /// 内部モデルはconnect_outbusで内部の信号名を指定して接続する
+    /// 内部モデルを接続する前に呼び出す必要がある
+    pub fn rename_outbus(&mut self, namelist: &[(&str, &str)]) -> anyhow::Result<()> {
+        if self.outbus_buf.iter().any(|sig| sig.is_connected()) {
+            return Err(anyhow!("SubSystem: 内部モデルが既に出力バスに接続されているため、信号名を付け替えることができません。"));
+        }
+
+        let sigdef = rename_sigdef(&self.outbus.get_sigdef(), self.outbus_buf.get_sigdef(), namelist).context("SubSystem: 出力信号名の付け替えに失敗しました。")?;
+        self.outbus_buf = RefBus::try_from(sigdef).context("SubSystem: 出力信号名の付け替えに失敗しました。")?;
+
+        Ok(())
+    }
+
     /// SubSystem内部のモデルの入力にSubSystemの入力インターフェースを接続する
     pub fn connect_inbus<T:ModelCore>(&self, target_mdl: &mut T, srclist: &[&str], dstlist: &[&str]) -> anyhow::Result<()> {
         if let Some(target_inbus) = target_mdl.interface_in() {
@@ -67,6 +105,70 @@ impl<'a> SubSystem<'a> {
         }
         Ok(())
     }
+
+    /// 内部の信号を記録用インターフェースに公開する（出力バスには含めない）
+    /// names: 登録済みの内部モデルの出力信号名、またはSubSystemの入力信号名（rename_inbusで付け替えた場合は内部の信号名）
+    /// 公開した信号は内部の信号を共有するため、値のコピーは不要。親のSimSystemからはconnect_recordingでレコーダに接続する
+    /// 信号が見つからない場合、同じ名前の出力を持つ内部モデルが複数ある場合、既に公開している場合はエラーとする（いずれの信号も公開しない）
+    pub fn expose_for_recording(&mut self, names: &[&str]) -> anyhow::Result<()> {
+        let mut bus = self.recording_bus.clone(); // 信号の実体は共有される
+        for name in names.iter() {
+            let mut found = self.models.iter().filter_map(|mdl| mdl.interface_out().and_then(|outbus| outbus.get_by_name(*name))).collect::<Vec<&Signal>>();
+            if found.is_empty() {
+                found.extend(self.inbus_buf.get_by_name(*name));
+            }
+
+            match found.as_slice() {
+                [sig] => bus.push((*sig).clone()).context(format!("SubSystem: 信号\"{}\"は既に公開されています。", name))?,
+                [] => return Err(anyhow!("SubSystem: 公開する信号\"{}\"が内部モデルの出力・入力バスに見つかりませんでした。", name)),
+                _ => return Err(anyhow!("SubSystem: 信号\"{}\"を出力する内部モデルが複数あります。", name)),
+            }
+        }
+
+        self.recording_bus = bus;
+        Ok(())
+    }
+
+    /// 記録用インターフェース（expose_for_recordingで公開した信号）
+    pub fn recording_bus(&self) -> &Bus {
+        &self.recording_bus
+    }
+
+    /// 記録用インターフェースの信号を、親のSimSystemのレコーダ等の入力に接続する
+    pub fn connect_recording<T:ModelCore>(&self, target_mdl: &mut T, srclist: &[&str], dstlist: &[&str]) -> anyhow::Result<()> {
+        if let Some(target_inbus) = target_mdl.interface_in() {
+            target_inbus.connect_to(&self.recording_bus, srclist, dstlist)?;
+        } else {
+            return Err(anyhow!("入力インターフェースが定義されていないモデルです。信号の接続はできません。"));
+        }
+        Ok(())
+    }
+
+    /// SimSystemの1ステップ（刻み幅outer_dt）を内部の刻み幅で分割する数 ceil(|outer_dt| / delta_t)
+    /// 浮動小数の誤差で1つ多く切り上げられないよう、整数に十分近い値は丸める。内部の刻み幅の方が長い・不正な値の場合は1とする
+    fn substep_num(&self, outer_dt: f64) -> usize {
+        let num = outer_dt.abs() / self.delta_t;
+        if num.is_nan() || num <= 1.0 {
+            return 1;
+        }
+
+        let rounded = num.round();
+        let num = if (num - rounded).abs() <= SUBSTEP_NUM_EPS * rounded { rounded } else { num.ceil() };
+        num.min(usize::MAX as f64) as usize
+    }
+}
+
+/// 外部バスの信号名で指定された信号について、バッファ側の信号定義の名前を付け替える
+/// （外部バスとバッファは同じ並びで作成されているため、インデックスで対応付ける）
+fn rename_sigdef(extdef: &[SigDef], mut sigdef: Vec<SigDef>, namelist: &[(&str, &str)]) -> anyhow::Result<Vec<SigDef>> {
+    for (ext_name, int_name) in namelist.iter() {
+        let idx = extdef.iter().position(|sig| sig.name() == *ext_name)
+                        .ok_or_else(|| anyhow!("信号名{}が見つかりませんでした。", ext_name))?;
+        let unit = sigdef[idx].unit().to_string();
+        sigdef[idx] = SigDef::new(*int_name, unit);
+    }
+
+    Ok(sigdef)
 }
 
 impl<'a> ModelCore for SubSystem<'a> {
@@ -75,8 +177,10 @@ impl<'a> ModelCore for SubSystem<'a> {
             self.delta_t = sim_time.delta_t(); 
         }
 
-        // モデルの初期化
-        self.models.iter_mut().for_each(|mdl| mdl.initialize(sim_time));
+        // モデルの初期化（内部のモデルには分割後の刻み幅を渡す）
+        let outer_dt = sim_time.delta_t();
+        let local_time = SimTime::new(sim_time.time(), sim_time.end_time(), outer_dt / self.substep_num(outer_dt) as f64);
+        self.models.iter_mut().for_each(|mdl| mdl.initialize(&local_time));
     }
 
     fn finalize(&mut self) {
@@ -85,18 +189,35 @@ impl<'a> ModelCore for SubSystem<'a> {
     }
 
     fn nextstate(&mut self, sim_time: &SimTime) {
-        // 入力バスの値をバッファへコピーする
-        self.inbus_buf.copy_val_from_bus(&self.inbus);
-        // 各モデルを1ステップ進める
-        self.models.iter_mut().for_each(|mdl| mdl.nextstate(sim_time));
+        // 入力バスの値をバッファへコピーする（信号名を付け替えている場合があるため位置で対応付ける）
+        self.inbus_buf.copy_val_by_index(&self.inbus).unwrap(); // バッファは同じ要素数で作成しているため失敗しない
+        // 内部の刻み幅がSimSystemの刻み幅より短い場合は、1ステップを分割して各モデルを複数回進める
+        // 分割後の刻み幅は外部の1ステップをちょうど等分する値とし、サブステップの間は入力バッファの値を保持する
+        let outer_dt = sim_time.delta_t();
+        let num = self.substep_num(outer_dt);
+        if num == 1 {
+            self.models.iter_mut().for_each(|mdl| mdl.nextstate(sim_time));
+        } else {
+            let mut local_time = SimTime::new(sim_time.time() - outer_dt, sim_time.time(), outer_dt / num as f64);
+            for _ in 0..num {
+                local_time.nextstate();
+                self.models.iter_mut().for_each(|mdl| mdl.nextstate(&local_time));
+            }
+        }
         // 出力バッファの値を出力バスへコピーする
-        self.outbus.copy_val_from_bus(&self.outbus_buf);
+        self.outbus.copy_val_by_index(&self.outbus_buf).unwrap();
     }
 
     fn interface_in(&mut self) -> Option<&mut RefBus> {
         Some(&mut self.inbus)
     }
 
+    /// 内部のモデルのエラーを返す（複数ある場合は登録順で最初のもの）
+    fn take_step_error(&mut self) -> Option<anyhow::Error> {
+        self.models.iter_mut().enumerate()
+                   .find_map(|(idx, mdl)| mdl.take_step_error().map(|e| e.context(format!("SubSystem: 内部のモデル{}でエラーが発生しました。", idx))))
+    }
+
     fn interface_out(&self) -> Option<&Bus> {
         Some(&self.outbus)
     }
@@ -110,12 +231,15 @@ mod subsystem_test {
     use sim_model::{de_models, controller_models, subsystem, source_models, sample_models, sink_models};
     use controller_models::PIDController;
     use subsystem::SubSystem;
-    use de_models::SolverType;
-    use source_models::StepFunc;
+    use de_models::{SolverType, SpaceStateModel};
+    use source_models::{StepFunc, ConstantFunc};
     use sample_models::BallAndBeam;
     use sink_models::SimRecorder;
-    use sim_signal::signal::SigDef;
-    use sim_system::SimSystem;
+    use sim_signal::signal::{SigDef, SigTrait};
+    use sim_signal::bus::Bus;
+    use sim_system::{SimSystem, SimTime};
+    use sim_model::model_core::ModelCore;
+    use sim_model::test_models::TestModel;
 
 
     fn make_controller<'a>() -> SubSystem<'a> {
@@ -167,6 +291,126 @@ mod subsystem_test {
         sys
     }
 
+    #[test]
+    fn subsystem_rename_test() {
+        let mut src = Bus::try_from(vec![SigDef::new("a", "A"), SigDef::new("b", "V")]).unwrap();
+        src[0].set_val(2.0);
+        src[1].set_val(3.0);
+
+        let mut sub = SubSystem::new(
+            vec![SigDef::new("a", "A"), SigDef::new("b", "V")],
+            vec![SigDef::new("power", "kW")],
+            1.0
+        ).unwrap();
+
+        assert!(sub.rename_inbus(&[("c", "test_in1")]).is_err()); // 外部に存在しない信号名
+        assert!(sub.rename_inbus(&[("a", "b")]).is_err()); // 内部で名前が重複
+
+        sub.rename_inbus(&[("a", "test_in1"), ("b", "test_in2")]).unwrap();
+        sub.rename_outbus(&[("power", "test_out1")]).unwrap();
+
+        let mut mdl = TestModel::new();
+        sub.connect_inbus(&mut mdl, &["test_in1", "test_in2"], &["test_in1", "test_in2"]).unwrap();
+        sub.connect_outbus(&mdl, &["test_out1"], &["test_out1"]).unwrap();
+
+        assert!(sub.rename_inbus(&[("a", "x")]).is_err()); // 接続後の付け替えはエラー
+        assert!(sub.rename_outbus(&[("power", "x")]).is_err());
+
+        sub.regist_model(mdl);
+        sub.interface_in().unwrap().connect_to(&src, &["a", "b"], &["a", "b"]).unwrap();
+
+        let sim_time = SimTime::new(0.0, 1.0, 1.0);
+        sub.initialize(&sim_time);
+        sub.nextstate(&sim_time);
+
+        assert_eq!(sub.interface_out().unwrap()[0].val(), 6.0);
+    }
+
+    #[test]
+    fn subsystem_substep_test() {
+        // 硬い1次系 x' = -5000 x + 5000 u をオイラー法で解く（刻み幅1[ms]では不安定、0.1[ms]では安定）
+        let make_model = || {
+            let mut mdl = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 1, SolverType::Euler).unwrap();
+            mdl.set_mtrx_a(&[-5000.0]).unwrap();
+            mdl.set_mtrx_b(&[5000.0]).unwrap();
+            mdl.set_mtrx_c(&[1.0]).unwrap();
+            mdl
+        };
+        let run = |outer_dt: f64, sub_dt: Option<f64>| {
+            let src = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+            let mut sys = SimSystem::new(0.0, 0.01, outer_dt);
+            match sub_dt {
+                Some(sub_dt) => {
+                    let mut sub = SubSystem::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], sub_dt).unwrap();
+                    let mut mdl = make_model();
+                    sub.connect_inbus(&mut mdl, &["u"], &["u"]).unwrap();
+                    sub.connect_outbus(&mdl, &["y"], &["y"]).unwrap();
+                    sub.regist_model(mdl);
+                    connect_models(&src, &["u"], &mut sub, &["u"]).unwrap();
+                    connect_models(&sub, &["y"], &mut rcd, &["y"]).unwrap();
+                    sys.regist_model(src);
+                    sys.regist_model(sub);
+                },
+                None => {
+                    let mut mdl = make_model();
+                    connect_models(&src, &["u"], &mut mdl, &["u"]).unwrap();
+                    connect_models(&mdl, &["y"], &mut rcd, &["y"]).unwrap();
+                    sys.regist_model(src);
+                    sys.regist_model(mdl);
+                },
+            }
+            sys.regist_recorder("rcd", rcd);
+            sys.run();
+            sys.get_result("rcd").unwrap().get_signal("y").unwrap().to_vec()
+        };
+
+        // 外部1[ms]・内部0.1[ms]のサブシステムは、0.1[ms]で直接解いた結果の10ステップごとの値と一致する
+        let direct = run(1e-4, None);
+        let sub = run(1e-3, Some(1e-4));
+        assert_eq!(sub.len(), 11);
+        for (k, y) in sub.iter().enumerate().skip(1) {
+            assert!((y - direct[k * 10]).abs() < 1e-12, "k = {}, sub = {}, direct = {}", k, y, direct[k * 10]);
+        }
+        assert!((sub[10] - 1.0).abs() < 1e-6);
+
+        // 内部の刻み幅が外部と同じ場合は分割しないため発散する
+        assert!(run(1e-3, Some(1e-3))[10].abs() > 1e3);
+    }
+
+    #[test]
+    fn subsystem_recording_test() {
+        // TestModel（出力: test_out1 = in1 * in2、test_out2 = in1の累積）をまとめたサブシステムの内部信号を親で記録する
+        let src = ConstantFunc::new(vec![SigDef::new("a", "A"), SigDef::new("b", "V")], &[2.0, 3.0]).unwrap();
+        let mut sub = SubSystem::new(vec![SigDef::new("a", "A"), SigDef::new("b", "V")], vec![SigDef::new("power", "kW")], 1.0).unwrap();
+        sub.rename_inbus(&[("a", "test_in1"), ("b", "test_in2")]).unwrap();
+        sub.rename_outbus(&[("power", "test_out1")]).unwrap();
+        let mut mdl = TestModel::new();
+        sub.connect_inbus(&mut mdl, &["test_in1", "test_in2"], &["test_in1", "test_in2"]).unwrap();
+        sub.connect_outbus(&mdl, &["test_out1"], &["test_out1"]).unwrap();
+        sub.regist_model(mdl);
+
+        assert!(sub.expose_for_recording(&["test_out2", "unknown"]).is_err());
+        assert_eq!(sub.recording_bus().len(), 0); // エラーの場合は何も公開しない
+        sub.expose_for_recording(&["test_out2", "test_in1"]).unwrap(); // 内部モデルの出力と入力信号
+        assert!(sub.expose_for_recording(&["test_out2"]).is_err()); // 公開済み
+        assert_eq!(sub.interface_out().unwrap().len(), 1); // 出力バスは変わらない
+
+        connect_models(&src, &["a", "b"], &mut sub, &["a", "b"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("acc", "t"), SigDef::new("in1", "A")]).unwrap();
+        sub.connect_recording(&mut rcd, &["test_out2", "test_in1"], &["acc", "in1"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 3.0, 1.0);
+        sys.regist_model(src);
+        sys.regist_model(sub);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_signal("acc").unwrap(), &[0.0, 2.0, 4.0, 6.0]);
+        assert_eq!(rcd.get_signal("in1").unwrap(), &[0.0, 2.0, 2.0, 2.0]);
+    }
+
     #[test]
     fn subsystem_test() {
         // ボールアンドビームを2つのコントローラをサブシステムにまとめて実装してみる
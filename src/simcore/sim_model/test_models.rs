@@ -115,4 +115,107 @@ impl ModelCore for TestModel2 {
     fn interface_out(&self) -> Option<&Bus> {
         Some(&self.outbus)
     }
-}
\ No newline at end of file
+}
+
+/// 省略可能な入力を持つテスト用モデル：y = u + bias（"bias"は省略可能で、未接続の場合は0）
+pub struct OptionalInputModel {
+    inbus: RefBus,
+    outbus: Bus,
+}
+
+impl OptionalInputModel {
+    pub fn new() -> Self {
+        Self {
+            inbus: RefBus::try_from(vec![SigDef::new("u", "-"), SigDef::new("bias", "-")]).unwrap(),
+            outbus: Bus::try_from(vec![SigDef::new("y", "-")]).unwrap(),
+        }
+    }
+}
+
+impl Default for OptionalInputModel {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ModelCore for OptionalInputModel {
+    fn initialize(&mut self, _sim_time: &SimTime) {
+        self.outbus[0].set_val(0.0);
+    }
+
+    fn finalize(&mut self) {}
+
+    fn nextstate(&mut self, _sim_time: &SimTime) {
+        let y = self.inbus[0].val() + self.inbus[1].val_or(0.0);
+        self.outbus[0].set_val(y);
+    }
+
+    fn interface_in(&mut self) -> Option<&mut RefBus> {
+        Some(&mut self.inbus)
+    }
+
+    fn interface_out(&self) -> Option<&Bus> {
+        Some(&self.outbus)
+    }
+
+    fn optional_inputs(&self) -> Vec<String> {
+        vec!["bias".to_string()]
+    }
+}
+
+/// モデル単体のテスト用ヘルパー：入力に定数を与えて1ステップ実行し、出力（信号名, 値）を出力バスの順に返す
+/// 入力は（入力バスの信号名, 値）で指定し、同名の定数信号をモデルの入力に接続する（単位は確認しない。指定しなかった入力は未接続のまま）
+/// 時刻はSimTime::new(0.0, dt, dt)とし、initialize → nextstate → post_stepの順に呼び出す
+/// 入力の接続に失敗した場合（存在しない信号名・接続済みの入力など）はパニックする。
+/// `cargo test`では常に、それ以外ではtest-util featureを有効にした場合に使用できる
+///
+/// 例：let out = test_step(&mut gain, &[("u1", 1.0), ("u2", 2.0)], 0.1); // → [("y", 3.0)]
+#[cfg(any(test, feature = "test-util"))]
+pub fn test_step<M: ModelCore>(model: &mut M, inputs: &[(&str, f64)], dt: f64) -> Vec<(String, f64)> {
+    let names = inputs.iter().map(|(name, _)| *name).collect::<Vec<&str>>();
+    let mut srcbus = Bus::try_from(names.iter().map(|name| SigDef::new(*name, "-")).collect::<Vec<SigDef>>())
+                         .unwrap_or_else(|e| panic!("test_step: 入力の指定が不正です。{:?}", e));
+    srcbus.iter_mut().zip(inputs.iter()).for_each(|(sig, (_, val))| sig.set_val(*val));
+
+    if !inputs.is_empty() {
+        let inbus = model.interface_in().unwrap_or_else(|| panic!("test_step: モデルに入力バスがありません。"));
+        inbus.connect_to_unchecked(&srcbus, &names, &names).unwrap_or_else(|e| panic!("test_step: 入力を接続できません。{:?}", e));
+    }
+
+    let mut sim_time = SimTime::new(0.0, dt, dt);
+    model.initialize(&sim_time);
+    sim_time.next();
+    model.nextstate(&sim_time);
+    model.post_step(&sim_time);
+
+    model.interface_out().map_or(Vec::new(), |outbus| outbus.iter().map(|sig| (sig.name(), sig.val())).collect())
+}
+
+#[cfg(test)]
+mod test_models_test {
+    use super::*;
+    use crate::simcore::sim_model::basic_models::{Gain, EnergyMeter};
+
+    #[test]
+    fn test_step_test() {
+        let mut gain = Gain::new(vec![SigDef::new("u1", "-"), SigDef::new("u2", "-")], vec![SigDef::new("y", "-")], &[1.0, 2.0]).unwrap();
+        assert_eq!(test_step(&mut gain, &[("u1", 1.0), ("u2", 2.0)], 0.1), vec![("y".to_string(), 5.0)]);
+
+        // 入力はinitialize前に接続されるため、初期化時に入力を使うモデルも1ステップで評価できる
+        let mut meter = EnergyMeter::new(vec![SigDef::new("f", "N"), SigDef::new("v", "m/s")], vec![SigDef::new("p", "W"), SigDef::new("e", "J")]).unwrap();
+        let out = test_step(&mut meter, &[("f", 2.0), ("v", 3.0)], 0.5);
+        assert_eq!(out, vec![("p".to_string(), 6.0), ("e".to_string(), 3.0)]);
+
+        let mut model = TestModel::new();
+        let out = test_step(&mut model, &[("test_in1", 2.0), ("test_in2", 4.0)], 1.0);
+        assert_eq!(out[0], ("test_out1".to_string(), 8.0));
+        assert_eq!(out[1], ("test_out2".to_string(), 2.0));
+    }
+
+    #[test]
+    #[should_panic(expected = "test_step")]
+    fn test_step_unknown_input_test() {
+        let mut gain = Gain::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0]).unwrap();
+        test_step(&mut gain, &[("none", 1.0)], 0.1);
+    }
+}
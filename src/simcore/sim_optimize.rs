@@ -0,0 +1,176 @@
+/// # 最適化
+/// シミュレーション結果から計算したコストを最小化するパラメータを探索する
+/// 
+/// - 有限差分による勾配降下法（バックトラッキングによるステップ幅調整付き）
+use anyhow::{anyhow, Context};
+
+/// 最適化の設定
+#[derive(Debug, Clone)]
+pub struct OptimizeSetting {
+    pub max_iter: usize,                 // 最大反復回数
+    pub step_size: f64,                  // 勾配降下の初期ステップ幅
+    pub fd_delta: f64,                   // 有限差分の刻み幅
+    pub tolerance: f64,                  // 収束判定（勾配のノルム、コストの改善量がこれ以下で終了）
+    pub bounds: Option<Vec<(f64, f64)>>, // パラメータの上下限 (min, max)
+}
+
+impl Default for OptimizeSetting {
+    fn default() -> Self {
+        Self {
+            max_iter: 100,
+            step_size: 1.0,
+            fd_delta: 1e-6,
+            tolerance: 1e-9,
+            bounds: None,
+        }
+    }
+}
+
+/// 最適化結果
+#[derive(Debug, Clone)]
+pub struct OptimizeResult {
+    pub params: Vec<f64>, // 最適化後のパラメータ
+    pub cost: f64,        // 最適化後のコスト
+    pub iter: usize,      // 反復回数
+}
+
+/// 入力のパラメータを最適化する
+/// cost_funcはパラメータを受け取ってモデルを作成・シミュレーションを実行し、
+/// SimRecorderの記録結果などから計算したスカラーのコストを返す関数として定義する
+pub fn optimize_input<F>(init_params: &[f64], mut cost_func: F, setting: &OptimizeSetting) -> anyhow::Result<OptimizeResult>
+    where F: FnMut(&[f64]) -> anyhow::Result<f64>
+{
+    if init_params.is_empty() {
+        return Err(anyhow!("optimize_input: パラメータが空です。"));
+    }
+
+    if let Some(bounds) = &setting.bounds {
+        if bounds.len() != init_params.len() {
+            return Err(anyhow!("optimize_input: boundsとパラメータの要素数が異なります。bounds:{}, params:{}", bounds.len(), init_params.len()));
+        }
+    }
+
+    let mut params = clamp_params(init_params.to_vec(), &setting.bounds);
+    let mut cost = eval_cost(&mut cost_func, &params)?;
+    let mut step = setting.step_size;
+    let mut iter = 0;
+
+    while iter < setting.max_iter {
+        iter += 1;
+
+        // 中心差分で勾配を計算する
+        let mut grad = vec![0.0; params.len()];
+        for i in 0..params.len() {
+            let mut p_plus = params.clone();
+            let mut p_minus = params.clone();
+            p_plus[i] += setting.fd_delta;
+            p_minus[i] -= setting.fd_delta;
+            grad[i] = (eval_cost(&mut cost_func, &p_plus)? - eval_cost(&mut cost_func, &p_minus)?) / (2.0 * setting.fd_delta);
+        }
+
+        let grad_norm = grad.iter().map(|g| g * g).sum::<f64>().sqrt();
+        if grad_norm <= setting.tolerance {
+            break;
+        }
+
+        // コストが下がるまでステップ幅を半分にしていく
+        let mut improved = false;
+        while step > f64::EPSILON {
+            let candidate = clamp_params(params.iter().zip(grad.iter()).map(|(p, g)| p - step * g).collect(), &setting.bounds);
+            let new_cost = eval_cost(&mut cost_func, &candidate)?;
+
+            if new_cost < cost {
+                let gain = cost - new_cost;
+                params = candidate;
+                cost = new_cost;
+                step *= 2.0; // 次の反復では少し大きいステップから試す
+                improved = gain > setting.tolerance;
+                break;
+            }
+            step *= 0.5;
+        }
+
+        if !improved {
+            break;
+        }
+    }
+
+    Ok(OptimizeResult { params, cost, iter })
+}
+
+/// コストを評価する（NaNなど不正な値はエラーとする）
+fn eval_cost<F>(cost_func: &mut F, params: &[f64]) -> anyhow::Result<f64>
+    where F: FnMut(&[f64]) -> anyhow::Result<f64>
+{
+    let cost = cost_func(params).context(format!("コストの計算に失敗しました。params:{:?}", params))?;
+    if !cost.is_finite() {
+        return Err(anyhow!("コストが有限の値ではありません。params:{:?}, cost:{}", params, cost));
+    }
+    Ok(cost)
+}
+
+/// パラメータを上下限の範囲に収める
+fn clamp_params(params: Vec<f64>, bounds: &Option<Vec<(f64, f64)>>) -> Vec<f64> {
+    match bounds {
+        Some(b) => params.iter().zip(b.iter()).map(|(p, (min, max))| p.max(*min).min(*max)).collect(),
+        None => params,
+    }
+}
+
+#[cfg(test)]
+mod optimize_test {
+    use super::*;
+    use crate::prelude::{*};
+
+    #[test]
+    fn quadratic_test() {
+        let res = optimize_input(
+            &[0.0, 0.0],
+            |p| Ok((p[0] - 1.0).powi(2) + 10.0 * (p[1] + 2.0).powi(2)),
+            &OptimizeSetting { step_size: 0.1, ..Default::default() }
+        ).unwrap();
+
+        assert!((res.params[0] - 1.0).abs() < 1e-3);
+        assert!((res.params[1] + 2.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn bounds_test() {
+        let res = optimize_input(
+            &[0.0],
+            |p| Ok((p[0] - 3.0).powi(2)),
+            &OptimizeSetting { bounds: Some(vec![(-1.0, 2.0)]), ..Default::default() }
+        ).unwrap();
+
+        assert_eq!(res.params[0], 2.0);
+        assert!(optimize_input(&[0.0], |p| Ok(p[0]), &OptimizeSetting { bounds: Some(vec![]), ..Default::default() }).is_err());
+        assert!(optimize_input(&[0.0], |_p| Ok(f64::NAN), &OptimizeSetting::default()).is_err());
+    }
+
+    /// 1次遅れ系 1/(s+1) の1秒後の出力が0.5になるステップ入力の大きさを求める
+    #[test]
+    fn simulation_test() {
+        let cost_func = |p: &[f64]| -> anyhow::Result<f64> {
+            let input = StepFunc::new(vec![SigDef::new("u", "-")], vec![(0.0, p[0], 0.0)])?;
+            let mut plant = TransFuncModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], &[1.0], &[1.0, 1.0], SolverType::RungeKutta)?;
+            let mut scp = SimRecorder::new(vec![SigDef::new("y", "-")])?;
+
+            connect_models(&input, &["u"], &mut plant, &["u"])?;
+            connect_models(&plant, &["y"], &mut scp, &["y"])?;
+
+            let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+            sys.regist_model(input);
+            sys.regist_model(plant);
+            sys.regist_recorder("scp", scp);
+            sys.run();
+
+            let y = sys.get_recorder("scp")?.get_signal("y").ok_or(anyhow!("信号が見つかりません"))?;
+            Ok((y[y.len() - 1] - 0.5).powi(2))
+        };
+
+        let res = optimize_input(&[0.0], cost_func, &OptimizeSetting::default()).unwrap();
+        let expected = 0.5 / (1.0 - (-1.0f64).exp());
+
+        assert!((res.params[0] - expected).abs() < 1e-2);
+    }
+}
@@ -11,6 +11,9 @@ pub type RefBus = BusCore<RefSignal>;
 
 use anyhow::{*};
 
+use std::ops::{Add, Sub, Mul};
+use std::f64::consts::PI;
+
 extern crate nalgebra as na;
 use na::{DMatrix};
 impl Bus {
@@ -32,17 +35,135 @@ impl Bus {
         self.iter_mut().for_each(|sig| sig.set_val(value));
     }
 
+    /// 値を小数点以下precision桁で表示した文字列を返す（format!("{:.3}", bus)と同じ）
+    pub fn fmt_with_precision(&self, precision: usize) -> String {
+        format!("{:.*}", precision, self)
+    }
+
+    /// 要素ごとに他のバスの値を加算する（要素数が同じであること）
+    pub fn add_bus<T:SigTrait>(&mut self, other: &BusCore<T>) -> anyhow::Result<()> {
+        self.elementwise(other, |a, b| a + b).context("add_bus: 加算に失敗しました。")
+    }
+
+    /// 要素ごとに他のバスの値を減算する（要素数が同じであること）
+    pub fn sub_bus<T:SigTrait>(&mut self, other: &BusCore<T>) -> anyhow::Result<()> {
+        self.elementwise(other, |a, b| a - b).context("sub_bus: 減算に失敗しました。")
+    }
+
+    /// すべての信号の値をfactor倍する
+    pub fn scale(&mut self, factor: f64) {
+        self.iter_mut().for_each(|sig| sig.set_val(sig.val() * factor));
+    }
+
+    /// 要素ごとの和を新しいバスとして返す（要素数が同じであること。信号名・単位は自身のものを引き継ぐ）
+    pub fn try_add<T:SigTrait>(&self, other: &BusCore<T>) -> anyhow::Result<Bus> {
+        self.zip_with(other, |a, b| a + b).context("try_add: 加算に失敗しました。")
+    }
+
+    /// 要素ごとの差を新しいバスとして返す（要素数が同じであること。信号名・単位は自身のものを引き継ぐ）
+    pub fn try_sub<T:SigTrait>(&self, other: &BusCore<T>) -> anyhow::Result<Bus> {
+        self.zip_with(other, |a, b| a - b).context("try_sub: 減算に失敗しました。")
+    }
+
+    /// 要素ごとの積を新しいバスとして返す（要素数が同じであること。信号名・単位は自身のものを引き継ぐ）
+    pub fn try_mul<T:SigTrait>(&self, other: &BusCore<T>) -> anyhow::Result<Bus> {
+        self.zip_with(other, |a, b| a * b).context("try_mul: 乗算に失敗しました。")
+    }
+
+    /// 各信号の値にfuncを適用した新しいバスを返す（信号名・単位は自身のものを引き継ぐ）
+    pub fn map(&self, func: impl Fn(f64) -> f64) -> Bus {
+        let mut bus = self.detached();
+        bus.iter_mut().for_each(|sig| sig.set_val(func(sig.val())));
+        bus
+    }
+
+    /// 同じ位置の信号の値にfuncを適用した新しいバスを返す（信号名・単位は自身のものを引き継ぐ）
+    /// 要素数が異なる場合はエラーとする
+    pub fn zip_with<T:SigTrait>(&self, other: &BusCore<T>, func: impl Fn(f64, f64) -> f64) -> anyhow::Result<Bus> {
+        let mut bus = self.detached();
+        bus.elementwise(other, func)?;
+        Ok(bus)
+    }
+
+    /// 信号名・単位・値が同じで、信号の実体を共有しないバス（cloneは信号の実体を共有する）
+    fn detached(&self) -> Bus {
+        let mut bus = Bus::try_from(self.get_sigdef()).expect("バス内の信号名は重複しない");
+        bus.iter_mut().zip(self.iter()).for_each(|(dst, src)| dst.set_val(src.val()));
+        bus
+    }
+
+    /// 要素ごとの演算の共通処理
+    fn elementwise<T:SigTrait>(&mut self, other: &BusCore<T>, func: impl Fn(f64, f64) -> f64) -> anyhow::Result<()> {
+        if self.len() != other.len() {
+            return Err(anyhow!("バスの要素数が異なります。要素数:{}, 相手の要素数:{}", self.len(), other.len()));
+        }
+
+        self.iter_mut().zip(other.iter()).for_each(|(sig, o)| sig.set_val(func(sig.val(), o.val())));
+
+        Ok(())
+    }
+
     /// 値のコピー（バスの中の同じ名前の信号をコピーする）
-    pub fn copy_val_from_bus<T:SigTrait>(&mut self, srcbus: &BusCore<T>) {
+    /// 要素数が異なる場合、コピー元に同じ名前の信号が見つからない場合はエラーとする（値は変更しない）
+    pub fn copy_val_from_bus<T:SigTrait>(&mut self, srcbus: &BusCore<T>) -> anyhow::Result<()> {
+        if self.len() != srcbus.len() {
+            return Err(anyhow!("copy_val_from_bus: バスの要素数が異なります。コピー先:{}, コピー元:{}", self.len(), srcbus.len()));
+        }
+
+        let notfoundlist = self.iter().filter(|sig| srcbus.get_by_name(sig.name()).is_none())
+                                      .map(|sig| format!("\"{}\"", sig.name())).collect::<Vec<String>>();
+        if !notfoundlist.is_empty() {
+            return Err(anyhow!("copy_val_from_bus: コピー元に下記の信号が見つかりませんでした。\n{}", notfoundlist.join(", ")));
+        }
+
         self.iter_mut().for_each(|sig|{
             if let Some(src) = srcbus.get_by_name(sig.name()) {
                 sig.set_val(src.val());
             }
         });
+
+        Ok(())
+    }
+
+    /// 値のコピー（信号名によらず、同じ位置の信号をコピーする）
+    /// 要素数が異なる場合はエラーとする（値は変更しない）
+    pub fn copy_val_by_index<T:SigTrait>(&mut self, srcbus: &BusCore<T>) -> anyhow::Result<()> {
+        if self.len() != srcbus.len() {
+            return Err(anyhow!("copy_val_by_index: バスの要素数が異なります。コピー先:{}, コピー元:{}", self.len(), srcbus.len()));
+        }
+
+        self.iter_mut().zip(srcbus.iter()).for_each(|(dst, src)| dst.set_val(src.val()));
+
+        Ok(())
     }
     
 }
 
+/// 要素ごとの四則演算（&a + &b）。要素数が異なる場合はパニックする（エラーで扱う場合はtry_add等を使用する）
+impl<T:SigTrait> Add<&BusCore<T>> for &Bus {
+    type Output = Bus;
+
+    fn add(self, other: &BusCore<T>) -> Bus {
+        self.try_add(other).unwrap_or_else(|e| panic!("{:#}", e))
+    }
+}
+
+impl<T:SigTrait> Sub<&BusCore<T>> for &Bus {
+    type Output = Bus;
+
+    fn sub(self, other: &BusCore<T>) -> Bus {
+        self.try_sub(other).unwrap_or_else(|e| panic!("{:#}", e))
+    }
+}
+
+impl<T:SigTrait> Mul<&BusCore<T>> for &Bus {
+    type Output = Bus;
+
+    fn mul(self, other: &BusCore<T>) -> Bus {
+        self.try_mul(other).unwrap_or_else(|e| panic!("{:#}", e))
+    }
+}
+
 /// Bus用の実装
 impl TryFrom<Vec<SigDef>> for Bus {
     type Error = anyhow::Error;
@@ -63,15 +184,64 @@ impl fmt::Display for Bus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let buslen = self.len();
         let sigstr = self.iter().map(
-            |sig| format!("  {}: {}[{}]", sig.name(), sig.val(), sig.unit())).collect::<Vec<String>>().join("\n");
+            |sig| match f.precision() {
+                Some(p) => format!("  {:.*}", p, sig),
+                None => format!("  {}", sig),
+            }).collect::<Vec<String>>().join("\n");
 
         write!(f, "Bus: size = {}\nSignal List:\n{}\n", buslen, sigstr)
     }
 }
 
+/// 単位の換算係数（from → toの倍率）。換算表に無い組み合わせはNone
+fn unit_conversion(from: &str, to: &str) -> Option<f64> {
+    const TABLE: [(&str, &str, f64); 8] = [
+        ("deg", "rad", PI / 180.0),
+        ("deg/s", "rad/s", PI / 180.0),
+        ("rpm", "rad/s", 2.0 * PI / 60.0),
+        ("mm", "m", 1.0e-3),
+        ("km/h", "m/s", 1.0 / 3.6),
+        ("kW", "W", 1.0e3),
+        ("kN", "N", 1.0e3),
+        ("ms", "s", 1.0e-3),
+    ];
+
+    TABLE.iter().find_map(|(a, b, k)| {
+        if (*a, *b) == (from, to) {
+            Some(*k)
+        } else if (*b, *a) == (from, to) {
+            Some(1.0 / k)
+        } else {
+            None
+        }
+    })
+}
+
 /// RefBus用の実装
 impl RefBus {
+    /// 接続元の信号srclistを接続先の信号dstlistに接続する
+    /// 接続元と接続先の信号の単位が異なる場合はエラーとする（いずれの信号も接続しない）
+    /// 単位を確認せずに接続する場合はconnect_to_uncheckedを使用する
     pub fn connect_to<T:SigTrait> (&mut self, srcbus: &BusCore<T>, srclist: &[&str], dstlist: &[&str]) -> anyhow::Result<()> {
+        let mismatches = srclist.iter().zip(dstlist.iter()).filter_map(|(src_signame, dst_signame)| {
+            let src = srcbus.get_by_name(*src_signame)?;
+            let dst = self.get_by_name(*dst_signame)?;
+            if src.unit() == dst.unit() {
+                return None;
+            }
+            let hint = unit_conversion(&src.unit(), &dst.unit()).map_or(String::new(), |k| format!("（換算係数: {}）", k));
+            Some(format!("  \"{}\"[{}] -> \"{}\"[{}]{}", src.name(), src.unit(), dst.name(), dst.unit(), hint))
+        }).collect::<Vec<String>>();
+
+        if !mismatches.is_empty() {
+            return Err(anyhow!("接続元と接続先の信号の単位が異なります。Gainモデル等で換算してから接続してください（単位を確認しない場合はconnect_to_unchecked・connect_models_uncheckedを使用する）。\n{}", mismatches.join("\n")));
+        }
+
+        self.connect_to_unchecked(srcbus, srclist, dstlist)
+    }
+
+    /// 単位を確認せずに、接続元の信号srclistを接続先の信号dstlistに接続する
+    pub fn connect_to_unchecked<T:SigTrait> (&mut self, srcbus: &BusCore<T>, srclist: &[&str], dstlist: &[&str]) -> anyhow::Result<()> {
         if srclist.len() != dstlist.len() {
             return Err(anyhow!("接続元(srclist)と接続先の信号名リストのサイズが異なっています。"))
         }
@@ -106,7 +276,21 @@ impl RefBus {
 
         Ok(())
     }
-    
+
+    /// 信号名によらず、先頭からcount個の信号をインデックス順に接続する
+    /// （srcbus[i] -> self[i]）
+    pub fn connect_positional<T:SigTrait> (&mut self, srcbus: &BusCore<T>, count: usize) -> anyhow::Result<()> {
+        if count > srcbus.len() || count > self.len() {
+            return Err(anyhow!("接続数が不正です。count:{}, 接続元のサイズ:{}, 接続先のサイズ:{}", count, srcbus.len(), self.len()));
+        }
+
+        for idx in 0..count {
+            self[idx].connect_to(&srcbus[idx])?;
+        }
+
+        Ok(())
+    }
+
     /// 指定した信号の接続を解除する
     pub fn disconnect(&mut self, signame: &str) -> anyhow::Result<()> {
         match self.get_by_name_mut(signame) {
@@ -116,6 +300,16 @@ impl RefBus {
         Ok(())
     }
 
+    /// 値を小数点以下precision桁で表示した文字列を返す（format!("{:.3}", bus)と同じ）
+    pub fn fmt_with_precision(&self, precision: usize) -> String {
+        format!("{:.*}", precision, self)
+    }
+
+    /// 各信号の(信号名, 接続元の信号名)の一覧（未接続の信号の接続元はNone）
+    pub fn connection_report(&self) -> Vec<(String, Option<String>)> {
+        self.iter().map(|sig| (sig.name(), sig.source_name())).collect()
+    }
+
     /// すべての信号の接続を解除する
     pub fn disconnect_all(&mut self) {
         self.iter_mut().for_each(|sig| sig.disconnect());
@@ -125,6 +319,11 @@ impl RefBus {
         DMatrix::from_vec(self.len(), 1, self.to_vec_f64())
     }
 
+    /// 時刻tにおける値をDMatrixに変換する（ソルバの中間段で入力をサンプリングする時に使用する）
+    pub fn export_to_matrix_at(&self, t: f64) -> DMatrix<f64> {
+        DMatrix::from_vec(self.len(), 1, self.to_vec_f64_at(t))
+    }
+
 }
 
 impl TryFrom<Vec<SigDef>> for RefBus {
@@ -146,7 +345,10 @@ impl fmt::Display for RefBus {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         let buslen = self.len();
         let sigstr = self.iter().map(
-            |sig| format!("  {}", sig)).collect::<Vec<String>>().join("\n");
+            |sig| match f.precision() {
+                Some(p) => format!("  {:.*}", p, sig),
+                None => format!("  {}", sig),
+            }).collect::<Vec<String>>().join("\n");
 
         write!(f, "RefBus: size = {}\nSignal List:\n{}\n", buslen, sigstr)
     }
@@ -171,6 +373,14 @@ mod sim_bus_test {
 
     }
 
+    #[test]
+    fn refbus_connection_report() {
+        let src = Bus::try_from(vec![SigDef::new("a", "-"), SigDef::new("b", "-")]).unwrap();
+        let mut dst = RefBus::try_from(vec![SigDef::new("x", "-"), SigDef::new("y", "-")]).unwrap();
+        dst.connect_to(&src, &["b"], &["x"]).unwrap();
+        assert_eq!(dst.connection_report(), vec![("x".to_string(), Some("b".to_string())), ("y".to_string(), None)]);
+    }
+
     #[test]
     fn bus_fmt_display() {
         let mut a = 
@@ -188,6 +398,152 @@ mod sim_bus_test {
         assert_eq!(format!("{}", a), format!("Bus: size = 3\nSignal List:\n  {}\n  {}\n  {}\n", a[0], a[1], a[2]));
     }
 
+    #[test]
+    fn bus_arithmetic() {
+        let mut a = Bus::try_from( vec![
+            SigDef::new("test1", "A"),
+            SigDef::new("test2", "A"),
+        ]).unwrap();
+        a.import_matrix(&DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+
+        let mut b = Bus::try_from( vec![
+            SigDef::new("test3", "A"),
+            SigDef::new("test4", "A"),
+        ]).unwrap();
+        b.import_matrix(&DMatrix::from_vec(2, 1, vec![10.0, 20.0]));
+
+        a.add_bus(&b).unwrap();
+        assert_eq!(a.to_vec_f64(), vec![11.0, 22.0]);
+
+        let mut c = RefBus::try_from( vec![SigDef::new("ref1", "A"), SigDef::new("ref2", "A")]).unwrap();
+        c.connect_to(&b, &["test3", "test4"], &["ref1", "ref2"]).unwrap();
+        a.sub_bus(&c).unwrap();
+        assert_eq!(a.to_vec_f64(), vec![1.0, 2.0]);
+
+        a.scale(-0.5);
+        assert_eq!(a.to_vec_f64(), vec![-0.5, -1.0]);
+
+        let d = Bus::try_from( vec![SigDef::new("test5", "A")]).unwrap();
+        assert!(a.add_bus(&d).is_err());
+        assert!(a.sub_bus(&d).is_err());
+    }
+
+    #[test]
+    fn bus_copy_val() {
+        let mut a = Bus::try_from( vec![
+            SigDef::new("test1", "A"),
+            SigDef::new("test2", "A"),
+        ]).unwrap();
+
+        let mut b = Bus::try_from( vec![
+            SigDef::new("test2", "A"),
+            SigDef::new("test1", "A"),
+        ]).unwrap();
+        b.import_matrix(&DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+
+        a.copy_val_from_bus(&b).unwrap();
+        assert_eq!(a.to_vec_f64(), vec![2.0, 1.0]);
+
+        a.copy_val_by_index(&b).unwrap();
+        assert_eq!(a.to_vec_f64(), vec![1.0, 2.0]);
+
+        let c = Bus::try_from( vec![SigDef::new("test1", "A"), SigDef::new("test3", "A")]).unwrap();
+        assert!(a.copy_val_from_bus(&c).is_err()); // 信号名が見つからない
+        assert_eq!(a.to_vec_f64(), vec![1.0, 2.0]);
+
+        let d = Bus::try_from( vec![SigDef::new("test1", "A")]).unwrap();
+        assert!(a.copy_val_from_bus(&d).is_err()); // 要素数が異なる
+        assert!(a.copy_val_by_index(&d).is_err());
+    }
+
+    #[test]
+    fn bus_fmt_precision() {
+        let mut a = 
+        Bus::try_from( vec![    
+            SigDef::new("test1", "A"),
+            SigDef::new("test2", "V"),
+        ]).unwrap();
+
+        a[0].set_val(1.0000000003);
+        a[1].set_val(-2.5);
+
+        assert_eq!(format!("{:.3}", a), "Bus: size = 2\nSignal List:\n  test1: 1.000[A]\n  test2: -2.500[V]\n");
+        assert_eq!(a.fmt_with_precision(1), "Bus: size = 2\nSignal List:\n  test1: 1.0[A]\n  test2: -2.5[V]\n");
+
+        let mut b = RefBus::try_from( vec![SigDef::new("ref1", "A")]).unwrap();
+        b.connect_to(&a, &["test1"], &["ref1"]).unwrap();
+        assert_eq!(b.fmt_with_precision(2), "RefBus: size = 1\nSignal List:\n  ref1: 1.00 [A] Referrer: test1[A]\n");
+    }
+
+    #[test]
+    fn refbus_connect_unit_check() {
+        let src = Bus::try_from(vec![SigDef::new("angle", "deg"), SigDef::new("angle2", "deg")]).unwrap();
+
+        // deg -> radはエラー（換算係数を示す）
+        let mut rad = RefBus::try_from(vec![SigDef::new("angle", "rad")]).unwrap();
+        let err = rad.connect_to(&src, &["angle"], &["angle"]).unwrap_err().to_string();
+        assert!(err.contains("\"angle\"[deg] -> \"angle\"[rad]"), "{}", err);
+        assert!(err.contains(&format!("換算係数: {}", PI / 180.0)), "{}", err);
+        assert!(!rad[0].is_connected());
+        rad.connect_to_unchecked(&src, &["angle"], &["angle"]).unwrap();
+        assert!(rad[0].is_connected());
+
+        // deg -> degは接続できる
+        let mut deg = RefBus::try_from(vec![SigDef::new("a", "deg"), SigDef::new("b", "rad")]).unwrap();
+        deg.connect_to(&src, &["angle"], &["a"]).unwrap();
+        assert!(deg[0].is_connected());
+
+        // 一部の単位が異なる場合はいずれも接続しない
+        let mut deg = RefBus::try_from(vec![SigDef::new("a", "deg"), SigDef::new("b", "rad")]).unwrap();
+        assert!(deg.connect_to(&src, &["angle", "angle2"], &["a", "b"]).is_err());
+        assert!(deg.iter().all(|sig| !sig.is_connected()));
+
+        assert_eq!(unit_conversion("rad", "deg"), Some(180.0 / PI));
+        assert_eq!(unit_conversion("m", "deg"), None);
+    }
+
+    #[test]
+    fn bus_operators() {
+        let mut volt = Bus::try_from(vec![SigDef::new("v1", "V"), SigDef::new("v2", "V")]).unwrap();
+        volt.import_matrix(&DMatrix::from_vec(2, 1, vec![2.0, 3.0]));
+        let mut curr = Bus::try_from(vec![SigDef::new("i1", "A"), SigDef::new("i2", "A")]).unwrap();
+        curr.import_matrix(&DMatrix::from_vec(2, 1, vec![0.5, -1.0]));
+
+        // 要素ごとの積（信号名は左辺のもの）
+        let mut power = &volt * &curr;
+        assert_eq!(power.to_vec_f64(), vec![1.0, -3.0]);
+        assert_eq!(power.get_sigdef().iter().map(|s| s.name().to_string()).collect::<Vec<_>>(), vec!["v1", "v2"]);
+        power[0].set_val(10.0); // 結果は元のバスと信号を共有しない
+        assert_eq!(volt.to_vec_f64(), vec![2.0, 3.0]);
+
+        assert_eq!((&volt + &curr).to_vec_f64(), vec![2.5, 2.0]);
+        assert_eq!((&volt - &curr).to_vec_f64(), vec![1.5, 4.0]);
+        assert_eq!(volt.map(|v| v * v).to_vec_f64(), vec![4.0, 9.0]);
+        assert_eq!(volt.zip_with(&curr, f64::max).unwrap().to_vec_f64(), vec![2.0, 3.0]);
+
+        // 同じ信号名が逆順で並んでいても、演算は位置で行い信号名は左辺のものとする
+        let mut a = Bus::try_from(vec![SigDef::new("x", "-"), SigDef::new("y", "-")]).unwrap();
+        a.import_matrix(&DMatrix::from_vec(2, 1, vec![1.0, 2.0]));
+        let mut b = Bus::try_from(vec![SigDef::new("y", "-"), SigDef::new("x", "-")]).unwrap();
+        b.import_matrix(&DMatrix::from_vec(2, 1, vec![10.0, 20.0]));
+        let c = &a + &b;
+        assert_eq!(c.get_by_name("x").unwrap().val(), 11.0);
+        assert_eq!(c.get_by_name("y").unwrap().val(), 22.0);
+
+        // 要素数が異なる場合
+        let d = Bus::try_from(vec![SigDef::new("x", "-")]).unwrap();
+        assert!(a.try_mul(&d).is_err());
+        assert!(a.zip_with(&d, |x, _| x).is_err());
+    }
+
+    #[test]
+    #[should_panic]
+    fn bus_operators_size_mismatch() {
+        let a = Bus::try_from(vec![SigDef::new("x", "-"), SigDef::new("y", "-")]).unwrap();
+        let b = Bus::try_from(vec![SigDef::new("x", "-")]).unwrap();
+        let _ = &a + &b;
+    }
+
 }
 
 #[cfg(test)]
@@ -330,6 +686,33 @@ mod sim_refbus_test {
         c.connect_to(&b, &["bus4"], &["refbus1"]).unwrap(); // 既に接続した信号に再接続はNG
     }
 
+    #[test]
+    fn refbus_connect_positional() {
+        let mut a = Bus::try_from( vec![
+            SigDef::new("fx", "N"),
+            SigDef::new("fy", "N"),
+            SigDef::new("fz", "N"),
+        ]).unwrap();
+
+        a[0].set_val(1.0);
+        a[1].set_val(2.0);
+        a[2].set_val(3.0);
+
+        let mut c =
+            RefBus::try_from( vec![
+                SigDef::new("in1", "N"),
+                SigDef::new("in2", "N"),
+                SigDef::new("in3", "N"),
+            ]).unwrap();
+
+        c.connect_positional(&a, 2).unwrap();
+        assert_eq!(c[0].val(), 1.0);
+        assert_eq!(c[1].val(), 2.0);
+        assert!(!c[2].is_connected());
+
+        assert!(c.connect_positional(&a, 4).is_err());
+    }
+
     #[test]
     #[should_panic]
     fn refbus_connect_notfound_panic() {
@@ -69,6 +69,11 @@ impl<T: SigTrait> BusCore<T> {
         self.signals.iter().map(|sig| sig.val()).collect::<Vec<f64>>()
     }
 
+    /// 時刻tにおける値を集めたVecを返す（SigTrait::val_atを参照）
+    pub fn to_vec_f64_at(&self, t: f64) -> Vec<f64> {
+        self.signals.iter().map(|sig| sig.val_at(t)).collect::<Vec<f64>>()
+    }
+
     /// Vec<SigDef>を返す。モデルの作成時に使用する。
     pub fn get_sigdef(&self) -> Vec<SigDef> {
         self.signals.iter().map(|sig| SigDef::new(sig.name().to_string(), sig.unit().to_string()))
@@ -26,6 +26,48 @@ impl fmt::Display for SigDef {
     }
 }
 
+/// SigDefのリストを作成するビルダー
+/// SigDefList::new().add("a", "m").add_many(&[("b", "m/s")]).build() のように使用する
+/// 信号名の重複はbuild時にエラーとする
+#[derive(Debug, Clone, Default)]
+pub struct SigDefList {
+    list: Vec<SigDef>,
+}
+
+impl SigDefList {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// 信号を1つ追加する
+    pub fn add(mut self, name: impl Into<String>, unit: impl Into<String>) -> Self {
+        self.list.push(SigDef::new(name, unit));
+        self
+    }
+
+    /// (信号名, 単位)のリストをまとめて追加する
+    pub fn add_many(mut self, defs: &[(&str, &str)]) -> Self {
+        self.list.extend(defs.iter().map(|(name, unit)| SigDef::new(*name, *unit)));
+        self
+    }
+
+    /// Vec<SigDef>を作成する（信号名が重複している場合はエラー）
+    pub fn build(self) -> anyhow::Result<Vec<SigDef>> {
+        let mut dups = Vec::new();
+        for (idx, def) in self.list.iter().enumerate() {
+            if self.list[..idx].iter().any(|d| d.name() == def.name()) && !dups.contains(&def.name()) {
+                dups.push(def.name());
+            }
+        }
+
+        if !dups.is_empty() {
+            return Err(anyhow!("信号名が重複しています。: {}", dups.join(", ")));
+        }
+
+        Ok(self.list)
+    }
+}
+
 #[macro_export]
 macro_rules! MakeSigList {
     ($( $x:expr ),* ) => {
@@ -51,6 +93,33 @@ pub trait SigTrait {
     fn val(&self) -> f64;
     
     fn sig(&self) -> &Rc<RefCell<SigCore>>;
+
+    /// 時刻tにおける値を取得する
+    /// 信号の出力元が任意の時刻の値を計算できる（サンプラが設定されている）場合はその値を、
+    /// そうでない場合は現在の値を返す。ソルバの中間段で入力をサンプリングする時に使用する
+    fn val_at(&self, t: f64) -> f64 {
+        let core = self.sig().borrow();
+        match &core.sampler {
+            Some(sampler) if !core.hold => (sampler.0)(t),
+            _ => core.val(),
+        }
+    }
+}
+
+/// 任意の時刻の信号値を計算する関数（時刻の解析関数として値が決まるソースモデルが設定する）
+#[derive(Clone)]
+pub struct SigSampler(Rc<dyn Fn(f64) -> f64>);
+
+impl fmt::Debug for SigSampler {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SigSampler")
+    }
+}
+
+impl PartialEq for SigSampler {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
 }
 
 // SigCore構造体
@@ -62,6 +131,8 @@ use std::cell::{RefCell};
 pub struct SigCore {
     value: f64,   
     sigdef: SigDef,
+    sampler: Option<SigSampler>, // 任意時刻の値を計算する関数（設定されていない信号が大半）
+    hold: bool,                  // trueの時はサンプラを使わず現在値を保持する
 }
 
 impl SigCore {
@@ -69,6 +140,8 @@ impl SigCore {
         Self {
             value: initvalue,
             sigdef: SigDef::new(name.into(), unit.into()),
+            sampler: None,
+            hold: false,
         }
     }
 
@@ -85,9 +158,17 @@ impl SigCore {
     }
 }
 
+/// 値の表示（フォーマッタに精度の指定（{:.3}など）があればその桁数で表示する）
+fn fmt_val(f: &fmt::Formatter, val: f64) -> String {
+    match f.precision() {
+        Some(p) => format!("{:.*}", p, val),
+        None => format!("{}", val),
+    }
+}
+
 impl fmt::Display for SigCore {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}: {}[{}]", self.sigdef.name(), self.value, self.sigdef.unit())
+        write!(f, "{}: {}[{}]", self.sigdef.name(), fmt_val(f, self.value), self.sigdef.unit())
     }
 }
 
@@ -107,6 +188,27 @@ impl Signal {
     pub fn set_val(&mut self, val: f64) {
         self.sig.borrow_mut().value = val;
     }
+
+    /// 任意の時刻の値を計算する関数を設定する（val_atで使用される）
+    pub fn set_sampler(&mut self, sampler: impl Fn(f64) -> f64 + 'static) {
+        self.sig.borrow_mut().sampler = Some(SigSampler(Rc::new(sampler)));
+    }
+
+    /// サンプラを解除する（val_atは現在値を返す）
+    pub fn clear_sampler(&mut self) {
+        self.sig.borrow_mut().sampler = None;
+    }
+
+    /// サンプラが設定されているかどうか
+    pub fn has_sampler(&self) -> bool {
+        self.sig.borrow().sampler.is_some()
+    }
+
+    /// 値を現在値に固定する（val_atでもサンプラを使わない）
+    /// 出力元のモデルを進めずに入力を保持する時に使用する（SimSystem::run_subsetを参照）
+    pub(crate) fn set_hold(&self, hold: bool) {
+        self.sig.borrow_mut().hold = hold;
+    }
 }
 
 impl SigTrait for Signal {
@@ -162,9 +264,19 @@ impl RefSignal {
         self.sig.is_some()
     }
 
+    /// 接続元の信号名（未接続の場合はNone）
+    pub fn source_name(&self) -> Option<String> {
+        self.sig.as_ref().map(|sig| sig.borrow().name())
+    }
+
     pub fn disconnect(&mut self) {
         self.sig = None;
     }
+
+    /// 接続元の値（未接続の場合はdefault）。省略可能な入力（ModelCore::optional_inputs）の参照に使用する
+    pub fn val_or(&self, default: f64) -> f64 {
+        self.sig.as_ref().map_or(default, |sig| sig.borrow().val())
+    }
 }
 
 impl SigTrait for RefSignal {
@@ -193,7 +305,7 @@ impl fmt::Display for RefSignal {
         match &self.sig {
             Some(sig) => {
                 write!(f, "{}: {} [{}] Referrer: {}[{}]", 
-                    self.sigdef.name(), self.val(), self.sigdef.unit(),
+                    self.sigdef.name(), fmt_val(f, self.val()), self.sigdef.unit(),
                     sig.borrow().name(), sig.borrow().unit())
                 
             }
@@ -236,6 +348,36 @@ mod sim_signals_test {
         assert_eq!(s, "motor2: 1.1 [A] Referrer: motor_current[A]");
         println!("{}", s);
 
+        let s = format!("{:.3}", a);
+        assert_eq!(s, "motor_current: 1.100[A]");
+
+        let s = format!("{:.2}", b);
+        assert_eq!(s, "motor2: 1.10 [A] Referrer: motor_current[A]");
+
+    }
+
+    #[test]
+    fn sigdef_list_test() {
+        let list = SigDefList::new().add("a", "m").add_many(&[("b", "m/s"), ("c", "m/s^2")]).build().unwrap();
+        assert_eq!(list, vec![SigDef::new("a", "m"), SigDef::new("b", "m/s"), SigDef::new("c", "m/s^2")]);
+
+        let err = SigDefList::new().add("a", "m").add_many(&[("b", "m/s"), ("a", "-")]).build().unwrap_err();
+        assert!(err.to_string().contains("a"));
+        assert!(SigDefList::new().build().unwrap().is_empty());
+    }
+
+    #[test]
+    fn sampler_test() {
+        let mut a = Signal::new(1.0, "a", "-");
+        let mut b = RefSignal::new("b", "-");
+        b.connect_to(&a).unwrap();
+
+        assert_eq!(b.val_at(3.0), 1.0); // サンプラが無い場合は現在値
+
+        a.set_sampler(|t| 2.0 * t);
+        assert_eq!(a.val_at(3.0), 6.0);
+        assert_eq!(b.val_at(3.0), 6.0);
+        assert_eq!(b.val(), 1.0);
     }
 
     #[test]
@@ -1,21 +1,38 @@
 use std::{collections::HashMap};
-use anyhow::{anyhow};
+use std::rc::Rc;
+use std::fmt;
+use std::time::{Duration, Instant};
+use std::sync::mpsc::Sender;
+use std::path::PathBuf;
+use std::panic::{self, AssertUnwindSafe};
+use anyhow::{anyhow, Context};
 
 /// モデルを組み合わせて一つのシステムを構成する
-use super::sim_model::{model_core, sink_models};
+use super::sim_model::{model_core, sink_models, de_models::DEModel};
 use model_core::{ModelCore};
-use sink_models::SimRecorder;
+use sink_models::{SimRecorder, EventRecorder};
+use super::sim_signal::signal::{SigDef, SigTrait, Signal};
+use super::sim_signal::bus::RefBus;
 
 
+/// step_num()で整数とみなす相対誤差
+const STEP_NUM_EPS: f64 = 1e-9;
+
 /// SimTime
 /// シミュレーションの時間を管理
+/// 時刻は刻み幅の加算を繰り返すと丸め誤差が累積するため、基準時刻 + (ステップ番号 - 基準ステップ) * 刻み幅で毎ステップ計算する
+/// 基準は開始時刻（ステップ0）とし、刻み幅を変更した時はその時点の時刻・ステップに移す
+/// ステップ数は (end_time - start_time) / delta_t を切り捨てた値（整数との差がSTEP_NUM_EPS以内なら丸める。step_numを参照）。
+/// 例：SimTime::new(0.0, 1.0, 0.1)は時刻0.1〜1.0の10ステップ進む（初期化時の時刻0.0を含めて記録点は11点）
 #[derive(Clone, Copy, Debug)]
 pub struct SimTime {
-    time: f64,       // シミュレーション時刻
-    step: u32,       // ステップ番号
-    delta_t: f64,    // シミュレーション刻み幅
-    start_time: f64, // 開始時刻
-    end_time: f64,   // 終了時刻
+    time: f64,        // シミュレーション時刻
+    step: u64,        // ステップ番号（u32では細かい刻み幅の長時間シミュレーションで溢れるためu64）
+    delta_t: f64,     // シミュレーション刻み幅
+    start_time: f64,  // 開始時刻
+    end_time: f64,    // 終了時刻
+    origin_time: f64, // 時刻計算の基準時刻
+    origin_step: u64, // 時刻計算の基準ステップ
 }
 
 impl SimTime {
@@ -30,6 +47,8 @@ impl SimTime {
             delta_t: delta_t,
             start_time: start_time,
             end_time: end_time,
+            origin_time: start_time,
+            origin_step: 0,
         }
     }
 
@@ -37,16 +56,24 @@ impl SimTime {
     pub fn reset(&mut self) {
         self.step = 0;
         self.time = self.start_time;
+        self.origin_time = self.start_time;
+        self.origin_step = 0;
     }
 
-    /// シミュレーション刻み幅Δtを変更する
+    /// シミュレーション刻み幅Δtを変更する（以降の時刻は現在時刻から新しい刻み幅で進む）
     pub fn change_delta_t(&mut self, delta_t: f64) {
+        self.origin_time = self.time;
+        self.origin_step = self.step;
         self.delta_t = delta_t;
     }
     /// シミュレーション時刻を1ステップ進める
     pub fn nextstate(&mut self) {
-        self.time = self.time + self.delta_t;
+        if self.step == u64::MAX { // ステップ番号が飽和した後は時刻のみ加算で進める
+            self.time += self.delta_t;
+            return;
+        }
         self.step += 1;
+        self.time = self.origin_time + (self.step - self.origin_step) as f64 * self.delta_t;
     }
     /// 現在時刻を取得する
     pub fn time(&self) -> f64 {
@@ -56,9 +83,27 @@ impl SimTime {
     pub fn delta_t(&self) -> f64 {
         self.delta_t
     }
+    /// 現在のステップ番号を取得する
+    pub fn step(&self) -> u64 {
+        self.step
+    }
     /// シミュレーションのステップ数を取得する
+    /// 浮動小数の誤差で1ステップ少なく切り捨てられないよう、整数に十分近い値は丸める
+    /// 不正な値（負・NaN）の時は0、usizeに収まらない時はusize::MAXに飽和させる
     pub fn step_num(&self) -> usize {
-        ((self.end_time - self.start_time) / self.delta_t) as usize
+        let num = (self.end_time - self.start_time) / self.delta_t;
+        if num.is_nan() || num <= 0.0 {
+            return 0;
+        }
+
+        let rounded = num.round();
+        let num = if (num - rounded).abs() <= STEP_NUM_EPS * rounded.max(1.0) { rounded } else { num.floor() };
+
+        if num >= usize::MAX as f64 {
+            usize::MAX
+        } else {
+            num as usize
+        }
     }
     /// シミュレーションの開始時刻を終了する
     pub fn start_time(&self) -> f64 {
@@ -68,14 +113,24 @@ impl SimTime {
     pub fn end_time(&self) -> f64 {
         self.end_time
     }
+    /// 時間を逆向きに進めるSimTimeを作成する（end_timeからstart_timeへ-delta_t刻みで進む）
+    pub fn reversed(&self) -> Self {
+        SimTime::new(self.end_time, self.start_time, -self.delta_t)
+    }
+    /// 時間を逆向きに進めている（delta_t < 0）かどうか
+    pub fn is_backward(&self) -> bool {
+        self.delta_t < 0.0
+    }
 }
 
 /// SimTimeのイテレータ実装
 impl Iterator for SimTime {
-    type Item = (u32, f64); // (step, time)
+    type Item = (u64, f64); // (step, time)
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.time < self.end_time {
+        // 逆向き（delta_t < 0）の場合はtime > end_timeの間進める
+        // 時刻の丸め誤差で終了時刻の直前に止まった場合に余分な1ステップを進めないよう、刻み幅に対して十分小さい残りは終了とみなす
+        if (self.end_time - self.time) * self.delta_t.signum() > self.delta_t.abs() * STEP_NUM_EPS {
             self.nextstate();
             Some((self.step, self.time))
         } else {
@@ -84,13 +139,308 @@ impl Iterator for SimTime {
     }
 }
 
+/// 実行統計
+/// run()の実行ステップ数と実行時間を保持する。
+/// プロファイリングを有効にした場合はモデルごとのnextstateの累積実行時間も保持する
+#[derive(Clone, Debug, Default)]
+pub struct RunStats {
+    pub steps: u64,                // 実行したステップ数
+    pub elapsed: Duration,         // run()全体の実行時間
+    pub model_times: Vec<Duration>, // モデルごとのnextstate累積時間（登録順、プロファイリング無効時は空）
+}
+
+impl RunStats {
+    /// モデルごとの内訳（モデル番号, 累積時間, 全モデル合計に対する割合）を時間の長い順に返す
+    pub fn model_breakdown(&self) -> Vec<(usize, Duration, f64)> {
+        let total = self.model_times.iter().sum::<Duration>().as_secs_f64();
+        let mut list = self.model_times.iter().enumerate()
+                           .map(|(idx, t)| (idx, *t, if total > 0.0 { t.as_secs_f64() / total } else { 0.0 }))
+                           .collect::<Vec<_>>();
+        list.sort_by_key(|a| std::cmp::Reverse(a.1));
+        list
+    }
+}
+
+impl fmt::Display for RunStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "steps: {}, elapsed: {:?}", self.steps, self.elapsed)?;
+        for (idx, t, ratio) in self.model_breakdown() {
+            writeln!(f, "  モデル{}: {:?} ({:.1}%)", idx, t, ratio * 100.0)?;
+        }
+        Ok(())
+    }
+}
+
+/// 発散監視の設定
+/// レコーダに記録している各系列の絶対値が閾値を超えた状態がsustain_stepsステップ続いた場合に実行を中断する
+/// NaNは常に閾値超過として扱う
+#[derive(Clone, Debug)]
+pub struct DivergenceMonitor {
+    pub abs_limit: Option<f64>, // 絶対値の閾値
+    pub rel_limit: Option<f64>, // 初期値の絶対値（1未満の場合は1）に対する倍率の閾値
+    pub sustain_steps: u64,     // 閾値超過が何ステップ続いたら中断するか
+}
+
+impl Default for DivergenceMonitor {
+    fn default() -> Self {
+        Self {
+            abs_limit: None,
+            rel_limit: Some(1e6),
+            sustain_steps: 10,
+        }
+    }
+}
+
+impl DivergenceMonitor {
+    /// 初期値に対する閾値
+    fn limit(&self, init: f64) -> f64 {
+        let rel = self.rel_limit.map_or(f64::INFINITY, |r| r * init.abs().max(1.0));
+        self.abs_limit.map_or(rel, |a| a.min(rel))
+    }
+}
+
+/// モデルのnextstateでパニックが発生した時の扱い（SimSystem::set_failure_policyで設定する）
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FailurePolicy {
+    Propagate,       // パニックをそのまま伝播する（既定。実行全体が中断され、実行状態はRunningのまま残る）
+    Abort,           // 実行を中断してエラーを返す（終了処理を行うため、失敗前のステップまでの記録データを参照できる）
+    Substitute(f64), // 失敗したモデルの出力をこの値に固定し、以降そのモデルを進めずに実行を続ける
+}
+
+/// モデルの実行失敗の記録（SimSystem::model_failuresで取得する）
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModelFailure {
+    pub model_idx: usize,          // 失敗したモデルの番号（登録順）
+    pub model_type: &'static str,  // 失敗したモデルの型名
+    pub step: u64,                 // 失敗したステップ
+    pub time: f64,                 // 失敗した時刻[s]
+    pub message: String,           // パニックのメッセージ
+}
+
+impl fmt::Display for ModelFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "モデル{}（{}）が時刻{}[s]（ステップ{}）で失敗しました: {}", self.model_idx, self.model_type, self.time, self.step, self.message)
+    }
+}
+
+/// 実行時アサーション（SimSystem::add_assertionで登録する）
+/// 監視する信号の値がpredicateを満たさなくなった時点で実行を中断する
+struct SimAssertion<'a> {
+    model_idx: usize,                         // 信号の出力元のモデル番号（登録順）
+    signal: Signal,                           // 監視する信号（モデルの出力信号を共有する）
+    predicate: Box<dyn Fn(f64) -> bool + 'a>, // 値が満たすべき条件
+    message: String,                          // 違反時に表示するメッセージ
+}
+
+/// 信号の変化の通知内容（SimSystem::subscribeで登録したコールバックに渡す）
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalChange {
+    pub model_idx: usize, // 信号の出力元のモデル番号（登録順）
+    pub time: f64,        // 変化を検出した時刻[s]
+    pub old: f64,         // 前回通知した値（初回は初期値）
+    pub new: f64,         // 現在の値
+}
+
+/// 信号の変化の購読（SimSystem::subscribeで登録する）
+/// 前回通知した値からの変化量がdeltaを超えた時にコールバックを呼び出す
+struct SignalSubscription<'a> {
+    model_idx: usize,                                // 信号の出力元のモデル番号（登録順）
+    signal: Signal,                                  // 監視する信号（モデルの出力信号を共有する）
+    delta: f64,                                      // 通知する変化量の閾値
+    last: f64,                                       // 前回通知した値
+    callback: Box<dyn FnMut(&SignalChange) + 'a>,    // 変化時に呼び出すコールバック
+}
+
+/// プローブ（SimSystem::probeで登録する）
+/// 既存の接続を変えずにモデルの出力信号を記録するレコーダの情報
+#[derive(Debug, Clone, PartialEq)]
+pub struct Probe {
+    pub model_idx: usize, // 信号の出力元のモデル番号（登録順）
+    pub signame: String,  // 記録する信号名
+    pub recorder: String, // 記録先のレコーダ名
+}
+
+/// 信号の接続（接続元モデル番号, 接続元信号名, 接続先, 接続先信号名）
+/// 接続先はモデル番号またはレコーダ名
+#[derive(Clone, Debug, PartialEq)]
+pub struct ManifestConnection {
+    pub src_model: usize,
+    pub src_signal: String,
+    pub dst: ManifestTarget,
+    pub dst_signal: String,
+}
+
+/// 接続先の種類
+#[derive(Clone, Debug, PartialEq)]
+pub enum ManifestTarget {
+    Model(usize),     // モデル番号（登録順）
+    Recorder(String), // レコーダ名
+}
+
+/// シミュレーションの構成確認用マニフェスト
+/// 時間設定・登録モデルの型・信号の接続関係を記録する。テキスト形式（タブ区切り）で保存・読み込みができる
+/// モデルのパラメータ（ゲインや乱数シードなど）は含まないため、マニフェストだけでは実行を再現できない。
+/// 読み込んだマニフェストはSimSystem::from_manifestで時間設定を復元し、
+/// 同じ構成でモデルを登録・接続した後にSimSystem::verify_manifestで構成が一致することを確認する
+/// パラメータを含めて構成を再現するコードが必要な場合はSimSystem::to_rust_sourceを使用する
+#[derive(Clone, Debug, PartialEq)]
+pub struct SimManifest {
+    pub start_time: f64,
+    pub end_time: f64,
+    pub delta_t: f64,
+    pub models: Vec<String>, // 登録順のモデルの型名
+    pub connections: Vec<ManifestConnection>,
+}
+
+impl fmt::Display for SimManifest {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "sim_time\t{}\t{}\t{}", self.start_time, self.end_time, self.delta_t)?;
+        for (idx, name) in self.models.iter().enumerate() {
+            writeln!(f, "model\t{}\t{}", idx, name)?;
+        }
+        for con in self.connections.iter() {
+            match &con.dst {
+                ManifestTarget::Model(idx) => writeln!(f, "connect\t{}\t{}\t{}\t{}", con.src_model, con.src_signal, idx, con.dst_signal)?,
+                ManifestTarget::Recorder(name) => writeln!(f, "record\t{}\t{}\t{}\t{}", con.src_model, con.src_signal, name, con.dst_signal)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for SimManifest {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> anyhow::Result<Self> {
+        let mut sim_time = None;
+        let mut models = Vec::new();
+        let mut connections = Vec::new();
+
+        for (lineno, line) in s.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+            let err = || anyhow!("マニフェストの{}行目が不正です。: {}", lineno + 1, line);
+            let fields = line.split('\t').collect::<Vec<&str>>();
+            match (fields[0], fields.len()) {
+                ("sim_time", 4) => {
+                    let v = fields[1..].iter().map(|x| x.parse::<f64>()).collect::<Result<Vec<f64>, _>>().map_err(|_| err())?;
+                    sim_time = Some((v[0], v[1], v[2]));
+                },
+                ("model", 3) => {
+                    if fields[1].parse::<usize>().map_err(|_| err())? != models.len() {
+                        return Err(err().context("モデル番号は登録順に0から記載してください。"));
+                    }
+                    models.push(fields[2].to_string());
+                },
+                ("connect", 5) | ("record", 5) => {
+                    let dst = if fields[0] == "connect" {
+                        ManifestTarget::Model(fields[3].parse().map_err(|_| err())?)
+                    } else {
+                        ManifestTarget::Recorder(fields[3].to_string())
+                    };
+                    connections.push(ManifestConnection {
+                        src_model: fields[1].parse().map_err(|_| err())?,
+                        src_signal: fields[2].to_string(),
+                        dst,
+                        dst_signal: fields[4].to_string(),
+                    });
+                },
+                _ => return Err(err()),
+            }
+        }
+
+        let (start_time, end_time, delta_t) = sim_time.ok_or(anyhow!("マニフェストにsim_timeがありません。"))?;
+        Ok(Self { start_time, end_time, delta_t, models, connections })
+    }
+}
+
+/// 収束性評価の結果
+/// 刻み幅を半分ずつ小さくして実行した時の最終値を保持し、隣り合う結果の差から収束次数を推定する
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConvergenceStudy {
+    pub delta_t: Vec<f64>, // 刻み幅（粗い順）
+    pub values: Vec<f64>,  // 各刻み幅での最終値
+}
+
+impl ConvergenceStudy {
+    /// 隣り合う刻み幅での最終値の差の絶対値 |v[k] - v[k+1]|
+    pub fn differences(&self) -> Vec<f64> {
+        self.values.windows(2).map(|v| (v[0] - v[1]).abs()).collect()
+    }
+
+    /// 観測された収束次数 p[k] = log2(d[k] / d[k+1])（d：隣り合う最終値の差）
+    pub fn orders(&self) -> Vec<f64> {
+        self.differences().windows(2).map(|d| (d[0] / d[1]).log2()).collect()
+    }
+
+    /// 最も細かい刻み幅で観測された収束次数（3水準未満の時はNone）
+    pub fn observed_order(&self) -> Option<f64> {
+        self.orders().last().copied()
+    }
+
+    /// Richardsonの補外による推定値 v_fine + (v_fine - v_coarse) / (2^p - 1)（収束次数が求まらない時はNone）
+    pub fn extrapolated(&self) -> Option<f64> {
+        let p = self.observed_order().filter(|p| p.is_finite() && *p > 0.0)?;
+        let n = self.values.len();
+        Some(self.values[n - 1] + (self.values[n - 1] - self.values[n - 2]) / (2f64.powf(p) - 1.0))
+    }
+}
+
+impl fmt::Display for ConvergenceStudy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let diffs = self.differences();
+        let orders = self.orders();
+        writeln!(f, "delta_t\tvalue\tdiff\torder")?;
+        for (k, (dt, v)) in self.delta_t.iter().zip(self.values.iter()).enumerate() {
+            let diff = if k == 0 { "-".to_string() } else { format!("{:.3e}", diffs[k - 1]) };
+            let order = if k < 2 { "-".to_string() } else { format!("{:.3}", orders[k - 2]) };
+            writeln!(f, "{:e}\t{}\t{}\t{}", dt, v, diff, order)?;
+        }
+        Ok(())
+    }
+}
+
+/// SimSystemの実行状態
+/// Uninitialized → (run) → Running → Finished と遷移する。Finishedから再実行する時はreset()でUninitializedに戻す
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunState {
+    Uninitialized, // 未実行（reset直後を含む）
+    Running,       // 実行中（実行中にパニックした場合はこの状態のまま残る）
+    Finished,      // 実行完了（発散監視で中断した場合を含む）
+}
+
 /// SimSystem
 /// モデル同士の接続とシミュレーションの実行を司る
+/// 
+/// ## 信号の書き込みについて
+/// - 1つの信号に値を書き込むモデル（信号の出力元）は必ず1つとする
+/// - RefBusの信号は接続元を1つしか持てない（RefSignal::connect_toで二重接続はエラー）
+/// - モデルをcloneすると出力バスの信号が共有されてしまい、複数のモデルが同じ信号に書き込むことになる。
+///   このような接続はvalidate()でエラーとし、run()の実行前に検出する
+/// - モデルは登録順に1ステップずつ実行されるため、同じステップ内での値の受け渡し順序は登録順で決まる
+///   （resolve_order()を呼び出すと信号の接続関係から実行順を決める）
 pub struct SimSystem<'a> {
     sim_time: SimTime,
     models: Vec<Box<dyn ModelCore + 'a>>, // 個々のモデルを管理するコンテナ
                                           // Boxは参照しているのでstructの本体とライフタイムが一致する必要があるためライフタイムパラメータが必要
     recorders: HashMap<String, SimRecorder>, // シミュレーション結果を保存するレコーダコンテナ SimRecorderはinbusを持っていて必要なモデルに接続してあることが必要
+    event_recorders: HashMap<String, EventRecorder>, // イベント発生時のみ記録するレコーダコンテナ
+    profiling: bool,  // モデルごとの実行時間を計測するかどうか
+    stats: RunStats,  // 直近のrun()の実行統計
+    subset: Option<Vec<bool>>, // run_subset実行中に進めるモデル（Noneの時は全モデル）
+    monitor: Option<DivergenceMonitor>, // 発散監視の設定
+    monitor_state: HashMap<(String, String), (f64, u64)>, // (レコーダ名, 系列名) -> (閾値, 連続超過ステップ数)
+    model_types: Vec<&'static str>, // 登録順のモデルの型名（マニフェスト用）
+    model_inputs: Vec<Option<RefBus>>, // 登録順のモデルの入力バス（マニフェスト用、信号の実体はモデルと共有する）
+    order: Option<Vec<usize>>, // resolve_orderで決めた実行順（Noneの時は登録順）
+    state: RunState, // 実行状態
+    assertions: Vec<SimAssertion<'a>>, // 実行時アサーション
+    subscriptions: Vec<SignalSubscription<'a>>, // 信号の変化の購読
+    probes: Vec<Probe>, // 登録済みのプローブ
+    feedback_check: bool, // validateでフィードバックループの良設定性を確認するかどうか
+    output_dir: Option<PathBuf>, // レコーダのファイル出力先ディレクトリ
+    warmup: Option<f64>, // レコーダのウォームアップ時間[s]
+    failure_policy: FailurePolicy, // モデルのnextstateでパニックが発生した時の扱い
+    failures: Vec<ModelFailure>, // 直近の実行で失敗したモデルの記録（失敗順）
+    manual_start: Option<Instant>, // start()で手動実行を開始した時刻（手動実行中以外はNone）
 }
 
 impl<'a> SimSystem<'a> {
@@ -99,7 +449,223 @@ impl<'a> SimSystem<'a> {
             sim_time: SimTime::new(start_time, end_time, delta_t),
             models: Vec::<Box<dyn ModelCore>>::new(),
             recorders: HashMap::new(),
+            event_recorders: HashMap::new(),
+            profiling: false,
+            stats: RunStats::default(),
+            subset: None,
+            monitor: None,
+            monitor_state: HashMap::new(),
+            model_types: Vec::new(),
+            model_inputs: Vec::new(),
+            order: None,
+            state: RunState::Uninitialized,
+            assertions: Vec::new(),
+            subscriptions: Vec::new(),
+            probes: Vec::new(),
+            feedback_check: false,
+            output_dir: None,
+            warmup: None,
+            failure_policy: FailurePolicy::Propagate,
+            failures: Vec::new(),
+            manual_start: None,
+        }
+    }
+
+    /// マニフェストの時間設定でSimSystemを作成する（モデルは別途登録する）
+    pub fn from_manifest(manifest: &SimManifest) -> Self {
+        Self::new(manifest.start_time, manifest.end_time, manifest.delta_t)
+    }
+
+    /// 現在の時間設定・登録モデル・接続関係から構成確認用のマニフェストを作成する（モデルのパラメータは含まない）
+    pub fn manifest(&self) -> SimManifest {
+        // 信号の実体 -> (出力元のモデル番号, 信号名)
+        let mut writers: HashMap<*const (), (usize, String)> = HashMap::new();
+        for (mdl_idx, mdl) in self.models.iter().enumerate() {
+            if let Some(outbus) = mdl.interface_out() {
+                outbus.iter().for_each(|sig| { writers.insert(Rc::as_ptr(sig.sig()) as *const (), (mdl_idx, sig.name())); });
+            }
+        }
+
+        let mut connections = Vec::new();
+        let mut push_inputs = |inbus: Option<&RefBus>, dst: ManifestTarget| {
+            if let Some(inbus) = inbus {
+                for sig in inbus.iter().filter(|sig| sig.is_connected()) {
+                    if let Some((src_model, src_signal)) = writers.get(&(Rc::as_ptr(sig.sig()) as *const ())) {
+                        connections.push(ManifestConnection { src_model: *src_model, src_signal: src_signal.clone(), dst: dst.clone(), dst_signal: sig.name() });
+                    }
+                }
+            }
+        };
+
+        for (mdl_idx, inbus) in self.model_inputs.iter().enumerate() {
+            push_inputs(inbus.as_ref(), ManifestTarget::Model(mdl_idx));
+        }
+        let mut rcd_names = self.recorders.keys().cloned().collect::<Vec<String>>();
+        rcd_names.sort(); // HashMapの順序に依存しないようにする
+        for name in rcd_names {
+            let rcd = &self.recorders[&name];
+            push_inputs(Some(rcd.input_bus()), ManifestTarget::Recorder(name));
+        }
+
+        SimManifest {
+            start_time: self.sim_time.start_time(),
+            end_time: self.sim_time.end_time(),
+            delta_t: self.sim_time.delta_t(),
+            models: self.model_types.iter().map(|s| s.to_string()).collect(),
+            connections,
+        }
+    }
+
+    /// 現在の構成（時間設定・モデルの登録・信号の接続・レコーダ）を再現するRustのコードを返す
+    /// 試行錯誤で組み立てたシステムを、バージョン管理するシミュレーションコードに移す時に使用する
+    /// モデルの生成はModelCore::to_sourceで出力する。to_sourceを実装していないモデルがある場合は、そのモデルを示してエラーとする
+    /// 外部のバスとの接続・イベントレコーダは含まない。生成したコードはuse mint_sim::prelude::*;を前提とする
+    pub fn to_rust_source(&self) -> anyhow::Result<String> {
+        let unsupported = self.models.iter().enumerate()
+                              .filter(|(_, mdl)| mdl.to_source().is_none())
+                              .map(|(idx, _)| format!("モデル{}({})", idx, self.model_types[idx]))
+                              .collect::<Vec<String>>();
+        if !unsupported.is_empty() {
+            return Err(anyhow!("コードを生成できないモデルがあります。: {}", unsupported.join(", ")));
+        }
+
+        let manifest = self.manifest();
+        let mut rcd_names = self.recorders.keys().cloned().collect::<Vec<String>>();
+        rcd_names.sort(); // HashMapの順序に依存しないようにする
+
+        let var_name = |target: &ManifestTarget| match target {
+            ManifestTarget::Model(idx) => Ok(format!("model{}", idx)),
+            ManifestTarget::Recorder(name) => rcd_names.iter().position(|n| n == name)
+                                                       .map(|idx| format!("recorder{}", idx))
+                                                       .ok_or_else(|| anyhow!("接続先のレコーダが見つかりません。レコーダ名:{}", name)),
+        };
+        let siglist = |names: &[String]| names.iter().map(|name| format!("{:?}", name)).collect::<Vec<String>>().join(", ");
+
+        // 接続元と接続先の組ごとに一つのconnect_modelsにまとめる（最初に現れた順）
+        let mut groups: Vec<(usize, ManifestTarget, Vec<String>, Vec<String>)> = Vec::new();
+        for con in manifest.connections.iter() {
+            match groups.iter_mut().find(|(src, dst, _, _)| *src == con.src_model && *dst == con.dst) {
+                Some((_, _, srcs, dsts)) => {
+                    srcs.push(con.src_signal.clone());
+                    dsts.push(con.dst_signal.clone());
+                },
+                None => groups.push((con.src_model, con.dst.clone(), vec![con.src_signal.clone()], vec![con.dst_signal.clone()])),
+            }
+        }
+
+        let mut lines = vec![
+            "// SimSystem::to_rust_sourceで生成したコード（use mint_sim::prelude::*;を前提とする）".to_string(),
+        ];
+        for (idx, mdl) in self.models.iter().enumerate() {
+            let is_dst = groups.iter().any(|(_, dst, _, _)| *dst == ManifestTarget::Model(idx));
+            let source = mdl.to_source().ok_or_else(|| anyhow!("モデル{}({})のコードを生成できません。", idx, self.model_types[idx]))?;
+            lines.push(format!("let {}model{} = {};", if is_dst { "mut " } else { "" }, idx, source));
+        }
+        for (rcd_idx, name) in rcd_names.iter().enumerate() {
+            let sigdefs = self.recorders[name].input_bus().get_sigdef();
+            lines.push(format!("let mut recorder{} = SimRecorder::new(vec![", rcd_idx));
+            sigdefs.iter().for_each(|sig| lines.push(format!("    SigDef::new({:?}, {:?}),", sig.name(), sig.unit())));
+            lines.push("]).unwrap();".to_string());
+        }
+
+        lines.push(String::new());
+        for (src, dst, srcs, dsts) in groups.iter() {
+            lines.push(format!("connect_models(&model{}, &[{}], &mut {}, &[{}]).unwrap();", src, siglist(srcs), var_name(dst)?, siglist(dsts)));
+        }
+
+        lines.push(String::new());
+        lines.push(format!("let mut sys = SimSystem::new({:?}, {:?}, {:?});", manifest.start_time, manifest.end_time, manifest.delta_t));
+        (0..manifest.models.len()).for_each(|idx| lines.push(format!("sys.regist_model(model{});", idx)));
+        rcd_names.iter().enumerate().for_each(|(rcd_idx, name)| lines.push(format!("sys.regist_recorder({:?}, recorder{});", name, rcd_idx)));
+
+        Ok(lines.join("\n") + "\n")
+    }
+
+    /// 信号の接続表（モデル・レコーダの入力信号ごとの接続元）を文字列で返す
+    /// 接続元がモデルの出力信号であれば"model{番号}.{信号名}"、それ以外（外部のバス）は信号名のみを示す
+    pub fn connection_report(&mut self) -> String {
+        // 信号の実体 -> (出力元のモデル番号, 信号名)
+        let mut writers: HashMap<*const (), (usize, String)> = HashMap::new();
+        for (mdl_idx, mdl) in self.models.iter().enumerate() {
+            if let Some(outbus) = mdl.interface_out() {
+                outbus.iter().for_each(|sig| { writers.insert(Rc::as_ptr(sig.sig()) as *const (), (mdl_idx, sig.name())); });
+            }
+        }
+
+        let mut lines = Vec::new();
+        let mut push_inputs = |inbus: Option<&mut RefBus>, dst: String| {
+            if let Some(inbus) = inbus {
+                for sig in inbus.iter() {
+                    let src = match sig.source_name() {
+                        Some(name) => match writers.get(&(Rc::as_ptr(sig.sig()) as *const ())) {
+                            Some((src_model, src_signal)) => format!("model{}.{}", src_model, src_signal),
+                            None => format!("{} (外部)", name),
+                        },
+                        None => "未接続".to_string(),
+                    };
+                    lines.push(format!("{}.{} <- {}", dst, sig.name(), src));
+                }
+            }
+        };
+
+        for (mdl_idx, mdl) in self.models.iter_mut().enumerate() {
+            push_inputs(mdl.interface_in(), format!("model{}", mdl_idx));
+        }
+        let mut rcd_names = self.recorders.keys().cloned().collect::<Vec<String>>();
+        rcd_names.sort(); // HashMapの順序に依存しないようにする
+        for name in rcd_names {
+            let rcd = self.recorders.get_mut(&name).unwrap();
+            push_inputs(rcd.interface_in(), name);
+        }
+
+        lines.join("\n")
+    }
+
+    /// 現在の構成がマニフェストと一致するか確認する（不一致の箇所をエラーで返す）
+    pub fn verify_manifest(&self, manifest: &SimManifest) -> anyhow::Result<()> {
+        let current = self.manifest();
+        let mut errlist = Vec::new();
+
+        if (current.start_time, current.end_time, current.delta_t) != (manifest.start_time, manifest.end_time, manifest.delta_t) {
+            errlist.push(format!("  時間設定: ({}, {}, {}) != ({}, {}, {})", current.start_time, current.end_time, current.delta_t, manifest.start_time, manifest.end_time, manifest.delta_t));
+        }
+        if current.models != manifest.models {
+            errlist.push(format!("  モデル: {:?} != {:?}", current.models, manifest.models));
         }
+        manifest.connections.iter().filter(|c| !current.connections.contains(c)).for_each(|c| errlist.push(format!("  接続が不足しています: {:?}", c)));
+        current.connections.iter().filter(|c| !manifest.connections.contains(c)).for_each(|c| errlist.push(format!("  余分な接続があります: {:?}", c)));
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("マニフェストと構成が一致しません。\n{}", errlist.join("\n")));
+        }
+        Ok(())
+    }
+
+    /// モデルごとのnextstateの実行時間計測を有効・無効にする（結果はrun_statsで取得する）
+    pub fn enable_profiling(&mut self, enable: bool) {
+        self.profiling = enable;
+    }
+
+    /// 発散監視を設定する（Noneで無効）
+    /// 閾値を超えた状態が続くとtry_runはエラーを返し、runはパニックする
+    pub fn set_divergence_monitor(&mut self, monitor: Option<DivergenceMonitor>) {
+        self.monitor = monitor;
+    }
+
+    /// モデルのnextstateでパニックが発生した時の扱いを設定する（既定はFailurePolicy::Propagate）
+    /// Propagate以外では各モデルのnextstateをcatch_unwindで囲み、失敗したモデル・ステップを記録して標準エラーに出力する
+    pub fn set_failure_policy(&mut self, policy: FailurePolicy) {
+        self.failure_policy = policy;
+    }
+
+    /// 直近の実行で失敗したモデルの記録（失敗順）
+    pub fn model_failures(&self) -> &[ModelFailure] {
+        &self.failures
+    }
+
+    /// 直近のrun()の実行統計を取得する
+    pub fn run_stats(&self) -> &RunStats {
+        &self.stats
     }
 
     pub fn set_sim_time(&mut self, sim_time: SimTime) {
@@ -110,134 +676,1892 @@ impl<'a> SimSystem<'a> {
         &mut self.sim_time
     }
 
-    pub fn regist_model<T>(&mut self, model: T) 
+    /// モデルを登録する
+    /// 戻り値は登録順のモデル番号（SimRecorder::subscribe_statesで内部状態を記録する時に使用する）
+    pub fn regist_model<T>(&mut self, mut model: T) -> usize
         where T: ModelCore + 'a
     {
+        self.model_inputs.push(model.interface_in().map(|inbus| inbus.clone()));
         self.models.push(Box::new(model));
+        self.model_types.push(std::any::type_name::<T>());
+        self.order = None; // モデルが増えたので実行順は登録順に戻す
+        self.models.len() - 1
     }
 
-    pub fn regist_recorder(&mut self, name: impl Into<String>, recorder: SimRecorder) {
-        self.recorders.insert(name.into(), recorder);
+    /// 信号の接続関係からモデルの実行順を決める（トポロジカルソート）
+    /// 入力の接続元のモデルを先に実行するよう並べ、以降のnextstate/runはこの順で実行する（モデル番号は登録順のまま）。
+    /// 依存関係の無いモデル同士は登録順を保つ。
+    /// ループ（フィードバック）がある場合は、ループ内の直達の無いモデル（ModelCore::direct_feedthroughがfalse）を先に実行して
+    /// ループを切る（そのモデルは前ステップの入力値を使う）。直達のあるモデルだけでできたループ（代数ループ）はエラーとする
+    /// モデルを追加登録すると実行順は登録順に戻るため、全モデルの登録後に呼び出すこと
+    pub fn resolve_order(&mut self) -> anyhow::Result<Vec<usize>> {
+        let n = self.models.len();
+        let deps = self.dependencies();
+
+        let mut done = vec![false; n];
+        let mut order = Vec::with_capacity(n);
+        while order.len() < n {
+            // 接続元がすべて実行済みのモデルを登録順に選ぶ
+            let ready = (0..n).find(|&j| !done[j] && deps[j].iter().all(|&i| done[i]));
+            // 無い場合はループが残っているので、ループ上の直達の無いモデルで切る
+            let next = ready.or_else(|| (0..n).find(|&j| !done[j] && !self.models[j].direct_feedthrough() && find_cycle(&deps, &done, j).is_some()));
+
+            match next {
+                Some(j) => {
+                    done[j] = true;
+                    order.push(j);
+                },
+                None => {
+                    let start = (0..n).find(|&j| !done[j] && find_cycle(&deps, &done, j).is_some()).unwrap_or(0);
+                    let cycle = find_cycle(&deps, &done, start).unwrap_or_default();
+                    let names = cycle.iter().map(|&j| format!("model{}({})", j, self.model_types[j])).collect::<Vec<String>>();
+                    return Err(anyhow!("代数ループが検出されました。ループ内のモデルはすべて直達があるため実行順を決められません。\nループ: {}", names.join(" -> ")));
+                },
+            }
+        }
+
+        self.order = Some(order.clone());
+        Ok(order)
     }
 
-    pub fn get_recorder(&mut self, name: impl Into<String>) -> anyhow::Result<&mut SimRecorder> {
-        let rcd_name = name.into();
-        let rcd = self.recorders.get_mut(&rcd_name);
+    /// モデル間の接続関係（deps[j]：モデルjの入力の接続元のモデル番号）
+    fn dependencies(&mut self) -> Vec<Vec<usize>> {
+        // 信号の実体 -> 出力元のモデル番号
+        let mut writers: HashMap<*const (), usize> = HashMap::new();
+        for (mdl_idx, mdl) in self.models.iter().enumerate() {
+            if let Some(outbus) = mdl.interface_out() {
+                outbus.iter().for_each(|sig| { writers.insert(Rc::as_ptr(sig.sig()) as *const (), mdl_idx); });
+            }
+        }
 
-        match rcd {
-            Some(r) => Ok(r),
-            None => Err(anyhow!("レコーダが見つかりません。レコーダ名:{}", &rcd_name))
+        let mut deps: Vec<Vec<usize>> = vec![Vec::new(); self.models.len()];
+        for (mdl_idx, mdl) in self.models.iter_mut().enumerate() {
+            if let Some(inbus) = mdl.interface_in() {
+                for sig in inbus.iter().filter(|sig| sig.is_connected()) {
+                    if let Some(src) = writers.get(&(Rc::as_ptr(sig.sig()) as *const ())) {
+                        if !deps[mdl_idx].contains(src) {
+                            deps[mdl_idx].push(*src);
+                        }
+                    }
+                }
+            }
         }
+
+        deps
     }
 
-    pub fn nextstate(&mut self) {
-        // 各モデルを1ステップ進める
-        self.models.iter_mut().for_each(|mdl| mdl.nextstate(&self.sim_time));
-        // 登録してあるレコーダーに結果を格納する
-        self.recorders.iter_mut().for_each(|(_name, rcd)| rcd.nextstate(&self.sim_time));
+    /// 遅れの無い自己依存（モデルの出力を直接自身の入力に接続している）が無いか確認する
+    /// 直達の無いモデル（ModelCore::direct_feedthroughがfalse 積分器・単位遅延など）は正当なフィードバックとしてエラーにしない
+    /// 他のモデルを経由するループはモデルの直達の有無が正しく設定されていない場合があるため、ここでは対象としない（check_feedback_loopsを参照）
+    pub fn check_self_loops(&mut self) -> anyhow::Result<()> {
+        let deps = self.dependencies();
+
+        let errlist = deps.iter().enumerate()
+            .filter(|(idx, dep)| dep.contains(idx) && self.models[*idx].direct_feedthrough())
+            .map(|(idx, _)| format!("  model{}({})", idx, self.model_types[idx]))
+            .collect::<Vec<String>>();
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("遅れの無い自己依存の接続が検出されました。（モデル番号は登録順）\nモデルの出力が直接自身の入力に接続されています。\n{}", errlist.join("\n")));
+        }
+        Ok(())
     }
 
-    pub fn run(&mut self) {
-        // 初期化処理
-        self.initialize();
+    /// 全てのフィードバックループが直達の無いモデル（ModelCore::direct_feedthroughがfalse）を含む（良設定である）ことを確認する
+    /// 直達のあるモデルだけで構成されたループ（代数ループ）があればエラーとする。
+    /// direct_feedthroughの既定値は安全側のtrueのため、直達の無いモデルでもオーバーライドしていなければループとして検出される
+    pub fn check_feedback_loops(&mut self) -> anyhow::Result<()> {
+        let deps = self.dependencies();
+        // 直達の無いモデルでループが切れるため、探索から除外する
+        let cut = self.models.iter().map(|mdl| !mdl.direct_feedthrough()).collect::<Vec<bool>>();
 
-        // シミュレーション実行処理 
-        let print_interval = self.sim_time.step_num() / 10;
-        let mut print_cnt = 0;
-        let mut progress_cnt = 0; // 進捗カウンタ
-        
-        while let Some(_s) = self.sim_time.next() {
-            print_cnt += 1;
-            if print_cnt >= print_interval {
-                progress_cnt += 1;
-                print_cnt = 0;
-                println!("processing now ... {}%)\n", progress_cnt * 10);
+        let mut errlist = Vec::new();
+        let mut reported = cut.clone();
+        for j in 0..self.models.len() {
+            if reported[j] {
+                continue;
+            }
+            if let Some(cycle) = find_cycle(&deps, &cut, j) {
+                cycle.iter().for_each(|&k| reported[k] = true);
+                let names = cycle.iter().map(|&k| format!("model{}({})", k, self.model_types[k])).collect::<Vec<String>>();
+                errlist.push(format!("  {} -> model{}", names.join(" -> "), cycle[0]));
             }
-            self.nextstate();
         }
-        
-        // 終了処理
-        self.finalize();
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("直達の無いモデルを含まないフィードバックループ（代数ループ）が検出されました。（モデル番号は登録順）\n{}", errlist.join("\n")));
+        }
+        Ok(())
     }
 
-    fn initialize(&mut self) {
-        println!("Simulation Initializing Now ...\n");
-        // 時刻の初期化
-        self.sim_time.reset();
-        // モデルの初期化
-        self.models.iter_mut().for_each(|mdl| mdl.initialize(&self.sim_time));
-        // レコーダの初期化
-        self.recorders.iter_mut().for_each(|(_name, rcd)| rcd.initialize(&self.sim_time));
+    /// validateでフィードバックループの良設定性（check_feedback_loops）を確認するかどうかを設定する（既定は確認しない）
+    pub fn set_feedback_check(&mut self, enable: bool) {
+        self.feedback_check = enable;
     }
 
-    fn finalize(&mut self) {
-        println!("Simulation Finalizing Now ...\n");
-        // モデルのファイナライズ
-        self.models.iter_mut().for_each(|mdl| mdl.finalize());
-        // レコーダのファイナライズ (特に処理はないが将来処理を追加した時のために呼び出し)
-        self.recorders.iter_mut().for_each(|(_name, rcd)| rcd.finalize());
+    /// 微分方程式モデルを登録する
+    /// 登録前にDEModel::verify_dimensionsで導関数と状態の次元が一致するか検証し、一致しない場合はエラーとする
+    pub fn regist_de_model<T>(&mut self, model: T) -> anyhow::Result<usize>
+        where T: DEModel + 'a
+    {
+        model.verify_dimensions().context(format!("モデル番号{}の登録に失敗しました。", self.models.len()))?;
+        Ok(self.regist_model(model))
+    }
 
+    pub fn regist_recorder(&mut self, name: impl Into<String>, mut recorder: SimRecorder) {
+        if let Some(dir) = &self.output_dir {
+            if recorder.output_dir().is_none() {
+                recorder.set_output_dir(dir.clone());
+            }
+        }
+        if let Some(warmup) = self.warmup {
+            if recorder.warmup().is_none() {
+                recorder.set_warmup(warmup).unwrap(); // set_warmupで検証済み
+            }
+        }
+        self.recorders.insert(name.into(), recorder);
     }
-}
 
-/* 逆に煩雑になるので実装しない
-impl<'a> From<Vec<Box<dyn ModelCore>>> for SimSystem<'a>
-{
-    fn from(mdl_list: Vec<Box<dyn ModelCore>>) -> Self
-    {
-        Self {
-            sim_time: SimTime { time: 0.0, step: 0, delta_t: DEFAULT_DELTA_T, start_time: 0.0, end_time: 1.0 },
-            models: mdl_list,
-            recorders: HashMap::new(),
+    /// 登録済み・以降に登録するレコーダのファイル出力先ディレクトリを設定する（SimRecorder::set_output_dirを参照）
+    /// 以降に登録するレコーダは、レコーダ側で出力先を設定していない場合のみこの設定を使用する
+    pub fn set_output_dir(&mut self, dir: impl Into<PathBuf>) {
+        let dir = dir.into();
+        self.recorders.values_mut().for_each(|rcd| rcd.set_output_dir(dir.clone()));
+        self.output_dir = Some(dir);
+    }
+
+    /// 登録済み・以降に登録するレコーダのウォームアップ時間[s]を設定する（SimRecorder::set_warmupを参照）
+    /// 以降に登録するレコーダは、レコーダ側でウォームアップ時間を設定していない場合のみこの設定を使用する
+    pub fn set_warmup(&mut self, warmup_time: f64) -> anyhow::Result<()> {
+        for rcd in self.recorders.values_mut() {
+            rcd.set_warmup(warmup_time)?;
         }
+        self.warmup = Some(warmup_time);
+        Ok(())
     }
-}*/
 
-#[cfg(test)]
-mod sim_bus_test {
-    use super::{*};
-    use crate::simcore::sim_model::test_models::{*};
-    use crate::simcore::sim_signal::signal::{*};
-    use crate::simcore::sim_signal::bus::{*};
+    /// イベントレコーダを登録する（各ステップでレコーダの後に更新される）
+    pub fn regist_event_recorder(&mut self, name: impl Into<String>, recorder: EventRecorder) {
+        self.event_recorders.insert(name.into(), recorder);
+    }
 
-    #[test]
-    fn system_regist_test() {
-        let mut sys = SimSystem::new(0.0, 10.0, 0.01);
-        
-        let mut mdl1 = TestModel::new();
-        let mut mdl2 = TestModel::new();
+    /// イベントレコーダを取得する
+    pub fn get_event_recorder(&self, name: &str) -> anyhow::Result<&EventRecorder> {
+        self.event_recorders.get(name).ok_or_else(|| anyhow!("イベントレコーダが見つかりません。レコーダ名:{}", name))
+    }
 
-        let mut datbus = Bus::try_from(vec![
-            SigDef::new("data1", "kW"),
-            SigDef::new("data2", "t"),
-        ]).unwrap();
+    /// 登録済みのモデル（番号は登録順）の出力信号を記録するレコーダを作成・接続して登録する
+    /// signalsが空の場合は全ての出力信号を記録する。レコーダ名は"model{モデル番号}"とし、登録したレコーダ名を返す
+    pub fn record_model_output(&mut self, model_idx: usize, signals: &[&str]) -> anyhow::Result<String> {
+        let name = format!("model{}", model_idx);
+        if self.recorders.contains_key(&name) {
+            return Err(anyhow!("レコーダが既に登録されています。レコーダ名:{}", name));
+        }
 
+        let model = self.models.get(model_idx).ok_or_else(|| anyhow!("モデル番号が範囲外です。モデル番号:{}（登録数:{}）", model_idx, self.models.len()))?;
+        let outbus = model.interface_out().ok_or_else(|| anyhow!("出力インターフェースが定義されていないモデルです。モデル番号:{}", model_idx))?;
 
-        datbus[0].set_val(1.0);
-        datbus[1].set_val(2.0);
-        
-        let inbus = mdl1.interface_in().unwrap();
-        inbus.connect_to(&datbus, &["data1", "data2"], &["test_in1", "test_in2"]).unwrap();
-        
-        mdl2.interface_in().unwrap().connect_to(mdl1.interface_out().unwrap(), 
-                        &["test_out1", "test_out2"], 
-                        &["test_in1", "test_in2"]).unwrap();
+        let signames = if signals.is_empty() {
+            outbus.iter().map(|sig| sig.name()).collect::<Vec<String>>()
+        } else {
+            signals.iter().map(|s| s.to_string()).collect()
+        };
+        let mut sigdefs = Vec::with_capacity(signames.len());
+        for signame in signames.iter() {
+            match outbus.get_by_name(signame.as_str()) {
+                Some(sig) => sigdefs.push(SigDef::new(signame.as_str(), sig.unit())),
+                None => return Err(anyhow!("モデルの出力信号が見つかりません。モデル番号:{}, 信号名:{}", model_idx, signame)),
+            }
+        }
 
-        sys.regist_model(mdl1);
-        sys.regist_model(mdl2);
+        let mut rcd = SimRecorder::new(sigdefs)?;
+        let siglist = signames.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
+        rcd.interface_in().unwrap().connect_to(outbus, &siglist, &siglist)?;
 
-        sys.nextstate();
-        
-        let outbus = sys.models[0].interface_out().unwrap();
-        assert_eq!(outbus[0].val(), 2.0);
-        assert_eq!(outbus[1].val(), 1.0);
+        self.regist_recorder(name.clone(), rcd);
+        Ok(name)
+    }
 
-        let outbus = sys.models[1].interface_out().unwrap();
-        assert_eq!(outbus[0].val(), 2.0);
-        assert_eq!(outbus[1].val(), 2.0);
+    /// プローブを登録する（モデル番号は登録順）
+    /// モデルの出力信号signameを記録するレコーダを作成し、レコーダ名recorder_nameで登録する（記録する信号名はsignameのまま）
+    /// レコーダは信号の接続先を一つ追加するだけなので、既に他のモデルに接続されている信号もその接続を変えずに記録できる
+    /// 例：sys.probe(plant_idx, "y", "plant_y")?; sys.run(); sys.get_result("plant_y")?.get_signal("y")
+    pub fn probe(&mut self, model_idx: usize, signame: &str, recorder_name: impl Into<String>) -> anyhow::Result<()> {
+        let recorder_name = recorder_name.into();
+        if self.recorders.contains_key(&recorder_name) {
+            return Err(anyhow!("レコーダが既に登録されています。レコーダ名:{}", recorder_name));
+        }
+        let model = self.models.get(model_idx).ok_or_else(|| anyhow!("モデル番号が範囲外です。モデル番号:{}（登録数:{}）", model_idx, self.models.len()))?;
+        let outbus = model.interface_out().ok_or_else(|| anyhow!("出力インターフェースが定義されていないモデルです。モデル番号:{}", model_idx))?;
+        let signal = outbus.get_by_name(signame)
+                           .ok_or_else(|| anyhow!("モデルの出力信号が見つかりません。モデル番号:{}, 信号名:{}", model_idx, signame))?;
 
-        sys.run();
-        
+        let mut rcd = SimRecorder::new(vec![SigDef::new(signame, signal.unit())])?;
+        rcd.interface_in().unwrap().connect_to(outbus, &[signame], &[signame])?;
+
+        self.regist_recorder(recorder_name.clone(), rcd);
+        self.probes.push(Probe { model_idx, signame: signame.to_string(), recorder: recorder_name });
+        Ok(())
+    }
+
+    /// 登録済みのプローブ（登録順）
+    pub fn probes(&self) -> &[Probe] {
+        &self.probes
+    }
+
+    /// 実行時アサーションを登録する（モデル番号は登録順）
+    /// 初期化後と各ステップの後にモデルの出力信号signameの値をpredicateで確認し、満たさない場合は直ちに実行を中断してエラーを返す
+    /// エラーには時刻・ステップ・違反した値・同じモデルの他の出力と内部状態の値を含める。
+    /// 中断後もモデルは違反したステップの状態を保持しているため、原因の調査に使用できる（記録のみ行う発散監視とは異なりその場で止める）
+    /// 例：sys.add_assertion(tank_idx, "level", |v| v <= 10.0, "タンク水位が上限を超えました")
+    pub fn add_assertion(&mut self, model_idx: usize, signame: &str, predicate: impl Fn(f64) -> bool + 'a, message: impl Into<String>) -> anyhow::Result<()> {
+        let model = self.models.get(model_idx).ok_or_else(|| anyhow!("モデル番号が範囲外です。モデル番号:{}（登録数:{}）", model_idx, self.models.len()))?;
+        let signal = model.interface_out()
+                          .and_then(|outbus| outbus.get_by_name(signame))
+                          .ok_or_else(|| anyhow!("モデルの出力信号が見つかりません。モデル番号:{}, 信号名:{}", model_idx, signame))?;
+
+        self.assertions.push(SimAssertion {
+            model_idx,
+            signal: signal.clone(),
+            predicate: Box::new(predicate),
+            message: message.into(),
+        });
+        Ok(())
+    }
+
+    /// 信号の変化を購読する（モデル番号は登録順）
+    /// 各ステップの後にモデルの出力信号signameの値を確認し、前回通知した値（初回は初期値）からの変化量がdeltaを超えた場合にcallbackを呼び出す
+    /// 変化量は前回通知した値を基準とするため、ゆっくりした変化も累積してdeltaを超えた時点で通知される。delta = 0.0の場合は値が変わるたびに通知する
+    /// 変化があった時だけ記録する疎なログや、GUIの更新に使用する
+    /// 例：sys.subscribe(tank_idx, "level", 0.1, |c| println!("{}[s]: {} -> {}", c.time, c.old, c.new))
+    pub fn subscribe(&mut self, model_idx: usize, signame: &str, delta: f64, callback: impl FnMut(&SignalChange) + 'a) -> anyhow::Result<()> {
+        let valid = delta >= 0.0 && delta.is_finite();
+        if !valid {
+            return Err(anyhow!("変化量の閾値は0以上の有限値で設定してください。delta = {}", delta));
+        }
+        let model = self.models.get(model_idx).ok_or_else(|| anyhow!("モデル番号が範囲外です。モデル番号:{}（登録数:{}）", model_idx, self.models.len()))?;
+        let signal = model.interface_out()
+                          .and_then(|outbus| outbus.get_by_name(signame))
+                          .ok_or_else(|| anyhow!("モデルの出力信号が見つかりません。モデル番号:{}, 信号名:{}", model_idx, signame))?;
+
+        self.subscriptions.push(SignalSubscription {
+            model_idx,
+            signal: signal.clone(),
+            delta,
+            last: signal.val(),
+            callback: Box::new(callback),
+        });
+        Ok(())
+    }
+
+    /// 購読している信号の変化を確認し、変化量が閾値を超えたものを通知する
+    fn notify_subscribers(&mut self) {
+        let time = self.sim_time.time();
+        for sub in self.subscriptions.iter_mut() {
+            let new = sub.signal.val();
+            if (new - sub.last).abs() > sub.delta {
+                (sub.callback)(&SignalChange { model_idx: sub.model_idx, time, old: sub.last, new });
+                sub.last = new;
+            }
+        }
+    }
+
+    /// 実行時アサーションを確認する（最初に違反したアサーションのエラーを返す）
+    fn check_assertions(&self) -> anyhow::Result<()> {
+        let Some(asrt) = self.assertions.iter().find(|asrt| !(asrt.predicate)(asrt.signal.val())) else {
+            return Ok(());
+        };
+
+        let mdl = &self.models[asrt.model_idx];
+        let outputs = mdl.interface_out().map_or(Vec::new(), |outbus| outbus.iter().map(|sig| format!("  {}", sig)).collect());
+        let states = mdl.internal_states().into_iter().map(|(name, val)| format!("  {}: {}", name, val)).collect::<Vec<String>>();
+        Err(anyhow!("アサーションに違反しました。: {}\n時刻{}[s]（ステップ{}）: モデル{}({})の\"{}\" = {}\n出力信号:\n{}\n内部状態:\n{}",
+            asrt.message, self.sim_time.time(), self.sim_time.step(), asrt.model_idx, self.model_types[asrt.model_idx], asrt.signal.name(), asrt.signal.val(),
+            outputs.join("\n"), if states.is_empty() { "  なし".to_string() } else { states.join("\n") }))
+    }
+
+    /// 現在の実行状態
+    pub fn run_state(&self) -> RunState {
+        self.state
+    }
+
+    /// 実行状態を未実行に戻す（実行済みのシステムを再実行する時に呼び出す）
+    /// モデル・レコーダの状態は次の実行開始時の初期化処理で初期値に戻る
+    pub fn reset(&mut self) {
+        self.state = RunState::Uninitialized;
+        self.manual_start = None;
+    }
+
+    /// 実行完了後のレコーダを取得する（実行前・実行中はエラー）
+    /// 記録データを参照する時はget_recorderではなくこちらを使うと、未実行のシステムのデータを誤って参照することを防げる
+    pub fn get_result(&self, name: &str) -> anyhow::Result<&SimRecorder> {
+        if self.state != RunState::Finished {
+            return Err(anyhow!("シミュレーションが実行完了していません。実行状態:{:?}", self.state));
+        }
+        self.recorders.get(name).ok_or_else(|| anyhow!("レコーダが見つかりません。レコーダ名:{}", name))
+    }
+
+    pub fn get_recorder(&mut self, name: impl Into<String>) -> anyhow::Result<&mut SimRecorder> {
+        let rcd_name = name.into();
+        let rcd = self.recorders.get_mut(&rcd_name);
+
+        match rcd {
+            Some(r) => Ok(r),
+            None => Err(anyhow!("レコーダが見つかりません。レコーダ名:{}", &rcd_name))
+        }
+    }
+
+    /// モデルの接続関係を検証する
+    /// 複数のモデルが同じ信号を出力している（書き込み元が複数ある）場合、遅れの無い自己依存がある場合（check_self_loops）はエラーとする
+    /// モデルの入力が未接続の場合もエラーとする（ModelCore::optional_inputsで宣言された入力は除く）
+    /// set_feedback_checkで有効にした場合は、直達のあるモデルだけのフィードバックループ（check_feedback_loops）もエラーとする
+    pub fn validate(&mut self) -> anyhow::Result<()> {
+        let mut writers: HashMap<*const (), (usize, String)> = HashMap::new(); // 信号の実体 -> (モデル番号, 信号名)
+        let mut errlist: Vec<String> = Vec::new();
+
+        for (mdl_idx, mdl) in self.models.iter().enumerate() {
+            if let Some(outbus) = mdl.interface_out() {
+                for sig in outbus.iter() {
+                    let key = Rc::as_ptr(sig.sig()) as *const ();
+                    match writers.get(&key) {
+                        Some((other_idx, other_name)) => {
+                            errlist.push(format!("  モデル{}の\"{}\"とモデル{}の\"{}\"", other_idx, other_name, mdl_idx, sig.name()));
+                        },
+                        None => {
+                            writers.insert(key, (mdl_idx, sig.name()));
+                        }
+                    }
+                }
+            }
+        }
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("複数のモデルが同じ信号に書き込んでいます。（モデル番号は登録順）\n{}", errlist.join("\n")));
+        }
+
+        // レコーダが記録する内部状態の存在確認
+        for (rcd_name, rcd) in self.recorders.iter() {
+            for (mdl_idx, state) in rcd.state_subscriptions() {
+                let found = self.models.get(*mdl_idx).is_some_and(|mdl| mdl.internal_states().iter().any(|(n, _)| n == state));
+                if !found {
+                    errlist.push(format!("  レコーダ{}: モデル{}の内部状態\"{}\"", rcd_name, mdl_idx, state));
+                }
+            }
+        }
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("記録対象の内部状態が見つかりません。（モデル番号は登録順）\n{}", errlist.join("\n")));
+        }
+
+        // 入力の未接続確認（省略可能な入力は除く）
+        for (mdl_idx, mdl) in self.models.iter_mut().enumerate() {
+            let optional = mdl.optional_inputs();
+            for (name, _) in mdl.connection_report().into_iter().filter(|(name, src)| src.is_none() && !optional.contains(name)) {
+                errlist.push(format!("  モデル{}の\"{}\"", mdl_idx, name));
+            }
+        }
+
+        if !errlist.is_empty() {
+            return Err(anyhow!("未接続の入力があります。（モデル番号は登録順）\n{}", errlist.join("\n")));
+        }
+
+        self.check_self_loops()?;
+        if self.feedback_check {
+            self.check_feedback_loops()?;
+        }
+
+        Ok(())
+    }
+
+    /// 全モデルを1ステップ進める（モデルが失敗してエラーとなる場合はパニックする。try_nextstateの説明を参照）
+    pub fn nextstate(&mut self) {
+        if let Err(e) = self.try_nextstate() {
+            panic!("{}", e);
+        }
+    }
+
+    /// 全モデルを1ステップ進める
+    /// FailurePolicy::Abortでモデルが失敗した場合、またはモデルがステップのエラー（ModelCore::take_step_error）を返した場合は、
+    /// そのステップの結果をレコーダに記録せずにエラーを返す（FailurePolicy::Substituteでは失敗を記録して継続する）
+    fn try_nextstate(&mut self) -> anyhow::Result<()> {
+        // 各モデルを1ステップ進める（run_subset実行中は対象のモデルのみ）
+        // resolve_orderを実行済みの場合はその実行順で進める
+        let order = self.order.clone().unwrap_or_else(|| (0..self.models.len()).collect());
+        if self.profiling {
+            // 初期化前に呼び出された場合や、初期化後にモデルを追加登録した場合に計測領域を合わせる
+            self.stats.model_times.resize(self.models.len(), Duration::ZERO);
+        }
+        for &idx in order.iter() {
+            if let Some(subset) = &self.subset {
+                if !subset[idx] {
+                    continue;
+                }
+            }
+            if self.has_failed(idx) {
+                continue;
+            }
+
+            if self.profiling {
+                let start = Instant::now();
+                self.step_model(idx)?;
+                self.stats.model_times[idx] += start.elapsed();
+            } else {
+                self.step_model(idx)?;
+            }
+        }
+        for &idx in order.iter() {
+            if self.subset.as_ref().is_some_and(|subset| !subset[idx]) || self.has_failed(idx) {
+                continue;
+            }
+            self.models[idx].post_step(&self.sim_time);
+        }
+        self.stats.steps += 1;
+        // 登録してあるレコーダーに結果を格納する
+        self.recorders.iter_mut().for_each(|(_name, rcd)| {
+            rcd.nextstate(&self.sim_time);
+            rcd.record_states(&self.models);
+        });
+        self.event_recorders.iter_mut().for_each(|(_name, rcd)| rcd.nextstate(&self.sim_time));
+
+        Ok(())
+    }
+
+    /// モデルを1ステップ進める（FailurePolicyがPropagate以外の時はパニックを捕捉して失敗を記録する）
+    /// モデルがステップのエラーを返した場合（ModelCore::take_step_error）は、FailurePolicyによらず失敗として記録する
+    fn step_model(&mut self, idx: usize) -> anyhow::Result<()> {
+        let mdl = &mut self.models[idx];
+        let sim_time = &self.sim_time;
+        let result = if self.failure_policy == FailurePolicy::Propagate {
+            mdl.nextstate(sim_time);
+            Ok(())
+        } else {
+            panic::catch_unwind(AssertUnwindSafe(|| mdl.nextstate(sim_time)))
+        };
+
+        let message = match result {
+            Ok(()) => match mdl.take_step_error() {
+                Some(e) => format!("{:#}", e),
+                None => return Ok(()),
+            },
+            Err(payload) => payload.downcast_ref::<&str>().map(|s| s.to_string())
+                                   .or_else(|| payload.downcast_ref::<String>().cloned())
+                                   .unwrap_or_else(|| "（メッセージなし）".to_string()),
+        };
+        let failure = ModelFailure {
+            model_idx: idx,
+            model_type: self.model_types[idx],
+            step: self.sim_time.step(),
+            time: self.sim_time.time(),
+            message,
+        };
+        eprintln!("{}", failure);
+        self.failures.push(failure.clone());
+
+        match self.failure_policy {
+            FailurePolicy::Substitute(value) => {
+                if let Some(outbus) = self.models[idx].interface_out() {
+                    outbus.iter().for_each(|sig| sig.clone().set_val(value)); // cloneした信号は値を共有する
+                }
+                Ok(())
+            },
+            _ => Err(anyhow!("{}\n失敗前のステップまでの記録データは保持しています。", failure)),
+        }
+    }
+
+    /// 直近の実行でモデルが失敗したか（FailurePolicy::Substituteで以降のステップを進めないモデルの判定）
+    fn has_failed(&self, idx: usize) -> bool {
+        self.failures.iter().any(|f| f.model_idx == idx)
+    }
+
+    /// 固定容量のレコーダの記録数が容量を超えていないか確認する
+    fn check_recorders(&self) -> anyhow::Result<()> {
+        for (rcd_name, rcd) in self.recorders.iter() {
+            rcd.check_capacity().map_err(|e| anyhow!("時刻{}[s]（ステップ{}）: レコーダ{}: {}", self.sim_time.time(), self.sim_time.step(), rcd_name, e))?;
+        }
+        Ok(())
+    }
+
+    /// 発散監視の状態を初期化する（レコーダの初期化後に呼び出す）
+    fn init_monitor(&mut self) {
+        self.monitor_state.clear();
+        if let Some(monitor) = &self.monitor {
+            for (rcd_name, rcd) in self.recorders.iter() {
+                for (name, init) in rcd.latest_values() {
+                    self.monitor_state.insert((rcd_name.clone(), name), (monitor.limit(init), 0));
+                }
+            }
+        }
+    }
+
+    /// 記録した最新値が閾値を超え続けていないか確認する
+    fn check_divergence(&mut self) -> anyhow::Result<()> {
+        let monitor = match &self.monitor {
+            Some(monitor) => monitor,
+            None => return Ok(()),
+        };
+
+        for (rcd_name, rcd) in self.recorders.iter() {
+            for (name, val) in rcd.latest_values() {
+                // ウォームアップ中で初期化時に記録が無かった系列は、最初に記録した値を初期値とする
+                let (limit, count) = self.monitor_state.entry((rcd_name.clone(), name.clone())).or_insert_with(|| (monitor.limit(val), 0));
+                if val.is_nan() || val.abs() > *limit {
+                    *count += 1;
+                } else {
+                    *count = 0;
+                }
+
+                if *count >= monitor.sustain_steps {
+                    return Err(anyhow!("シミュレーションが発散しました。時刻{}[s]（ステップ{}）: レコーダ{}の\"{}\" = {}が閾値{}を{}ステップ連続で超えています。",
+                        self.sim_time.time(), self.sim_time.step(), rcd_name, name, val, limit, count));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// シミュレーションを実行する（接続の検証エラー・発散時はパニックする）
+    pub fn run(&mut self) {
+        if let Err(e) = self.try_run() {
+            panic!("{}", e);
+        }
+    }
+
+    /// シミュレーションを実行する
+    /// 接続の検証エラー、実行時アサーションの違反、発散監視で発散を検出した場合、または固定容量のレコーダの容量を超えた場合はエラーを返す
+    /// （違反・発散・容量超過時は終了処理を行ってから中断する）
+    /// 実行済み（または実行が中断された）システムはreset()を呼び出すまで再実行できない
+    pub fn try_run(&mut self) -> anyhow::Result<()> {
+        self.run_with(|_| Ok(false))
+    }
+
+    /// 条件condを満たすまで（またはend_timeに達するまで）シミュレーションを実行し、停止した時刻[s]を返す
+    /// condは初期化後と各ステップの後に呼び出す（レコーダの値はpeek_signalで参照する）。条件を満たした時点で終了処理を行って実行完了とする
+    /// エラーの扱いはtry_runと同じ
+    pub fn run_until<F>(&mut self, cond: F) -> anyhow::Result<f64>
+        where F: Fn(&SimTime, &Self) -> bool
+    {
+        self.run_with(|sys| Ok(cond(&sys.sim_time, sys)))?;
+        Ok(self.sim_time.time())
+    }
+
+    /// レコーダに記録した信号（内部状態を含む）の最新値（記録が無い場合はNone）
+    pub fn peek_signal(&self, recorder: &str, signame: &str) -> Option<f64> {
+        self.recorders.get(recorder)?.get_signal(signame)?.last().copied()
+    }
+
+    /// シミュレーションを実行し、各ステップの結果をチャネルで送信する（初期値を含む）
+    /// 送信する内容は(時刻, 全レコーダの最新値)で、最新値の名前は"{レコーダ名}.{信号名}"（レコーダ名の順）とする
+    /// 受信側が切断された場合は終了処理を行ってからエラーを返す。その他のエラーはtry_runと同じ
+    pub fn run_streaming(&mut self, tx: Sender<(f64, Vec<(String, f64)>)>) -> anyhow::Result<()> {
+        self.run_with(|sys| {
+            tx.send((sys.sim_time.time(), sys.snapshot()))
+              .map_err(|_| anyhow!("受信側が切断されたため、時刻{}[s]でシミュレーションを中断しました。", sys.sim_time.time()))?;
+            Ok(false)
+        })
+    }
+
+    /// 全レコーダの最新値（"{レコーダ名}.{信号名}", 値）をレコーダ名の順に返す
+    fn snapshot(&self) -> Vec<(String, f64)> {
+        let mut rcd_names = self.recorders.keys().collect::<Vec<&String>>();
+        rcd_names.sort(); // HashMapの順序に依存しないようにする
+        rcd_names.into_iter()
+                 .flat_map(|name| self.recorders[name].latest_values().into_iter().map(move |(sig, val)| (format!("{}.{}", name, sig), val)))
+                 .collect()
+    }
+
+    /// シミュレーションを実行する（初期化後と各ステップの後にon_stepを呼び出す）
+    /// on_stepがtrueを返した場合は終了処理を行って正常に終了し、エラーを返した場合は終了処理を行ってから中断する
+    fn run_with<F>(&mut self, mut on_step: F) -> anyhow::Result<()>
+        where F: FnMut(&Self) -> anyhow::Result<bool>
+    {
+        let start = self.begin()?;
+        match self.check_assertions().and_then(|_| on_step(self)) {
+            Ok(false) => {},
+            Ok(true) => {
+                self.abort(start);
+                return Ok(());
+            },
+            Err(e) => {
+                self.abort(start);
+                return Err(e);
+            },
+        }
+
+        // シミュレーション実行処理 
+        let print_interval = self.sim_time.step_num() / 10;
+        let mut print_cnt = 0;
+        let mut progress_cnt = 0; // 進捗カウンタ
+        
+        while let Some(_s) = self.sim_time.next() {
+            print_cnt += 1;
+            if print_cnt >= print_interval {
+                progress_cnt += 1;
+                print_cnt = 0;
+                println!("processing now ... {}%)\n", progress_cnt * 10);
+            }
+            match self.advance().and_then(|_| on_step(self)) {
+                Ok(false) => {},
+                Ok(true) => break,
+                Err(e) => {
+                    self.abort(start);
+                    return Err(e);
+                },
+            }
+        }
+        
+        // 終了処理
+        self.finalize();
+        self.stats.elapsed = start.elapsed();
+        self.state = RunState::Finished;
+
+        Ok(())
+    }
+
+    /// 実行状態を確認し、接続の検証と初期化処理を行って実行中の状態にする（戻り値は実行開始時刻）
+    fn begin(&mut self) -> anyhow::Result<Instant> {
+        match self.state {
+            RunState::Uninitialized => {},
+            RunState::Running => return Err(anyhow!("前回の実行が正常に終了していません。reset()を呼び出してから実行してください。")),
+            RunState::Finished => return Err(anyhow!("シミュレーションは実行済みです。再実行する場合はreset()を呼び出してください。")),
+        }
+
+        // 接続の検証
+        self.validate()?;
+
+        // 初期化処理
+        let start = Instant::now();
+        self.state = RunState::Running;
+        self.initialize();
+        Ok(start)
+    }
+
+    /// 全モデルを1ステップ進め、信号の変化を通知して実行時アサーション・発散監視・レコーダの容量を確認する（時刻は呼び出し側で進める）
+    fn advance(&mut self) -> anyhow::Result<()> {
+        self.try_nextstate()?;
+        self.notify_subscribers();
+        self.check_assertions()?;
+        self.check_divergence()?;
+        self.check_recorders()
+    }
+
+    /// 手動実行を開始する（接続の検証・時刻とモデルの初期化を行う）
+    /// 以降はstep()で1ステップずつ進め、stop()で終了処理を行う。REPLやGUIから対話的に実行する時に使用する
+    /// 実行状態の扱いとエラーはtry_runと同じ（初期値で実行時アサーションに違反した場合は終了処理を行ってからエラーを返す）
+    pub fn start(&mut self) -> anyhow::Result<()> {
+        let start = self.begin()?;
+        if let Err(e) = self.check_assertions() {
+            self.abort(start);
+            return Err(e);
+        }
+        self.manual_start = Some(start);
+        Ok(())
+    }
+
+    /// 手動実行中のシステムを1ステップ進め、進めた後の時刻を返す（end_timeに達している場合は何もせずNoneを返す）
+    /// start()の前やstop()の後に呼び出した場合はエラーとする
+    /// モデルの失敗・実行時アサーションの違反・発散・レコーダの容量超過時は終了処理を行ってからエラーを返す
+    pub fn step(&mut self) -> anyhow::Result<Option<SimTime>> {
+        let start = self.manual_start.ok_or_else(|| anyhow!("手動実行が開始されていません。start()を呼び出してから実行してください。"))?;
+        if self.sim_time.next().is_none() {
+            return Ok(None);
+        }
+
+        if let Err(e) = self.advance() {
+            self.manual_start = None;
+            self.abort(start);
+            return Err(e);
+        }
+        Ok(Some(self.sim_time))
+    }
+
+    /// 手動実行を終了する（終了処理を行って実行完了の状態にする）。start()の前に呼び出した場合はエラーとする
+    pub fn stop(&mut self) -> anyhow::Result<()> {
+        let start = self.manual_start.take().ok_or_else(|| anyhow!("手動実行が開始されていません。"))?;
+        self.abort(start);
+        Ok(())
+    }
+
+    /// 実行を中断・早期終了する（終了処理を行って実行完了の状態にする）
+    fn abort(&mut self, start: Instant) {
+        self.finalize();
+        self.stats.elapsed = start.elapsed();
+        self.state = RunState::Finished;
+    }
+
+    /// 指定したモデルのみを進めてシミュレーションを実行する（モデル番号は登録順）
+    /// 全モデルを初期化した上で、対象外のモデルは初期化時の出力を保持したまま進めない。
+    /// 大きなシステムの一部を既知の入力に対して単体で検証する時に使用する
+    pub fn run_subset(&mut self, model_indices: &[usize]) -> anyhow::Result<()> {
+        let mut subset = vec![false; self.models.len()];
+        for &idx in model_indices {
+            match subset.get_mut(idx) {
+                Some(flag) => *flag = true,
+                None => return Err(anyhow!("モデル番号{}のモデルは登録されていません。登録数:{}", idx, self.models.len())),
+            }
+        }
+
+        // 対象外のモデルの出力はソルバの中間段でも現在値を保持させる
+        let set_hold = |models: &[Box<dyn ModelCore + 'a>], subset: &[bool], hold: bool| {
+            models.iter().zip(subset.iter()).filter(|(_, active)| !**active).for_each(|(mdl, _)| {
+                if let Some(outbus) = mdl.interface_out() {
+                    outbus.iter().for_each(|sig| sig.set_hold(hold));
+                }
+            });
+        };
+
+        set_hold(&self.models, &subset, true);
+        self.subset = Some(subset);
+        let result = self.try_run();
+        if let Some(subset) = self.subset.take() {
+            set_hold(&self.models, &subset, false);
+        }
+
+        result
+    }
+
+    /// 時間を逆向きに進めてシミュレーションを実行する（随伴法による感度解析など向け）
+    /// end_timeからstart_timeへ-delta_t刻みで進むため、微分方程式モデルは負の刻み幅で積分される
+    /// 各モデルの初期状態は終端条件（end_timeでの状態）として扱われる
+    pub fn run_backward(&mut self) {
+        let forward_time = self.sim_time;
+        self.sim_time = forward_time.reversed();
+
+        self.run();
+
+        self.sim_time = forward_time;
+    }
+
+    fn initialize(&mut self) {
+        println!("Simulation Initializing Now ...\n");
+        // 時刻の初期化
+        self.sim_time.reset();
+        // 実行統計・失敗記録の初期化
+        self.stats = RunStats {
+            model_times: if self.profiling { vec![Duration::ZERO; self.models.len()] } else { Vec::new() },
+            ..Default::default()
+        };
+        self.failures.clear();
+        // モデルの初期化
+        self.models.iter_mut().for_each(|mdl| mdl.initialize(&self.sim_time));
+        self.models.iter_mut().for_each(|mdl| mdl.post_step(&self.sim_time));
+        // レコーダの初期化
+        self.recorders.iter_mut().for_each(|(_name, rcd)| {
+            rcd.initialize(&self.sim_time);
+            rcd.record_states(&self.models);
+        });
+        self.event_recorders.iter_mut().for_each(|(_name, rcd)| rcd.initialize(&self.sim_time));
+        // 発散監視の初期化
+        self.init_monitor();
+        // 信号の変化の購読の初期化（初期値を基準とする）
+        self.subscriptions.iter_mut().for_each(|sub| sub.last = sub.signal.val());
+    }
+
+    fn finalize(&mut self) {
+        println!("Simulation Finalizing Now ...\n");
+        // モデルのファイナライズ
+        self.models.iter_mut().for_each(|mdl| mdl.finalize());
+        // レコーダのファイナライズ (特に処理はないが将来処理を追加した時のために呼び出し)
+        self.recorders.iter_mut().for_each(|(_name, rcd)| rcd.finalize());
+        self.event_recorders.iter_mut().for_each(|(_name, rcd)| rcd.finalize());
+
+    }
+}
+
+/// 収束性評価（刻み幅を変えた時に結果が収束するかの確認）を行う
+/// build_systemに刻み幅delta_t, delta_t/2, delta_t/4, ...（levels水準）を渡してSimSystemを作成・実行し、
+/// レコーダrecorder_nameの信号signameの最終値を比較する。収束次数はConvergenceStudy::observed_orderで確認する
+/// 刻み幅によって終了時刻がずれる（最終時刻の差が最小刻み幅の半分を超える）場合はエラーとする
+pub fn convergence_study<'a, F>(mut build_system: F, delta_t: f64, levels: usize, recorder_name: &str, signame: &str) -> anyhow::Result<ConvergenceStudy>
+where
+    F: FnMut(f64) -> anyhow::Result<SimSystem<'a>>,
+{
+    if levels < 2 {
+        return Err(anyhow!("収束性評価の水準数は2以上で設定してください。水準数:{}", levels));
+    }
+    if delta_t <= 0.0 {
+        return Err(anyhow!("収束性評価の刻み幅は正の値で設定してください。刻み幅:{}", delta_t));
+    }
+
+    let delta_ts = (0..levels).map(|k| delta_t / 2f64.powi(k as i32)).collect::<Vec<f64>>();
+    let mut values = Vec::with_capacity(levels);
+    let mut end_times = Vec::with_capacity(levels);
+    for dt in delta_ts.iter() {
+        let mut sys = build_system(*dt)?;
+        sys.try_run()?;
+
+        let rcd = sys.get_recorder(recorder_name)?;
+        let sig = rcd.get_signal(signame).ok_or_else(|| anyhow!("信号が見つかりません。信号名:{}", signame))?;
+        match (rcd.get_time().last(), sig.last()) {
+            (Some(t), Some(v)) => {
+                end_times.push(*t);
+                values.push(*v);
+            },
+            _ => return Err(anyhow!("信号が記録されていません。信号名:{}", signame)),
+        }
+    }
+
+    let finest = delta_ts[levels - 1];
+    if end_times.iter().any(|t| (t - end_times[0]).abs() > finest * 0.5) {
+        return Err(anyhow!("刻み幅によって終了時刻が異なります。終了時刻:{:?}", end_times));
+    }
+
+    Ok(ConvergenceStudy { delta_t: delta_ts, values })
+}
+
+/// 未実行のモデルの中でstartを含む接続のループを探す（ループ上のモデルを接続元から順に返す）
+fn find_cycle(deps: &[Vec<usize>], done: &[bool], start: usize) -> Option<Vec<usize>> {
+    // startから接続元をたどり、startに戻る経路を深さ優先で探す
+    let mut stack = vec![(start, 0usize)];
+    let mut visited = vec![false; deps.len()];
+    visited[start] = true;
+
+    while let Some((node, edge)) = stack.last_mut() {
+        let node = *node;
+        match deps[node].get(*edge) {
+            Some(&src) => {
+                *edge += 1;
+                if done[src] {
+                    continue;
+                }
+                if src == start {
+                    let mut cycle = stack.iter().map(|(j, _)| *j).collect::<Vec<usize>>();
+                    cycle.reverse(); // 接続元 -> 接続先の順にする
+                    return Some(cycle);
+                }
+                if !visited[src] {
+                    visited[src] = true;
+                    stack.push((src, 0));
+                }
+            },
+            None => { stack.pop(); },
+        }
+    }
+    None
+}
+
+/* 逆に煩雑になるので実装しない
+impl<'a> From<Vec<Box<dyn ModelCore>>> for SimSystem<'a>
+{
+    fn from(mdl_list: Vec<Box<dyn ModelCore>>) -> Self
+    {
+        Self {
+            sim_time: SimTime { time: 0.0, step: 0, delta_t: DEFAULT_DELTA_T, start_time: 0.0, end_time: 1.0 },
+            models: mdl_list,
+            recorders: HashMap::new(),
+        }
+    }
+}*/
+
+#[cfg(test)]
+mod sim_bus_test {
+    use super::{*};
+    use crate::simcore::sim_model::test_models::{*};
+    use crate::simcore::sim_signal::signal::{*};
+    use crate::simcore::sim_signal::bus::{*};
+    use crate::simcore::sim_model::{controller_models::PIDController, de_models::{SolverType, SpaceStateModel}};
+    use crate::simcore::sim_model::{source_models::{ConstantFunc, RampFunc}, model_core::connect_models, sink_models::SimRecorder, basic_models::Gain};
+
+    #[test]
+    fn system_regist_test() {
+        let mut sys = SimSystem::new(0.0, 10.0, 0.01);
+        
+        let mut mdl1 = TestModel::new();
+        let mut mdl2 = TestModel::new();
+
+        let mut datbus = Bus::try_from(vec![
+            SigDef::new("data1", "A"),
+            SigDef::new("data2", "V"),
+        ]).unwrap();
+
+
+        datbus[0].set_val(1.0);
+        datbus[1].set_val(2.0);
+        
+        let inbus = mdl1.interface_in().unwrap();
+        inbus.connect_to(&datbus, &["data1", "data2"], &["test_in1", "test_in2"]).unwrap();
+        
+        mdl2.interface_in().unwrap().connect_to_unchecked(mdl1.interface_out().unwrap(), // TestModelは入出力の単位が異なる
+                        &["test_out1", "test_out2"], 
+                        &["test_in1", "test_in2"]).unwrap();
+
+        sys.regist_model(mdl1);
+        sys.regist_model(mdl2);
+
+        sys.nextstate();
+        
+        let outbus = sys.models[0].interface_out().unwrap();
+        assert_eq!(outbus[0].val(), 2.0);
+        assert_eq!(outbus[1].val(), 1.0);
+
+        let outbus = sys.models[1].interface_out().unwrap();
+        assert_eq!(outbus[0].val(), 2.0);
+        assert_eq!(outbus[1].val(), 2.0);
+
+        sys.run();
+        
+    }
+
+    #[test]
+    fn simtime_step_num_test() {
+        assert_eq!(SimTime::new(0.0, 1.0, 0.1).step_num(), 10); // 1.0 / 0.1 = 9.999...で切り捨てられないこと
+        assert_eq!(SimTime::new(0.0, 0.01, 0.00001).step_num(), 1000);
+        assert_eq!(SimTime::new(0.0, 1.05, 0.1).step_num(), 10);
+        assert_eq!(SimTime::new(1.0, 0.0, 0.1).step_num(), 0);
+        assert_eq!(SimTime::new(0.0, 1.0e30, 1.0e-6).step_num(), usize::MAX);
+    }
+
+    #[test]
+    fn simtime_drift_test() {
+        // 1e6ステップ進めても時刻は開始時刻 + ステップ数 * 刻み幅に一致する（加算の繰り返しでは誤差が累積する）
+        let mut sim_time = SimTime::new(0.5, 0.5 + 1.0e6 * 0.001, 0.001);
+        let mut accumulated = 0.5;
+        let mut count = 0;
+        for (step, time) in sim_time.by_ref() {
+            accumulated += 0.001;
+            count += 1;
+            assert_eq!(step, count);
+            assert_eq!(time, 0.5 + step as f64 * 0.001);
+        }
+        assert_eq!(count as usize, sim_time.step_num());
+        assert!((sim_time.time() - (0.5 + sim_time.step_num() as f64 * 0.001)).abs() < 1e-9);
+        assert!((accumulated - sim_time.time()).abs() > 1e-9); // 加算では誤差が残る
+
+        // 刻み幅0.1で0から1までは10ステップ
+        assert_eq!(SimTime::new(0.0, 1.0, 0.1).count(), 10);
+
+        // 刻み幅を変更した場合はその時点の時刻から新しい刻み幅で進む
+        let mut sim_time = SimTime::new(0.0, 10.0, 0.1);
+        (0..5).for_each(|_| sim_time.nextstate());
+        sim_time.change_delta_t(0.25);
+        sim_time.nextstate();
+        assert!((sim_time.time() - 0.75).abs() < 1e-12);
+        sim_time.reset();
+        sim_time.nextstate();
+        assert_eq!(sim_time.time(), 0.25);
+    }
+
+    #[test]
+    fn simtime_step_test() {
+        let mut sim_time = SimTime::new(0.0, 1.0, 0.1);
+        sim_time.step = u32::MAX as u64;
+        sim_time.nextstate();
+        assert_eq!(sim_time.step(), u32::MAX as u64 + 1);
+
+        sim_time.step = u64::MAX;
+        sim_time.nextstate();
+        assert_eq!(sim_time.step(), u64::MAX);
+    }
+
+    #[test]
+    fn system_validate_test() {
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+
+        let mut mdl1 = PIDController::new(
+            vec![SigDef::new("target", "-"), SigDef::new("current", "-")],
+            vec![SigDef::new("out", "-")],
+            (1.0, 0.0, 0.0),
+            (-1.0, 1.0),
+            SolverType::Euler,
+        ).unwrap();
+        let databus = Bus::try_from(vec![SigDef::new("target", "-"), SigDef::new("current", "-")]).unwrap();
+        mdl1.interface_in().unwrap().connect_to(&databus, &["target", "current"], &["target", "current"]).unwrap();
+        let mdl2 = mdl1.clone(); // 出力信号を共有してしまう
+
+        sys.regist_model(mdl1);
+        assert!(sys.validate().is_ok());
+
+        sys.regist_model(mdl2);
+        assert!(sys.validate().is_err());
+    }
+
+    #[test]
+    fn subscribe_test() {
+        // y = t を刻み幅0.125[s]で1[s]まで実行し、0.3を超える変化ごとに通知する（3ステップごと）
+        let mut changes = Vec::new();
+        {
+            let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+            let idx = sys.regist_model(RampFunc::new(vec![SigDef::new("y", "-")], vec![(0.0, 0.0, false, 0.0, 1.0)]).unwrap());
+            sys.subscribe(idx, "y", 0.3, |c| changes.push(*c)).unwrap();
+            assert!(sys.subscribe(idx, "x", 0.3, |_| {}).is_err()); // 存在しない信号
+            assert!(sys.subscribe(idx + 1, "y", 0.3, |_| {}).is_err()); // 範囲外のモデル番号
+            assert!(sys.subscribe(idx, "y", -1.0, |_| {}).is_err());
+            sys.run();
+        }
+        assert_eq!(changes, vec![
+            SignalChange { model_idx: 0, time: 0.375, old: 0.0, new: 0.375 },
+            SignalChange { model_idx: 0, time: 0.75, old: 0.375, new: 0.75 },
+        ]);
+
+        // delta = 0.0の場合は値が変わるたびに通知し、再実行時は初期値を基準に戻す
+        let mut count = 0;
+        {
+            let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+            let idx = sys.regist_model(RampFunc::new(vec![SigDef::new("y", "-")], vec![(0.0, 0.0, false, 0.0, 1.0)]).unwrap());
+            sys.subscribe(idx, "y", 0.0, |_| count += 1).unwrap();
+            sys.run();
+            sys.reset();
+            sys.run();
+        }
+        assert_eq!(count, 16);
+    }
+
+    #[test]
+    fn probe_test() {
+        // 定数 u = 2 をゲイン3倍のモデルに接続したまま、uをプローブで記録する
+        let mut sys = SimSystem::new(0.0, 1.0, 0.25);
+        let src = ConstantFunc::new(vec![SigDef::new("u", "V")], &[2.0]).unwrap();
+        let mut gain = Gain::new(vec![SigDef::new("u", "V")], vec![SigDef::new("y", "V")], &[3.0]).unwrap();
+        connect_models(&src, &["u"], &mut gain, &["u"]).unwrap();
+        let src_idx = sys.regist_model(src);
+        let gain_idx = sys.regist_model(gain);
+
+        sys.probe(src_idx, "u", "u_probe").unwrap();
+        sys.probe(gain_idx, "y", "y_probe").unwrap();
+        assert!(sys.probe(src_idx, "x", "x_probe").is_err()); // 存在しない信号
+        assert!(sys.probe(gain_idx + 1, "y", "y2_probe").is_err()); // 範囲外のモデル番号
+        assert!(sys.probe(gain_idx, "y", "u_probe").is_err()); // レコーダ名の重複
+        assert_eq!(sys.probes(), &[
+            Probe { model_idx: src_idx, signame: "u".to_string(), recorder: "u_probe".to_string() },
+            Probe { model_idx: gain_idx, signame: "y".to_string(), recorder: "y_probe".to_string() },
+        ]);
+
+        sys.run();
+        let u = sys.get_result("u_probe").unwrap().get_signal("u").unwrap();
+        assert_eq!(u, &[2.0; 5]);
+        let y = sys.get_result("y_probe").unwrap().get_signal("y").unwrap();
+        assert_eq!(&y[1..], &[6.0; 4]); // 既存の接続はそのまま
+    }
+
+    #[test]
+    fn optional_input_validate_test() {
+        // 必須の入力"u"が未接続の場合はエラー、省略可能な入力"bias"は未接続でもよい
+        let src = ConstantFunc::new(vec![SigDef::new("u", "-"), SigDef::new("bias", "-")], &[1.0, 0.5]).unwrap();
+        let mut sys = SimSystem::new(0.0, 0.5, 0.125);
+        sys.regist_model(OptionalInputModel::new());
+        let err = sys.validate().unwrap_err().to_string();
+        assert!(err.contains("未接続の入力があります。"), "{}", err);
+        assert!(err.contains("モデル0の\"u\"") && !err.contains("bias"), "{}", err);
+
+        let mut mdl = OptionalInputModel::new();
+        connect_models(&src, &["u"], &mut mdl, &["u"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+        connect_models(&mdl, &["y"], &mut rcd, &["y"]).unwrap();
+        let mut sys = SimSystem::new(0.0, 0.5, 0.125);
+        sys.regist_model(src);
+        sys.regist_model(mdl);
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+        sys.get_recorder("rcd").unwrap().assert_final_approx("y", 1.0, 1e-12).unwrap(); // biasは既定値0
+
+        // 省略可能な入力を接続した場合は接続元の値を使う
+        let src = ConstantFunc::new(vec![SigDef::new("u", "-"), SigDef::new("bias", "-")], &[1.0, 0.5]).unwrap();
+        let mut mdl = OptionalInputModel::new();
+        connect_models(&src, &["u", "bias"], &mut mdl, &["u", "bias"]).unwrap();
+        let mut step = SimTime::new(0.0, 0.125, 0.125);
+        mdl.initialize(&step);
+        step.next();
+        mdl.nextstate(&step);
+        assert_eq!(mdl.interface_out().unwrap()[0].val(), 1.5);
+    }
+
+    #[test]
+    fn system_run_backward_test() {
+        // dx/dt = -x を終端条件x(1) = e^-1から逆向きに積分すると x(0) = 1 になる
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[0.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::RungeKutta).unwrap();
+        ssm.set_mtrx_a(&[-1.0]).unwrap();
+        ssm.set_mtrx_c(&[1.0]).unwrap();
+        ssm.set_init_state(&[(-1.0f64).exp()]).unwrap();
+
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+        sys.regist_model(input);
+        sys.regist_model(ssm);
+
+        sys.run_backward();
+
+        assert!((sys.models[1].interface_out().unwrap()[0].val() - 1.0).abs() < 1e-6);
+        assert_eq!(sys.sim_time().start_time(), 0.0); // 実行後は元の時間設定に戻っていること
+        assert_eq!(sys.sim_time().delta_t(), 0.01);
+    }
+
+    #[test]
+    fn convergence_study_test() {
+        // dx/dt = -x, x(0) = 1 の x(1) を刻み幅を変えて比較する
+        let build = |solver: SolverType| move |dt: f64| -> anyhow::Result<SimSystem<'static>> {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[0.0])?;
+            let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, solver.clone())?;
+            ssm.set_mtrx_a(&[-1.0])?;
+            ssm.set_mtrx_c(&[1.0])?;
+            ssm.set_init_state(&[1.0])?;
+            connect_models(&input, &["u"], &mut ssm, &["u"])?;
+
+            let mut rcd = SimRecorder::new(vec![SigDef::new("x", "-")])?;
+            connect_models(&ssm, &["x"], &mut rcd, &["x"])?;
+
+            let mut sys = SimSystem::new(0.0, 1.0, dt);
+            sys.regist_model(input);
+            sys.regist_model(ssm);
+            sys.regist_recorder("rcd", rcd);
+            Ok(sys)
+        };
+
+        let euler = convergence_study(build(SolverType::Euler), 0.125, 4, "rcd", "x").unwrap();
+        assert_eq!(euler.values.len(), 4);
+        assert!((euler.observed_order().unwrap() - 1.0).abs() < 0.1);
+        let exact = (-1.0f64).exp();
+        assert!((euler.extrapolated().unwrap() - exact).abs() < (euler.values[3] - exact).abs());
+
+        let rk = convergence_study(build(SolverType::RungeKutta), 0.125, 4, "rcd", "x").unwrap();
+        assert!((rk.observed_order().unwrap() - 4.0).abs() < 0.2);
+        println!("{}", rk);
+
+        assert!(convergence_study(build(SolverType::Euler), 0.125, 1, "rcd", "x").is_err());
+        assert!(convergence_study(build(SolverType::Euler), 0.125, 2, "none", "x").is_err());
+        assert!(convergence_study(build(SolverType::Euler), 0.125, 2, "rcd", "y").is_err());
+    }
+
+    #[test]
+    fn record_model_output_test() {
+        let input = ConstantFunc::new(vec![SigDef::new("u", "V"), SigDef::new("w", "A")], &[2.0, 3.0]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        let idx = sys.regist_model(input);
+
+        assert_eq!(sys.record_model_output(idx, &[]).unwrap(), "model0");
+        assert!(sys.record_model_output(idx, &["u"]).is_err()); // 同じモデルのレコーダは登録済み
+        assert!(sys.record_model_output(1, &[]).is_err());
+        sys.run();
+
+        let rcd = sys.get_recorder("model0").unwrap();
+        assert_eq!(rcd.get_signal("u").unwrap().last(), Some(&2.0));
+        assert_eq!(rcd.get_signal("w").unwrap().last(), Some(&3.0));
+        assert_eq!(rcd.get_signal("u").unwrap().len(), rcd.get_time().len());
+
+        let input = ConstantFunc::new(vec![SigDef::new("u", "V"), SigDef::new("w", "A")], &[2.0, 3.0]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        let idx = sys.regist_model(input);
+        assert!(sys.record_model_output(idx, &["x"]).is_err());
+        sys.record_model_output(idx, &["w"]).unwrap();
+        sys.run();
+        let rcd = sys.get_recorder("model0").unwrap();
+        assert!(rcd.get_signal("u").is_none());
+        assert_eq!(rcd.get_signal("w").unwrap().last(), Some(&3.0));
+    }
+
+    #[test]
+    fn system_record_states_test() {
+        // x'' = -x（出力は位置のみ）の速度を内部状態として記録する
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[0.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 2, SolverType::RungeKutta).unwrap();
+        ssm.set_mtrx_a(&[0.0, 1.0, -1.0, 0.0]).unwrap();
+        ssm.set_mtrx_c(&[1.0, 0.0]).unwrap();
+        ssm.set_init_state(&[1.0, 0.0]).unwrap();
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+        let mut rcd = SimRecorder::new(vec![SigDef::new("x", "-")]).unwrap();
+        connect_models(&ssm, &["x"], &mut rcd, &["x"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+        sys.regist_model(input);
+        let ssm_idx = sys.regist_model(ssm);
+
+        rcd.subscribe_states(ssm_idx, &["x1"]).unwrap();
+        assert!(rcd.subscribe_states(ssm_idx, &["x"]).is_err()); // 信号名と重複
+        sys.regist_recorder("rcd", rcd);
+        sys.run();
+
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let t = rcd.get_time().to_vec();
+        let v = rcd.get_signal("x1").unwrap();
+        assert_eq!(v.len(), t.len());
+        t.iter().zip(v.iter()).for_each(|(t, v)| assert!((v + t.sin()).abs() < 1e-6));
+
+        // 存在しない内部状態を記録しようとすると検証エラー
+        rcd.subscribe_states(ssm_idx, &["x5"]).unwrap();
+        assert!(sys.validate().is_err());
+    }
+
+    #[test]
+    fn system_profiling_test() {
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::RungeKutta).unwrap();
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+        sys.regist_model(input);
+        sys.regist_model(ssm);
+
+        // プロファイリング無効時はモデルごとの時間は計測しない
+        sys.run();
+        assert_eq!(sys.run_stats().steps, 100);
+        assert!(sys.run_stats().model_times.is_empty());
+
+        sys.enable_profiling(true);
+        sys.reset();
+        sys.run();
+        let stats = sys.run_stats();
+        assert_eq!(stats.steps, 100);
+        assert_eq!(stats.model_times.len(), 2);
+
+        let breakdown = stats.model_breakdown();
+        assert_eq!(breakdown.len(), 2);
+        assert!(breakdown[0].1 >= breakdown[1].1);
+        assert!(stats.model_times.iter().sum::<Duration>() <= stats.elapsed);
+        println!("{}", stats);
+
+        // 初期化（start/run）をせずにnextstateを呼び出してもパニックしない
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+        sys.regist_model(input);
+        sys.enable_profiling(true);
+        sys.nextstate();
+        assert_eq!(sys.run_stats().model_times.len(), 1);
+    }
+
+    #[test]
+    fn system_run_subset_test() {
+        // 入力のランプは進めず、初期値1.0を保持したまま積分器だけを進める
+        let input = crate::simcore::sim_model::source_models::RampFunc::new(vec![SigDef::new("u", "-")], vec![(1.0, 0.0, false, 0.0, 1.0)]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::RungeKutta).unwrap();
+        ssm.set_mtrx_b(&[1.0]).unwrap();
+        ssm.set_mtrx_c(&[1.0]).unwrap();
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.01);
+        sys.regist_model(input);
+        let ssm_idx = sys.regist_model(ssm);
+
+        sys.run_subset(&[ssm_idx]).unwrap();
+        assert_eq!(sys.models[0].interface_out().unwrap()[0].val(), 1.0);
+        assert!((sys.models[1].interface_out().unwrap()[0].val() - 1.0).abs() < 1e-9);
+
+        // 通常の実行ではランプも進む（x = t + t^2 / 2）
+        sys.reset();
+        sys.run();
+        assert!((sys.models[1].interface_out().unwrap()[0].val() - 1.5).abs() < 1e-9);
+
+        assert!(sys.run_subset(&[2]).is_err());
+    }
+
+    #[test]
+    fn system_divergence_monitor_test() {
+        // x' = a * x（a > 0で発散）
+        let make_sys = |a: f64| {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[0.0]).unwrap();
+            let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::Euler).unwrap();
+            ssm.set_mtrx_a(&[a]).unwrap();
+            ssm.set_mtrx_c(&[1.0]).unwrap();
+            ssm.set_init_state(&[1.0]).unwrap();
+            connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+            let mut rcd = SimRecorder::new(vec![SigDef::new("x", "-")]).unwrap();
+            connect_models(&ssm, &["x"], &mut rcd, &["x"]).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 10.0, 0.01);
+            sys.regist_model(input);
+            sys.regist_model(ssm);
+            sys.regist_recorder("rcd", rcd);
+            sys.set_divergence_monitor(Some(DivergenceMonitor { abs_limit: Some(100.0), rel_limit: None, sustain_steps: 5 }));
+            sys
+        };
+
+        let mut sys = make_sys(-1.0);
+        assert!(sys.try_run().is_ok());
+
+        // e^t > 100となるt = 4.6[s]付近から5ステップ後に中断する
+        let mut sys = make_sys(1.0);
+        let err = sys.try_run().unwrap_err();
+        assert!(err.to_string().contains("\"x\""));
+        let rcd = sys.get_recorder("rcd").unwrap();
+        let t_end = *rcd.get_time().last().unwrap();
+        assert!(t_end > 4.6 && t_end < 4.8);
+
+        // 相対閾値：初期値1.0の10倍
+        let mut sys = make_sys(1.0);
+        sys.set_divergence_monitor(Some(DivergenceMonitor { abs_limit: None, rel_limit: Some(10.0), sustain_steps: 1 }));
+        assert!(sys.try_run().is_err());
+        assert!(*sys.get_recorder("rcd").unwrap().get_time().last().unwrap() < 2.4);
+    }
+
+    #[test]
+    fn assertion_test() {
+        // x' = x（x = e^t）
+        let make_sys = || {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[0.0]).unwrap();
+            let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::Euler).unwrap();
+            ssm.set_mtrx_a(&[1.0]).unwrap();
+            ssm.set_mtrx_c(&[1.0]).unwrap();
+            ssm.set_init_state(&[1.0]).unwrap();
+            connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 4.0, 0.125);
+            sys.regist_model(input);
+            let idx = sys.regist_model(ssm);
+            (sys, idx)
+        };
+
+        let (mut sys, idx) = make_sys();
+        sys.add_assertion(idx, "x", |x| x < 100.0, "xが上限を超えました").unwrap();
+        assert!(sys.try_run().is_ok());
+
+        // (1.125)^k >= 10 となる k = 20ステップ目（2.5[s]）で中断し、モデルはその時点の状態を保持する
+        let (mut sys, idx) = make_sys();
+        sys.add_assertion(idx, "x", |x| x < 10.0, "xが上限を超えました").unwrap();
+        let err = sys.try_run().unwrap_err().to_string();
+        assert!(err.contains("xが上限を超えました") && err.contains("ステップ20"), "{}", err);
+        assert_eq!(sys.run_state(), RunState::Finished);
+        let x = sys.models[idx].interface_out().unwrap()[0].val();
+        assert_eq!(x, 1.125f64.powi(20));
+
+        // 初期値で違反している場合は1ステップも進めない
+        let (mut sys, idx) = make_sys();
+        sys.add_assertion(idx, "x", |x| x < 1.0, "初期値").unwrap();
+        assert!(sys.try_run().unwrap_err().to_string().contains("ステップ0"));
+
+        let (mut sys, idx) = make_sys();
+        assert!(sys.add_assertion(idx, "unknown", |_| true, "").is_err());
+        assert!(sys.add_assertion(idx + 1, "x", |_| true, "").is_err());
+    }
+
+    #[test]
+    fn system_manifest_test() {
+        let build = |manifest: Option<&SimManifest>, connect_rcd: bool| {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::RungeKutta).unwrap();
+            connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("x_log", "-")]).unwrap();
+            if connect_rcd {
+                connect_models(&ssm, &["x"], &mut rcd, &["x_log"]).unwrap();
+            }
+
+            let mut sys = match manifest {
+                Some(m) => SimSystem::from_manifest(m),
+                None => SimSystem::new(0.0, 2.0, 0.01),
+            };
+            sys.regist_model(input);
+            sys.regist_model(ssm);
+            sys.regist_recorder("rcd", rcd);
+            sys
+        };
+
+        let mut sys = build(None, true);
+        let manifest = sys.manifest();
+        assert_eq!(manifest.models.len(), 2);
+        assert!(manifest.models[1].ends_with("SpaceStateModel"));
+        assert_eq!(manifest.connections, vec![
+            ManifestConnection { src_model: 0, src_signal: "u".to_string(), dst: ManifestTarget::Model(1), dst_signal: "u".to_string() },
+            ManifestConnection { src_model: 1, src_signal: "x".to_string(), dst: ManifestTarget::Recorder("rcd".to_string()), dst_signal: "x_log".to_string() },
+        ]);
+
+        // テキストに保存して読み込むと同じマニフェストになる
+        let loaded = manifest.to_string().parse::<SimManifest>().unwrap();
+        assert_eq!(loaded, manifest);
+
+        // 実行後も登録時の接続関係から同じマニフェストを作成できる（共有参照から呼び出せる）
+        sys.run();
+        let sys_ref = &sys;
+        assert_eq!(sys_ref.manifest(), manifest);
+
+        // 読み込んだマニフェストから同じ構成を作ると一致する
+        let sys2 = build(Some(&loaded), true);
+        sys2.verify_manifest(&loaded).unwrap();
+
+        let sys3 = build(Some(&loaded), false);
+        assert!(sys3.verify_manifest(&loaded).is_err());
+
+        assert!("model\t0\tFoo\n".parse::<SimManifest>().is_err()); // sim_timeが無い
+        assert!("sim_time\t0\t1\n".parse::<SimManifest>().is_err());
+    }
+
+    #[test]
+    fn to_rust_source_test() {
+        let input = ConstantFunc::new(vec![SigDef::new("u1", "V"), SigDef::new("u2", "V")], &[1.0, 2.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("a", "V"), SigDef::new("b", "V")], vec![SigDef::new("x", "m")], 1, SolverType::RungeKutta).unwrap();
+        ssm.set_mtrx_a(&[-2.0]).unwrap();
+        ssm.set_mtrx_b(&[1.0, 0.5]).unwrap();
+        ssm.set_mtrx_c(&[1.0]).unwrap();
+        ssm.set_init_state(&[0.25]).unwrap();
+        connect_models(&input, &["u1", "u2"], &mut ssm, &["a", "b"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("x_log", "m"), SigDef::new("u_log", "V")]).unwrap();
+        connect_models(&ssm, &["x"], &mut rcd, &["x_log"]).unwrap();
+        connect_models(&input, &["u1"], &mut rcd, &["u_log"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 2.0, 0.01);
+        sys.regist_model(input);
+        sys.regist_model(ssm);
+        sys.regist_recorder("scope", rcd);
+
+        assert_eq!(sys.to_rust_source().unwrap(), [
+            "// SimSystem::to_rust_sourceで生成したコード（use mint_sim::prelude::*;を前提とする）",
+            "let model0 = ConstantFunc::new(vec![SigDef::new(\"u1\", \"V\"), SigDef::new(\"u2\", \"V\")], &[1.0, 2.0]).unwrap();",
+            "let mut model1 = {",
+            "    let mut model = SpaceStateModel::new(vec![SigDef::new(\"a\", \"V\"), SigDef::new(\"b\", \"V\")], vec![SigDef::new(\"x\", \"m\")], 1, SolverType::RungeKutta).unwrap();",
+            "    model.set_mtrx_a(&[-2.0]).unwrap();",
+            "    model.set_mtrx_b(&[1.0, 0.5]).unwrap();",
+            "    model.set_mtrx_c(&[1.0]).unwrap();",
+            "    model.set_mtrx_d(&[0.0, 0.0]).unwrap();",
+            "    model.set_init_state(&[0.25]).unwrap();",
+            "    model",
+            "};",
+            "let mut recorder0 = SimRecorder::new(vec![",
+            "    SigDef::new(\"x_log\", \"m\"),",
+            "    SigDef::new(\"u_log\", \"V\"),",
+            "]).unwrap();",
+            "",
+            "connect_models(&model0, &[\"u1\", \"u2\"], &mut model1, &[\"a\", \"b\"]).unwrap();",
+            "connect_models(&model1, &[\"x\"], &mut recorder0, &[\"x_log\"]).unwrap();",
+            "connect_models(&model0, &[\"u1\"], &mut recorder0, &[\"u_log\"]).unwrap();",
+            "",
+            "let mut sys = SimSystem::new(0.0, 2.0, 0.01);",
+            "sys.regist_model(model0);",
+            "sys.regist_model(model1);",
+            "sys.regist_recorder(\"scope\", recorder0);",
+            "",
+        ].join("\n"));
+
+        // コードを生成できないモデルがある場合はエラー
+        sys.regist_model(TestModel::new());
+        let err = sys.to_rust_source().unwrap_err().to_string();
+        assert!(err.contains("モデル2") && err.contains("TestModel"), "{}", err);
+    }
+
+    #[test]
+    fn resolve_order_test() {
+        use crate::simcore::sim_model::closure_models::ClosureModel;
+        use crate::simcore::sim_model::de_models::Integrator;
+
+        let gain = |input: &str, output: &str, k: f64, b: f64| ClosureModel::new(
+            vec![SigDef::new(input, "-")],
+            vec![SigDef::new(output, "-")],
+            |outbus, _| outbus[0].set_val(0.0),
+            move |inbus, outbus, _| outbus[0].set_val(inbus[0].val() * k + b),
+        ).unwrap();
+
+        // src -> g1(x2) -> g2(+1) -> g3(x3) を逆順に登録する
+        let build = || {
+            let src = ConstantFunc::new(vec![SigDef::new("s", "-")], &[1.5]).unwrap();
+            let mut g1 = gain("s", "a", 2.0, 0.0);
+            let mut g2 = gain("a", "b", 1.0, 1.0);
+            let mut g3 = gain("b", "c", 3.0, 0.0);
+            connect_models(&src, &["s"], &mut g1, &["s"]).unwrap();
+            connect_models(&g1, &["a"], &mut g2, &["a"]).unwrap();
+            connect_models(&g2, &["b"], &mut g3, &["b"]).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+            sys.regist_model(g3);
+            sys.regist_model(g2);
+            sys.regist_model(g1);
+            sys.regist_model(src);
+            sys
+        };
+
+        let mut sys = build();
+        sys.initialize();
+        sys.sim_time.next();
+        sys.nextstate();
+        assert_eq!(sys.models[0].interface_out().unwrap()[0].val(), 0.0); // 登録順では1ステップ目に入力の値が伝わらない
+
+        let mut sys = build();
+        assert_eq!(sys.resolve_order().unwrap(), vec![3, 2, 1, 0]);
+        sys.initialize();
+        sys.sim_time.next();
+        sys.nextstate();
+        assert_eq!(sys.models[0].interface_out().unwrap()[0].val(), (1.5 * 2.0 + 1.0) * 3.0);
+
+        // 直達のあるモデルだけのループは代数ループとしてエラー
+        let mut g1 = gain("y", "x", 1.0, 0.0);
+        let mut g2 = gain("x", "y", 0.5, 1.0);
+        connect_models(&g1, &["x"], &mut g2, &["x"]).unwrap();
+        connect_models(&g2, &["y"], &mut g1, &["y"]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(g1);
+        sys.regist_model(g2);
+        let err = sys.resolve_order().unwrap_err().to_string();
+        assert!(err.contains("model0") && err.contains("model1"), "{}", err);
+
+        // ループ内に積分器があれば積分器でループを切る
+        let mut g1 = gain("x", "v", -1.0, 0.0);
+        let mut integ = Integrator::new(RefBus::try_from(vec![SigDef::new("v", "-")]).unwrap(), Bus::try_from(vec![SigDef::new("x", "-")]).unwrap(), SolverType::Euler).unwrap();
+        connect_models(&integ, &["x"], &mut g1, &["x"]).unwrap();
+        connect_models(&g1, &["v"], &mut integ, &["v"]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(g1);
+        sys.regist_model(integ);
+        assert_eq!(sys.resolve_order().unwrap(), vec![1, 0]);
+    }
+
+    #[test]
+    fn self_loop_test() {
+        use crate::simcore::sim_model::closure_models::ClosureModel;
+        use crate::simcore::sim_model::de_models::Integrator;
+
+        // 直達のあるモデルの出力を自身の入力に接続するとエラー
+        let mut gain = ClosureModel::new(
+            vec![SigDef::new("x", "-")],
+            vec![SigDef::new("x", "-")],
+            |outbus, _| outbus[0].set_val(0.0),
+            |inbus, outbus, _| outbus[0].set_val(inbus[0].val() * 0.5 + 1.0),
+        ).unwrap();
+        let out = gain.interface_out().unwrap().clone();
+        gain.interface_in().unwrap().connect_to(&out, &["x"], &["x"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(gain);
+        let err = sys.check_self_loops().unwrap_err().to_string();
+        assert!(err.contains("model0"), "{}", err);
+        assert!(sys.try_run().is_err());
+
+        // 積分器（直達無し）の自己フィードバックは正当な接続
+        let mut integ = Integrator::new(RefBus::try_from(vec![SigDef::new("x", "-")]).unwrap(), Bus::try_from(vec![SigDef::new("x", "-")]).unwrap(), SolverType::Euler).unwrap();
+        let out = integ.interface_out().unwrap().clone();
+        integ.interface_in().unwrap().connect_to(&out, &["x"], &["x"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(integ);
+        assert!(sys.check_self_loops().is_ok());
+        assert!(sys.try_run().is_ok());
+    }
+
+    #[test]
+    fn feedback_check_test() {
+        use crate::simcore::sim_model::closure_models::ClosureModel;
+        use crate::simcore::sim_model::de_models::Integrator;
+
+        let gain = |input: &str, output: &str, k: f64| ClosureModel::new(
+            vec![SigDef::new(input, "-")],
+            vec![SigDef::new(output, "-")],
+            |outbus, _| outbus[0].set_val(0.0),
+            move |inbus, outbus, _| outbus[0].set_val(inbus[0].val() * k),
+        ).unwrap();
+
+        // 直達のあるモデルだけのループ
+        let mut g1 = gain("y", "x", 1.0);
+        let mut g2 = gain("x", "y", 0.5);
+        connect_models(&g1, &["x"], &mut g2, &["x"]).unwrap();
+        connect_models(&g2, &["y"], &mut g1, &["y"]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(g1);
+        sys.regist_model(g2);
+        assert!(sys.validate().is_ok()); // 既定では確認しない
+        sys.set_feedback_check(true);
+        let err = sys.validate().unwrap_err().to_string();
+        assert!(err.contains("model0") && err.contains("model1"), "{}", err);
+        assert!(sys.try_run().is_err());
+
+        // ループ内に積分器（直達無し）があれば良設定
+        let mut g1 = gain("x", "v", -1.0);
+        let mut integ = Integrator::new(RefBus::try_from(vec![SigDef::new("v", "-")]).unwrap(), Bus::try_from(vec![SigDef::new("x", "-")]).unwrap(), SolverType::Euler).unwrap();
+        connect_models(&integ, &["x"], &mut g1, &["x"]).unwrap();
+        connect_models(&g1, &["v"], &mut integ, &["v"]).unwrap();
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        sys.regist_model(g1);
+        sys.regist_model(integ);
+        sys.set_feedback_check(true);
+        assert!(sys.check_feedback_loops().is_ok());
+        assert!(sys.try_run().is_ok());
+    }
+
+    #[test]
+    fn output_dir_test() {
+        let dir = std::env::temp_dir().join("mint_sim_output_dir_test");
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let make_rcd = || {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-")]).unwrap();
+            connect_models(&input, &["u"], &mut rcd, &["u"]).unwrap();
+            (input, rcd)
+        };
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+        let (input1, rcd1) = make_rcd();
+        let (input2, rcd2) = make_rcd();
+        sys.regist_model(input1);
+        sys.regist_model(input2);
+        sys.regist_recorder("rcd1", rcd1);
+        sys.set_output_dir(&dir); // 登録済みのレコーダにも反映する
+        sys.regist_recorder("rcd2", rcd2);
+        sys.run();
+
+        // ファイル名のみの場合は出力先ディレクトリ（無ければ作成）に出力する
+        sys.get_recorder("rcd1").unwrap().export("rcd1.csv").unwrap();
+        sys.get_recorder("rcd2").unwrap().timeplot_all("rcd2.png", (300, 200), (1, 1)).unwrap();
+        assert!(dir.join("rcd1.csv").exists());
+        assert!(dir.join("rcd2.png").exists());
+
+        // ディレクトリを含むパスは出力先ディレクトリより優先する
+        let sub = dir.join("sub");
+        std::fs::create_dir_all(&sub).unwrap();
+        sys.get_recorder("rcd1").unwrap().export(sub.join("full.csv").to_str().unwrap()).unwrap();
+        assert!(sub.join("full.csv").exists());
+        assert!(!dir.join("full.csv").exists());
+    }
+
+    #[test]
+    fn failure_policy_test() {
+        use crate::simcore::sim_model::closure_models::ClosureModel;
+
+        // 時刻0.5[s]を過ぎるとパニックするモデル
+        let make_system = |policy: FailurePolicy| {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut faulty = ClosureModel::new(
+                vec![SigDef::new("u", "-")],
+                vec![SigDef::new("y", "-")],
+                |outbus, _| outbus[0].set_val(0.0),
+                |inbus, outbus, sim_time| {
+                    if sim_time.time() > 0.5 {
+                        panic!("faulty model");
+                    }
+                    outbus[0].set_val(inbus[0].val() * 2.0)
+                },
+            ).unwrap();
+            connect_models(&input, &["u"], &mut faulty, &["u"]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-"), SigDef::new("y", "-")]).unwrap();
+            connect_models(&input, &["u"], &mut rcd, &["u"]).unwrap();
+            connect_models(&faulty, &["y"], &mut rcd, &["y"]).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 1.0, 0.125);
+            sys.regist_model(input);
+            sys.regist_model(faulty);
+            sys.regist_recorder("rcd", rcd);
+            sys.set_failure_policy(policy);
+            sys
+        };
+
+        // 中断：エラーを返し、失敗前のステップまでの記録データが残る
+        let mut sys = make_system(FailurePolicy::Abort);
+        let err = sys.try_run().unwrap_err().to_string();
+        assert!(err.contains("モデル1") && err.contains("faulty model"), "{}", err);
+        assert_eq!(sys.run_state(), RunState::Finished);
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_time(), &[0.0, 0.125, 0.25, 0.375, 0.5]);
+        assert_eq!(rcd.get_signal("y").unwrap()[4], 2.0);
+        assert_eq!(sys.model_failures().len(), 1);
+        assert_eq!(sys.model_failures()[0].model_idx, 1);
+        assert_eq!(sys.model_failures()[0].time, 0.625);
+
+        // 代替値：失敗したモデルの出力を代替値に固定して最後まで実行する
+        let mut sys = make_system(FailurePolicy::Substitute(-1.0));
+        sys.try_run().unwrap();
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_time().len(), 9);
+        assert_eq!(rcd.get_signal("y").unwrap(), &[0.0, 2.0, 2.0, 2.0, 2.0, -1.0, -1.0, -1.0, -1.0]);
+        assert_eq!(rcd.get_signal("u").unwrap()[8], 1.0);
+        assert_eq!(sys.model_failures().len(), 1); // 失敗したモデルは以降進めない
+
+        // 既定ではパニックをそのまま伝播する
+        let mut sys = make_system(FailurePolicy::Propagate);
+        assert!(panic::catch_unwind(AssertUnwindSafe(|| sys.try_run())).is_err());
+        assert_eq!(sys.run_state(), RunState::Running);
+    }
+
+    #[test]
+    fn step_error_test() {
+        use crate::simcore::sim_model::de_models::SolverConfig;
+
+        // Dormand-Prince法で試行回数の上限（1回）までに許容誤差を満たせないモデル
+        let make_system = || {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-")], vec![SigDef::new("x", "-")], 1, SolverType::DormandPrince).unwrap();
+            ssm.set_mtrx_a(&[-1.0]).unwrap();
+            ssm.set_mtrx_b(&[1.0]).unwrap();
+            ssm.set_mtrx_c(&[1.0]).unwrap();
+            ssm.set_solver_config(SolverConfig { max_substeps: 1, ..SolverConfig::with_tolerance(1e-12) }).unwrap();
+            connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+
+            let mut sys = SimSystem::new(0.0, 1.0, 0.5);
+            sys.regist_model(input);
+            sys.regist_model(ssm);
+            sys
+        };
+
+        // 既定のFailurePolicy::Propagateでもパニックせずにエラーを返す
+        let mut sys = make_system();
+        let err = sys.try_run().unwrap_err().to_string();
+        assert!(err.contains("モデル1") && err.contains("Dormand-Prince法"), "{}", err);
+        assert_eq!(sys.run_state(), RunState::Finished);
+        assert_eq!(sys.model_failures().len(), 1);
+        assert_eq!(sys.model_failures()[0].time, 0.5);
+
+        // 代替値の場合は失敗を記録して最後まで実行する
+        let mut sys = make_system();
+        sys.set_failure_policy(FailurePolicy::Substitute(0.0));
+        sys.try_run().unwrap();
+        assert_eq!(sys.model_failures().len(), 1);
+    }
+
+    #[test]
+    fn run_until_test() {
+        use crate::simcore::sim_model::source_models::RampFunc;
+
+        // y = 2t が1.0を超えた時点で停止する
+        let make_system = || {
+            let ramp = RampFunc::new(vec![SigDef::new("y", "-")], vec![(0.0, 0.0, false, 0.0, 2.0)]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+            connect_models(&ramp, &["y"], &mut rcd, &["y"]).unwrap();
+            let mut sys = SimSystem::new(0.0, 2.0, 0.125);
+            sys.regist_model(ramp);
+            sys.regist_recorder("rcd", rcd);
+            sys
+        };
+
+        let mut sys = make_system();
+        assert_eq!(sys.peek_signal("rcd", "y"), None);
+        let stop = sys.run_until(|_, sys| sys.peek_signal("rcd", "y").is_some_and(|y| y > 1.0)).unwrap();
+        assert_eq!(stop, 0.625);
+        assert!(stop < sys.sim_time().end_time());
+        assert_eq!(sys.run_state(), RunState::Finished);
+        assert_eq!(sys.get_result("rcd").unwrap().get_time().last(), Some(&0.625));
+        assert_eq!(sys.peek_signal("rcd", "y"), Some(1.25));
+        assert_eq!(sys.peek_signal("rcd", "none"), None);
+        assert_eq!(sys.peek_signal("none", "y"), None);
+
+        // 条件を満たさない場合はend_timeまで実行する
+        let mut sys = make_system();
+        let stop = sys.run_until(|time, _| time.time() > 10.0).unwrap();
+        assert_eq!(stop, 2.0);
+
+        // 初期値で条件を満たす場合は初期化直後に停止する
+        let mut sys = make_system();
+        assert_eq!(sys.run_until(|_, _| true).unwrap(), 0.0);
+        assert_eq!(sys.get_result("rcd").unwrap().get_time().len(), 1);
+    }
+
+    #[test]
+    fn manual_step_test() {
+        use crate::simcore::sim_model::de_models::TransFuncModel;
+
+        let make_system = || {
+            let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+            let mut plant = TransFuncModel::first_order(vec![SigDef::new("u", "-")], vec![SigDef::new("y", "-")], 2.0, 0.5, SolverType::RungeKutta).unwrap();
+            connect_models(&input, &["u"], &mut plant, &["u"]).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("y", "-")]).unwrap();
+            connect_models(&plant, &["y"], &mut rcd, &["y"]).unwrap();
+            let mut sys = SimSystem::new(0.0, 1.25, 0.125);
+            sys.regist_model(input);
+            sys.regist_model(plant);
+            sys.regist_recorder("rcd", rcd);
+            sys
+        };
+
+        let mut baseline = make_system();
+        baseline.run();
+        let expected = baseline.get_result("rcd").unwrap();
+
+        let mut sys = make_system();
+        assert!(sys.step().is_err()); // start前は進められない
+        assert!(sys.stop().is_err());
+        sys.start().unwrap();
+        assert_eq!(sys.run_state(), RunState::Running);
+        assert!(sys.start().is_err());
+        for k in 1..=10 {
+            let time = sys.step().unwrap().unwrap();
+            assert_eq!(time.step(), k);
+            assert_eq!(sys.peek_signal("rcd", "y"), expected.get_signal("y").unwrap().get(k as usize).copied());
+        }
+        assert!(sys.step().unwrap().is_none()); // end_timeに達した後は進まない
+        assert!(sys.step().unwrap().is_none());
+        sys.stop().unwrap();
+        assert_eq!(sys.run_state(), RunState::Finished);
+        assert!(sys.step().is_err());
+
+        let rcd = sys.get_result("rcd").unwrap();
+        assert_eq!(rcd.get_time(), expected.get_time());
+        assert_eq!(rcd.get_signal("y"), expected.get_signal("y"));
+    }
+
+    #[test]
+    fn run_state_test() {
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("u", "-")]).unwrap();
+        connect_models(&input, &["u"], &mut rcd, &["u"]).unwrap();
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(input);
+        sys.regist_recorder("rcd", rcd);
+        assert_eq!(sys.run_state(), RunState::Uninitialized);
+        assert!(sys.get_result("rcd").is_err()); // 実行前は結果を参照できない
+
+        sys.try_run().unwrap();
+        assert_eq!(sys.run_state(), RunState::Finished);
+        let len = sys.get_result("rcd").unwrap().get_time().len();
+        assert!(sys.get_result("none").is_err());
+
+        assert!(sys.try_run().is_err()); // resetせずに再実行はできない
+        sys.reset();
+        assert_eq!(sys.run_state(), RunState::Uninitialized);
+        assert!(sys.get_result("rcd").is_err());
+        sys.try_run().unwrap();
+        assert_eq!(sys.get_result("rcd").unwrap().get_time().len(), len); // 再実行でデータが重複しない
+    }
+
+    #[test]
+    fn run_streaming_test() {
+        use crate::simcore::sim_model::source_models::ClockFunc;
+        use std::sync::mpsc::channel;
+
+        let make_system = || {
+            let clock = ClockFunc::new(vec![SigDef::new("t", "s")], 2.0, 0.0).unwrap();
+            let mut rcd = SimRecorder::new(vec![SigDef::new("t2", "s")]).unwrap();
+            connect_models(&clock, &["t"], &mut rcd, &["t2"]).unwrap();
+            let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+            sys.regist_model(clock);
+            sys.regist_recorder("rcd", rcd);
+            sys
+        };
+
+        // 受信側は別スレッドで処理する
+        let (tx, rx) = channel();
+        let consumer = std::thread::spawn(move || rx.iter().collect::<Vec<(f64, Vec<(String, f64)>)>>());
+        let mut sys = make_system();
+        sys.run_streaming(tx).unwrap();
+        let received = consumer.join().unwrap();
+
+        assert_eq!(received.len(), sys.get_result("rcd").unwrap().get_time().len()); // 初期値 + 各ステップ
+        for (t, snapshot) in received.iter() {
+            assert_eq!(snapshot.len(), 1);
+            assert_eq!(snapshot[0].0, "rcd.t2");
+            assert!((snapshot[0].1 - 2.0 * t).abs() < 1e-12);
+        }
+
+        // 受信側が切断された場合はエラーで中断する
+        let (tx, rx) = channel();
+        drop(rx);
+        let mut sys = make_system();
+        assert!(sys.run_streaming(tx).is_err());
+        assert_eq!(sys.run_state(), RunState::Finished);
+    }
+
+    #[test]
+    fn connection_report_test() {
+        let extbus = Bus::try_from(vec![SigDef::new("ext", "-")]).unwrap();
+        let input = ConstantFunc::new(vec![SigDef::new("u", "-")], &[1.0]).unwrap();
+        let mut ssm = SpaceStateModel::new(vec![SigDef::new("u", "-"), SigDef::new("d", "-")], vec![SigDef::new("x", "-")], 1, SolverType::Euler).unwrap();
+        connect_models(&input, &["u"], &mut ssm, &["u"]).unwrap();
+        ssm.interface_in().unwrap().connect_to(&extbus, &["ext"], &["d"]).unwrap();
+        let mut rcd = SimRecorder::new(vec![SigDef::new("x_log", "-"), SigDef::new("y_log", "-")]).unwrap();
+        connect_models(&ssm, &["x"], &mut rcd, &["x_log"]).unwrap();
+
+        assert_eq!(ssm.connection_report(), vec![("u".to_string(), Some("u".to_string())), ("d".to_string(), Some("ext".to_string()))]);
+        assert_eq!(rcd.connection_report()[1], ("y_log".to_string(), None));
+
+        let mut sys = SimSystem::new(0.0, 1.0, 0.1);
+        sys.regist_model(input);
+        sys.regist_model(ssm);
+        sys.regist_recorder("rcd", rcd);
+        assert_eq!(sys.connection_report(), "model1.u <- model0.u\nmodel1.d <- ext (外部)\nrcd.x_log <- model1.x\nrcd.y_log <- 未接続");
+    }
+
+    #[test]
+    fn simtime_reversed_test() {
+        let sim_time = SimTime::new(0.0, 1.0, 0.25).reversed();
+        assert!(sim_time.is_backward());
+        assert_eq!(sim_time.step_num(), 4);
+
+        let times = sim_time.map(|(_s, t)| t).collect::<Vec<f64>>();
+        assert_eq!(times, vec![0.75, 0.5, 0.25, 0.0]);
     }
 
     #[test]